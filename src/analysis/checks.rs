@@ -0,0 +1,294 @@
+use std::fmt::Display;
+
+use crate::{
+    analysis::solve,
+    circuit::{Circuit, SeriesElement},
+    units::format_value,
+};
+
+/// Share of their ratings components should stay below, unless told
+/// otherwise.
+pub const DEFAULT_MARGIN: f64 = 0.8;
+
+/// Rating of a component, see
+/// [`ComponentInfo`](crate::circuit::ComponentInfo).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Rating {
+    /// Maximum dissipated power, in watts.
+    Power,
+    /// Maximum voltage across the component, in volts.
+    Voltage,
+}
+
+/// How far a component goes beyond one of its ratings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// Within the rating, but above the safety margin.
+    Warning,
+    /// Above the rating.
+    Overload,
+}
+
+/// Component flagged by [`check_ratings`].
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct RatingIssue {
+    /// Identifier of the component.
+    pub id: String,
+    /// Rating that is exceeded.
+    pub rating: Rating,
+    /// Power or voltage reached in the circuit, as an absolute value.
+    pub value: f64,
+    /// Rated power or voltage.
+    pub limit: f64,
+    /// Whether the rating itself or only the safety margin is exceeded.
+    pub severity: Severity,
+}
+
+impl RatingIssue {
+    /// Share of the rating reached, 1 at the rating.
+    pub fn load(&self) -> f64 {
+        self.value / self.limit
+    }
+}
+
+/// Design checks of a circuit, as computed by [`check_ratings`].
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct DesignChecks {
+    /// Share of their ratings components should stay below.
+    pub margin: f64,
+    /// Number of components carrying at least one rating.
+    pub rated: usize,
+    /// Components beyond their ratings or safety margin, most loaded first.
+    pub issues: Vec<RatingIssue>,
+}
+
+impl DesignChecks {
+    /// Whether some component exceeds its ratings.
+    pub fn has_overloads(&self) -> bool {
+        self.issues
+            .iter()
+            .any(|issue| issue.severity == Severity::Overload)
+    }
+
+    /// Worst issue of the component with the given identifier.
+    pub fn severity(&self, id: &str) -> Option<Severity> {
+        self.issues
+            .iter()
+            .filter(|issue| issue.id == id)
+            .map(|issue| issue.severity)
+            .max()
+    }
+}
+
+fn issue(
+    id: &str,
+    rating: Rating,
+    value: f64,
+    limit: Option<f64>,
+    margin: f64,
+) -> Option<RatingIssue> {
+    let limit = limit?;
+    let severity = if value > limit {
+        Severity::Overload
+    } else if value > margin * limit {
+        Severity::Warning
+    } else {
+        return None;
+    };
+    Some(RatingIssue {
+        id: String::from(id),
+        rating,
+        value,
+        limit,
+        severity,
+    })
+}
+
+/// Compares the power and voltage of every component carrying ratings with
+/// them, flagging the components beyond `margin` times their ratings.
+///
+/// ```
+/// use circuit_sim::{
+///     analysis::{check_ratings, Severity, DEFAULT_MARGIN},
+///     circuit::{Circuit, FileFormat},
+/// };
+///
+/// let circuit = Circuit::from_format(
+///     r#"{ "version": 2, "design": { "supply": { "voltage": 10 }, "circuit": [
+///         { "type": "resistor", "resistance": 100, "power_rating": "250m" },
+///         { "type": "resistor", "resistance": 100, "power_rating": "1" }
+///     ] } }"#,
+///     FileFormat::Json,
+/// )
+/// .unwrap();
+///
+/// // each resistor dissipates 250mW
+/// let checks = check_ratings(&circuit, DEFAULT_MARGIN);
+/// assert_eq!(checks.severity("R1"), Some(Severity::Warning));
+/// assert_eq!(checks.severity("R2"), None);
+/// ```
+pub fn check_ratings(circuit: &Circuit, margin: f64) -> DesignChecks {
+    let mut checks = DesignChecks {
+        margin,
+        rated: 0,
+        issues: Vec::new(),
+    };
+
+    for solution in solve(circuit).components {
+        let component = match circuit
+            .get_series_at(&solution.path)
+            .and_then(|series| series.get(solution.index))
+        {
+            Some(SeriesElement::Component(component)) => component,
+            _ => continue,
+        };
+        let info = component.get_info();
+        if info.power_rating.is_none() && info.max_voltage.is_none() {
+            continue;
+        }
+
+        let id = solution.id.as_deref().unwrap_or_default();
+        checks.rated += 1;
+        checks.issues.extend(issue(
+            id,
+            Rating::Power,
+            solution.power.abs(),
+            info.power_rating,
+            margin,
+        ));
+        checks.issues.extend(issue(
+            id,
+            Rating::Voltage,
+            solution.voltage.abs(),
+            info.max_voltage,
+            margin,
+        ));
+    }
+
+    checks.issues.sort_by(|a, b| b.load().total_cmp(&a.load()));
+    checks
+}
+
+impl Display for RatingIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (name, unit) = match self.rating {
+            Rating::Power => ("power", "W"),
+            Rating::Voltage => ("voltage", "V"),
+        };
+        let (mark, verb) = match self.severity {
+            Severity::Overload => ("✗", "exceeds"),
+            Severity::Warning => ("!", "is close to"),
+        };
+        write!(
+            f,
+            "{} {}: {} {} {} its {} rating ({:.0}%)",
+            mark,
+            self.id,
+            name,
+            format_value(self.value, unit),
+            verb,
+            format_value(self.limit, unit),
+            100.0 * self.load()
+        )
+    }
+}
+
+impl Display for DesignChecks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Design checks (safety margin {:.0}% of the ratings)",
+            100.0 * self.margin
+        )?;
+        if self.rated == 0 {
+            return writeln!(f, "No component carries a power or voltage rating");
+        }
+        if self.issues.is_empty() {
+            return writeln!(
+                f,
+                "All {} rated components are within the safety margin",
+                self.rated
+            );
+        }
+
+        for issue in &self.issues {
+            writeln!(f, "{}", issue)?;
+        }
+        let count = |severity| {
+            self.issues
+                .iter()
+                .filter(|issue| issue.severity == severity)
+                .count()
+        };
+        writeln!(
+            f,
+            "{} rated components, {} overloads, {} warnings",
+            self.rated,
+            count(Severity::Overload),
+            count(Severity::Warning)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::{CircuitBuilder, ComponentInfo, ElectronicComponent, Resistor};
+
+    fn rated(
+        resistance: f64,
+        power_rating: Option<f64>,
+        max_voltage: Option<f64>,
+    ) -> ElectronicComponent {
+        let mut resistor = Resistor::new(resistance);
+        resistor.set_info(ComponentInfo {
+            power_rating,
+            max_voltage,
+            ..ComponentInfo::default()
+        });
+        ElectronicComponent::Resistor(resistor)
+    }
+
+    #[test]
+    fn flags_overloads_and_warnings() {
+        // 100mA through every resistor
+        let circuit = CircuitBuilder::new()
+            .supply(30.0)
+            .component(rated(100.0, Some(0.5), None))
+            .component(rated(100.0, Some(1.2), Some(11.0)))
+            .component(rated(100.0, None, Some(100.0)))
+            .build();
+
+        let checks = check_ratings(&circuit, 0.8);
+        assert_eq!(checks.rated, 3);
+        assert_eq!(checks.severity("R1"), Some(Severity::Overload));
+        assert_eq!(checks.severity("R2"), Some(Severity::Warning));
+        assert_eq!(checks.severity("R3"), None);
+        assert!(checks.has_overloads());
+
+        let loads: Vec<_> = checks
+            .issues
+            .iter()
+            .map(|i| (i.id.as_str(), i.rating))
+            .collect();
+        assert_eq!(
+            loads,
+            [
+                ("R1", Rating::Power),
+                ("R2", Rating::Voltage),
+                ("R2", Rating::Power)
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_unrated_circuits() {
+        let circuit = Circuit::from_notation("12V: R10 + R20").unwrap();
+        let checks = check_ratings(&circuit, DEFAULT_MARGIN);
+
+        assert!(checks.issues.is_empty());
+        assert!(checks.to_string().contains("No component carries"));
+    }
+}
@@ -0,0 +1,629 @@
+use std::fmt::Display;
+
+use serde_json::{Map, Value};
+
+use crate::{
+    analysis::solve,
+    circuit::{
+        Circuit, Distribution, ElectronicComponent, Series, SeriesElement, REFERENCE_TEMPERATURE,
+    },
+    units::format_value,
+};
+
+/// Value that differs between two circuits.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Delta {
+    /// Value in the first circuit.
+    pub before: f64,
+    /// Value in the second circuit.
+    pub after: f64,
+}
+
+impl Delta {
+    /// Difference between the two values.
+    pub fn change(&self) -> f64 {
+        self.after - self.before
+    }
+}
+
+/// Optional detail that differs between two circuits.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change<T> {
+    /// Detail in the first circuit.
+    pub before: Option<T>,
+    /// Detail in the second circuit.
+    pub after: Option<T>,
+}
+
+/// Free-form text that differs between two circuits.
+pub type TextChange = Change<String>;
+
+/// Component found in only one of the circuits.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct ComponentEntry {
+    /// Identifier of the component.
+    pub id: String,
+    /// Name of the kind of component, such as "Resistor".
+    pub name: &'static str,
+    /// Main value of the component and its unit, see
+    /// [`ElectronicComponent::value`].
+    pub value: (f64, &'static str),
+    /// Nominal resistance of the component, in ohms, before the ambient
+    /// temperature.
+    pub resistance: f64,
+}
+
+/// Changes of a component found in both circuits.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[non_exhaustive]
+pub struct ComponentDiff {
+    /// Identifier of the component.
+    pub id: String,
+    /// Change of the kind of component, such as "Resistor".
+    pub name: Option<Change<&'static str>>,
+    /// Change of nominal resistance, in ohms; a change of ambient
+    /// temperature only shows in the results.
+    pub resistance: Option<Delta>,
+    /// Changes of the other parameters of plugin components, such as the
+    /// forward voltage of LEDs, by name.
+    pub parameters: Vec<(String, Change<Value>)>,
+    /// Change of label.
+    pub label: Option<TextChange>,
+    /// Change of notes.
+    pub notes: Option<TextChange>,
+    /// Change of the part number of the catalog part.
+    pub part: Option<TextChange>,
+    /// Change of tolerance, in percent.
+    pub tolerance: Option<Change<f64>>,
+    /// Change of the distribution within the tolerance.
+    pub distribution: Option<Change<Distribution>>,
+    /// Change of temperature coefficient, in ppm/°C.
+    pub temperature_coefficient: Option<Change<f64>>,
+    /// Change of the power rating, in watts.
+    pub power_rating: Option<Change<f64>>,
+    /// Change of the maximum voltage, in volts.
+    pub max_voltage: Option<Change<f64>>,
+    /// Change of the voltage across the component, in volts.
+    pub voltage: Option<Delta>,
+    /// Change of the current through the component, in amperes.
+    pub current: Option<Delta>,
+    /// Change of the dissipated power, in watts.
+    pub power: Option<Delta>,
+}
+
+impl ComponentDiff {
+    /// Whether the component itself changed, as opposed to its results only.
+    pub fn is_edited(&self) -> bool {
+        self.name.is_some()
+            || self.resistance.is_some()
+            || !self.parameters.is_empty()
+            || self.label.is_some()
+            || self.notes.is_some()
+            || self.part.is_some()
+            || self.tolerance.is_some()
+            || self.distribution.is_some()
+            || self.temperature_coefficient.is_some()
+            || self.power_rating.is_some()
+            || self.max_voltage.is_some()
+    }
+}
+
+/// Structural and result differences between two circuits, as computed by
+/// [`diff`].
+///
+/// Components are matched by identifier.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[non_exhaustive]
+pub struct CircuitDiff {
+    /// Change of the power supply voltage, in volts.
+    pub voltage: Option<Delta>,
+    /// Change of the ambient temperature, in °C, [`REFERENCE_TEMPERATURE`]
+    /// standing for a circuit without one.
+    pub ambient_temperature: Option<Delta>,
+    /// Components only in the second circuit.
+    pub added: Vec<ComponentEntry>,
+    /// Components only in the first circuit.
+    pub removed: Vec<ComponentEntry>,
+    /// Components found in both circuits whose details or results changed.
+    pub changed: Vec<ComponentDiff>,
+    /// Whether the components found in both circuits are connected
+    /// differently.
+    pub topology_changed: bool,
+    /// Change of the equivalent resistance, in ohms.
+    pub total_resistance: Option<Delta>,
+    /// Change of the current delivered by the power supply, in amperes.
+    pub current: Option<Delta>,
+}
+
+impl CircuitDiff {
+    /// Whether the two circuits are the same.
+    pub fn is_empty(&self) -> bool {
+        *self == CircuitDiff::default()
+    }
+}
+
+// Floating point noise of the solver is not a change
+fn delta(before: f64, after: f64) -> Option<Delta> {
+    let tolerance = 1e-12 * before.abs().max(after.abs());
+    ((before - after).abs() > tolerance).then_some(Delta { before, after })
+}
+
+fn change<T: Clone + PartialEq>(before: &Option<T>, after: &Option<T>) -> Option<Change<T>> {
+    (before != after).then(|| Change {
+        before: before.clone(),
+        after: after.clone(),
+    })
+}
+
+// Parameters of plugin components, but their resistance, compared on its own
+fn parameters(component: &ElectronicComponent) -> Map<String, Value> {
+    match component {
+        ElectronicComponent::Resistor(_) => Map::new(),
+        ElectronicComponent::Plugin(component) => {
+            let mut parameters = component.get_model().parameters();
+            parameters.remove("resistance");
+            parameters
+        }
+    }
+}
+
+fn parameter_changes(
+    before: &ElectronicComponent,
+    after: &ElectronicComponent,
+) -> Vec<(String, Change<Value>)> {
+    let (before, after) = (parameters(before), parameters(after));
+    let mut names: Vec<&String> = before.keys().chain(after.keys()).collect();
+    names.sort();
+    names.dedup();
+    names
+        .into_iter()
+        .filter_map(|name| {
+            change(&before.get(name).cloned(), &after.get(name).cloned())
+                .map(|change| (name.clone(), change))
+        })
+        .collect()
+}
+
+// Connections of the components kept by `keep`, replaced by their
+// identifiers, such as "R1 + (R2 || R3) + X1[X1.R1 + X1.R2]"
+fn topology(series: &Series, keep: &impl Fn(&str) -> bool) -> String {
+    series
+        .iter()
+        .filter_map(|element| match element {
+            SeriesElement::Component(component) => {
+                component.get_id().filter(|id| keep(id)).map(String::from)
+            }
+            SeriesElement::Parallel(branches) => Some(format!(
+                "({})",
+                branches
+                    .iter()
+                    .map(|branch| topology(branch, keep))
+                    .collect::<Vec<String>>()
+                    .join(" || ")
+            )),
+            SeriesElement::Subcircuit(subcircuit) => Some(format!(
+                "{}[{}]",
+                subcircuit
+                    .get_id()
+                    .filter(|id| keep(id))
+                    .unwrap_or_default(),
+                topology(subcircuit.get_series(), keep)
+            )),
+        })
+        .collect::<Vec<String>>()
+        .join(" + ")
+}
+
+// Resistance written in the circuit, before the ambient temperature
+fn nominal_resistance(component: &ElectronicComponent) -> f64 {
+    match component {
+        ElectronicComponent::Resistor(resistor) => resistor.get_nominal_resistance(),
+        ElectronicComponent::Plugin(component) => component.get_model().resistance(),
+    }
+}
+
+fn entry(circuit: &Circuit, id: String, resistance: f64) -> ComponentEntry {
+    let component = circuit.find_component(&id);
+    ComponentEntry {
+        name: component.map_or("Component", ElectronicComponent::name),
+        value: component.map_or((resistance, "Ω"), ElectronicComponent::value),
+        resistance: component.map_or(resistance, nominal_resistance),
+        id,
+    }
+}
+
+/// Compares two revisions of a circuit.
+///
+/// ```
+/// use circuit_sim::{analysis::diff, circuit::Circuit};
+///
+/// let before = Circuit::from_notation("12V: R10 + R20").unwrap();
+/// let after = Circuit::from_notation("12V: R10 + R30 + R20").unwrap();
+/// let diff = diff(&before, &after);
+///
+/// // Components are matched by identifier: R2 went from 20Ω to 30Ω
+/// assert_eq!(diff.added[0].id, "R3");
+/// assert_eq!(diff.changed[1].id, "R2");
+/// assert_eq!(diff.changed[1].resistance.unwrap().change(), 10.0);
+/// ```
+pub fn diff(before: &Circuit, after: &Circuit) -> CircuitDiff {
+    let (old, new) = (solve(before), solve(after));
+    let mut result = CircuitDiff {
+        voltage: delta(old.voltage, new.voltage),
+        ambient_temperature: delta(
+            before
+                .get_ambient_temperature()
+                .unwrap_or(REFERENCE_TEMPERATURE),
+            after
+                .get_ambient_temperature()
+                .unwrap_or(REFERENCE_TEMPERATURE),
+        ),
+        total_resistance: delta(old.total_resistance, new.total_resistance),
+        current: delta(old.current, new.current),
+        ..CircuitDiff::default()
+    };
+
+    for solution in &old.components {
+        let id = solution.id.clone().unwrap_or_default();
+        let Some(updated) = new.component(&id) else {
+            result.removed.push(entry(before, id, solution.resistance));
+            continue;
+        };
+
+        let (Some(old_component), Some(new_component)) =
+            (before.find_component(&id), after.find_component(&id))
+        else {
+            continue;
+        };
+        let (old_info, new_info) = (old_component.get_info(), new_component.get_info());
+        let component = ComponentDiff {
+            id,
+            name: change(&Some(old_component.name()), &Some(new_component.name())),
+            resistance: delta(
+                nominal_resistance(old_component),
+                nominal_resistance(new_component),
+            ),
+            parameters: parameter_changes(old_component, new_component),
+            label: change(&old_info.label, &new_info.label),
+            notes: change(&old_info.notes, &new_info.notes),
+            part: change(&old_info.part, &new_info.part),
+            tolerance: change(&old_info.tolerance, &new_info.tolerance),
+            distribution: change(&old_info.distribution, &new_info.distribution),
+            temperature_coefficient: change(
+                &old_info.temperature_coefficient,
+                &new_info.temperature_coefficient,
+            ),
+            power_rating: change(&old_info.power_rating, &new_info.power_rating),
+            max_voltage: change(&old_info.max_voltage, &new_info.max_voltage),
+            voltage: delta(solution.voltage, updated.voltage),
+            current: delta(solution.current, updated.current),
+            power: delta(solution.power, updated.power),
+        };
+        if component.is_edited() || component.current.is_some() || component.voltage.is_some() {
+            result.changed.push(component);
+        }
+    }
+
+    // Added and removed components are reported on their own
+    let common = |id: &str| old.component(id).is_some() && new.component(id).is_some();
+    result.topology_changed =
+        topology(before.get_series(), &common) != topology(after.get_series(), &common);
+
+    for solution in &new.components {
+        let id = solution.id.clone().unwrap_or_default();
+        if old.component(&id).is_none() {
+            result.added.push(entry(after, id, solution.resistance));
+        }
+    }
+
+    result
+}
+
+fn write_delta(
+    f: &mut std::fmt::Formatter<'_>,
+    name: &str,
+    delta: &Delta,
+    unit: &str,
+) -> std::fmt::Result {
+    let sign = if delta.change() > 0.0 { "+" } else { "" };
+    write!(
+        f,
+        "{} {} → {} ({}{})",
+        name,
+        format_value(delta.before, unit),
+        format_value(delta.after, unit),
+        sign,
+        format_value(delta.change(), unit)
+    )
+}
+
+fn write_change<T>(
+    f: &mut std::fmt::Formatter<'_>,
+    id: &str,
+    name: &str,
+    change: &Change<T>,
+    show: impl Fn(&T) -> String,
+) -> std::fmt::Result {
+    let show = |detail: &Option<T>| detail.as_ref().map_or(String::from("none"), &show);
+    writeln!(
+        f,
+        "~ {} {} {} → {}",
+        id,
+        name,
+        show(&change.before),
+        show(&change.after)
+    )
+}
+
+fn quoted(text: &String) -> String {
+    format!("\"{}\"", text)
+}
+
+fn show_parameter(value: &Value) -> String {
+    value
+        .as_f64()
+        .map_or(value.to_string(), |value| format_value(value, ""))
+}
+
+impl Display for CircuitDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "No differences");
+        }
+
+        if let Some(voltage) = &self.voltage {
+            write_delta(f, "Power supply voltage", voltage, "V")?;
+            writeln!(f)?;
+        }
+        if let Some(temperature) = &self.ambient_temperature {
+            write_delta(f, "Ambient temperature", temperature, "°C")?;
+            writeln!(f)?;
+        }
+        for component in &self.removed {
+            writeln!(
+                f,
+                "- {} {} {}",
+                component.id,
+                component.name,
+                format_value(component.value.0, component.value.1)
+            )?;
+        }
+        for component in &self.added {
+            writeln!(
+                f,
+                "+ {} {} {}",
+                component.id,
+                component.name,
+                format_value(component.value.0, component.value.1)
+            )?;
+        }
+        for component in self
+            .changed
+            .iter()
+            .filter(|component| component.is_edited())
+        {
+            let id = component.id.as_str();
+            if let Some(name) = &component.name {
+                write_change(f, id, "kind", name, |name| name.to_string())?;
+            }
+            if let Some(resistance) = &component.resistance {
+                write!(f, "~ {} ", id)?;
+                write_delta(f, "resistance", resistance, "Ω")?;
+                writeln!(f)?;
+            }
+            for (name, parameter) in &component.parameters {
+                write_change(f, id, name, parameter, show_parameter)?;
+            }
+            for (name, text) in [
+                ("label", &component.label),
+                ("notes", &component.notes),
+                ("part", &component.part),
+            ] {
+                if let Some(text) = text {
+                    write_change(f, id, name, text, quoted)?;
+                }
+            }
+            if let Some(tolerance) = &component.tolerance {
+                write_change(f, id, "tolerance", tolerance, |tolerance| {
+                    format!("{}%", format_value(*tolerance, ""))
+                })?;
+            }
+            if let Some(distribution) = &component.distribution {
+                write_change(f, id, "distribution", distribution, Distribution::to_string)?;
+            }
+            for (name, value, unit) in [
+                (
+                    "temperature coefficient",
+                    &component.temperature_coefficient,
+                    "ppm/°C",
+                ),
+                ("power rating", &component.power_rating, "W"),
+                ("maximum voltage", &component.max_voltage, "V"),
+            ] {
+                if let Some(value) = value {
+                    write_change(f, id, name, value, |value| format_value(*value, unit))?;
+                }
+            }
+        }
+        if self.topology_changed {
+            writeln!(f, "Connections changed")?;
+        }
+
+        let results = self
+            .changed
+            .iter()
+            .filter(|component| component.voltage.is_some() || component.current.is_some());
+        if self.total_resistance.is_none()
+            && self.current.is_none()
+            && results.clone().next().is_none()
+        {
+            return Ok(());
+        }
+
+        writeln!(f, "Results:")?;
+        if let Some(total_resistance) = &self.total_resistance {
+            write!(f, "  ")?;
+            write_delta(f, "Total resistance", total_resistance, "Ω")?;
+            writeln!(f)?;
+        }
+        if let Some(current) = &self.current {
+            write!(f, "  ")?;
+            write_delta(f, "Current", current, "A")?;
+            writeln!(f)?;
+        }
+        for component in results {
+            for (name, delta, unit) in [
+                ("voltage", &component.voltage, "V"),
+                ("current", &component.current, "A"),
+                ("power", &component.power, "W"),
+            ] {
+                if let Some(delta) = delta {
+                    write!(f, "  {} ", component.id)?;
+                    write_delta(f, name, delta, unit)?;
+                    writeln!(f)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::{
+        CircuitBuilder, ComponentInfo, ElectronicComponent, Led, PluginComponent, Resistor,
+    };
+
+    fn circuit(notation: &str) -> Circuit {
+        Circuit::from_notation(notation).unwrap()
+    }
+
+    #[test]
+    fn identical_circuits_have_no_differences() {
+        let notation = "12V: R10 + (R20 || R30)";
+        assert!(diff(&circuit(notation), &circuit(notation)).is_empty());
+        assert_eq!(circuit(notation), circuit(notation).clone());
+    }
+
+    #[test]
+    fn reports_component_and_result_changes() {
+        let before = circuit("12V: R10 + (R20 || R40)");
+        let mut after = circuit("12V: R10 + (R20 || R30) + R5");
+        let mut info = after.find_component("R1").unwrap().get_info().clone();
+        info.label = Some(String::from("Sense"));
+        after.set_component_info(&[], 0, info).unwrap();
+
+        let diff = diff(&before, &after);
+
+        assert_eq!(diff.voltage, None);
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.added[0].id, "R4");
+        assert!(diff.removed.is_empty());
+        assert!(!diff.topology_changed);
+
+        let r1 = diff.changed.iter().find(|c| c.id == "R1").unwrap();
+        assert_eq!(r1.resistance, None);
+        assert_eq!(r1.label.as_ref().unwrap().after.as_deref(), Some("Sense"));
+        assert!(r1.current.is_some());
+
+        let r3 = diff.changed.iter().find(|c| c.id == "R3").unwrap();
+        assert_eq!(
+            r3.resistance,
+            Some(Delta {
+                before: 40.0,
+                after: 30.0
+            })
+        );
+    }
+
+    #[test]
+    fn compares_nominal_resistances_across_temperatures() {
+        let before = circuit("12V: R10 + R20");
+        let mut after = before.clone();
+        after.set_ambient_temperature(Some(125.0));
+        let mut info = after.find_component("R1").unwrap().get_info().clone();
+        info.temperature_coefficient = Some(1000.0);
+        after.set_component_info(&[], 0, info).unwrap();
+
+        let diff = diff(&before, &after);
+
+        assert_eq!(
+            diff.ambient_temperature,
+            Some(Delta {
+                before: 25.0,
+                after: 125.0
+            })
+        );
+        let r1 = diff.changed.iter().find(|c| c.id == "R1").unwrap();
+        assert_eq!(r1.resistance, None);
+        assert_eq!(
+            r1.temperature_coefficient.as_ref().unwrap().after,
+            Some(1000.0)
+        );
+        assert!(r1.voltage.is_some());
+    }
+
+    #[test]
+    fn reports_ratings_and_plugin_parameters() {
+        let led_circuit = |forward_voltage| {
+            CircuitBuilder::new()
+                .supply(5.0)
+                .resistor(100.0)
+                .component(
+                    PluginComponent::new(Led {
+                        forward_voltage,
+                        resistance: 10.0,
+                    })
+                    .into(),
+                )
+                .build()
+        };
+        let before = led_circuit(2.0);
+        let mut after = led_circuit(1.8);
+        let mut info = after.find_component("R1").unwrap().get_info().clone();
+        info.tolerance = Some(1.0);
+        info.power_rating = Some(0.25);
+        after.set_component_info(&[], 0, info).unwrap();
+
+        let diff = diff(&before, &after);
+
+        let r1 = diff.changed.iter().find(|c| c.id == "R1").unwrap();
+        assert_eq!(r1.tolerance.as_ref().unwrap().after, Some(1.0));
+        assert_eq!(r1.power_rating.as_ref().unwrap().after, Some(0.25));
+        assert_eq!(r1.max_voltage, None);
+
+        let d1 = diff.changed.iter().find(|c| c.id == "D1").unwrap();
+        assert_eq!(d1.name, None);
+        assert_eq!(d1.resistance, None);
+        assert_eq!(d1.parameters.len(), 1);
+        assert_eq!(d1.parameters[0].0, "forward_voltage");
+        assert!(diff.to_string().contains("~ D1 forward_voltage 2 → 1.8\n"));
+        assert!(diff.to_string().contains("~ R1 tolerance none → 1%\n"));
+    }
+
+    #[test]
+    fn reports_reconnected_components() {
+        let resistor = |id: &str, resistance| {
+            let mut resistor = Resistor::new(resistance);
+            resistor.set_info(ComponentInfo {
+                id: Some(String::from(id)),
+                ..ComponentInfo::default()
+            });
+            ElectronicComponent::Resistor(resistor)
+        };
+        let before = circuit("5V: R1 + R2 + R3");
+        let after = CircuitBuilder::new()
+            .supply(5.0)
+            .component(resistor("R1", 1.0))
+            .component(resistor("R3", 3.0))
+            .component(resistor("R2", 2.0))
+            .build();
+
+        let diff = diff(&before, &after);
+        assert!(diff.topology_changed);
+        assert!(diff.changed.is_empty());
+        assert!(diff.total_resistance.is_none());
+    }
+}
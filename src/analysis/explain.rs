@@ -0,0 +1,582 @@
+use std::fmt::Display;
+
+use crate::{
+    circuit::{
+        calculate_parallel_resistance, calculate_total_resistance, series_equivalent, Circuit,
+        ElectronicComponentTrait, SeriesElement,
+    },
+    units::format_value,
+};
+
+/// Reasoning behind an [`ExplanationStep`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StepKind {
+    /// Equivalent resistance of elements in series.
+    Series,
+    /// Equivalent resistance of parallel branches.
+    Parallel,
+    /// Current from a voltage and a resistance, or voltage from a current.
+    OhmsLaw,
+    /// Share of the voltage across a series taken by one of its elements.
+    VoltageDivider,
+    /// Voltage across an open element, such as a blocking LED, left by the
+    /// other elements of its series.
+    Open,
+}
+
+/// One step of an [`Explanation`].
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct ExplanationStep {
+    /// Reasoning applied.
+    pub kind: StepKind,
+    /// What the step computes, such as `R2 and R3 in parallel`.
+    pub description: String,
+    /// Formula with its values, such as `Req1 = R2 || R3 = 1 / (1/2kΩ + 1/2kΩ) = 1kΩ`.
+    pub formula: String,
+}
+
+impl ExplanationStep {
+    /// Whether the step reduces the circuit to its equivalent resistance,
+    /// rather than computing voltages and currents.
+    pub fn is_reduction(&self) -> bool {
+        matches!(self.kind, StepKind::Series | StepKind::Parallel)
+    }
+}
+
+/// Steps solving a circuit by hand, as computed by [`explain`].
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct Explanation {
+    /// Compact notation of the explained circuit.
+    pub notation: String,
+    /// Every step, the reduction to the equivalent resistance first.
+    pub steps: Vec<ExplanationStep>,
+}
+
+// Element of the circuit reduced to a resistance and an electromotive force
+struct Reduced {
+    name: String,
+    resistance: f64,
+    force: f64,
+    parts: Parts,
+}
+
+enum Parts {
+    Component,
+    Series(Vec<Reduced>),
+    Parallel(Vec<Reduced>),
+}
+
+#[derive(Default)]
+struct Explainer {
+    steps: Vec<ExplanationStep>,
+    equivalents: usize,
+}
+
+// Joins names like "R1, R2 and R3"
+fn enumerate(names: &[&str]) -> String {
+    match names {
+        [] => String::new(),
+        [name] => String::from(*name),
+        [names @ .., last] => format!("{} and {}", names.join(", "), last),
+    }
+}
+
+// Resistance in a formula, "open" for the infinite resistance of blocking
+// components
+fn ohms(resistance: f64) -> String {
+    match resistance.is_infinite() {
+        true => String::from("open"),
+        false => format_value(resistance, "Ω"),
+    }
+}
+
+// "+ 1.5V" or "- 2V", to add an electromotive force to a formula
+fn signed(value: f64, unit: &str) -> String {
+    let sign = if value < 0.0 { "-" } else { "+" };
+    format!("{} {}", sign, format_value(value.abs(), unit))
+}
+
+impl Explainer {
+    fn push(&mut self, kind: StepKind, description: String, formula: String) {
+        self.steps.push(ExplanationStep {
+            kind,
+            description,
+            formula,
+        });
+    }
+
+    fn next_name(&mut self) -> String {
+        self.equivalents += 1;
+        format!("Req{}", self.equivalents)
+    }
+
+    // Reduces a series to a single resistance, naming it `name` or, when it
+    // holds several elements, a new equivalent
+    fn reduce_series(&mut self, elements: &[SeriesElement], name: Option<String>) -> Reduced {
+        let mut parts: Vec<Reduced> = elements
+            .iter()
+            .map(|element| self.reduce_element(element))
+            .collect();
+        if parts.len() == 1 && name.is_none() {
+            return parts.remove(0);
+        }
+
+        let name = name.unwrap_or_else(|| self.next_name());
+        let resistance = calculate_total_resistance(elements);
+        if parts.is_empty() {
+            // like the solver, an empty branch or subcircuit is a plain wire
+            self.push(
+                StepKind::Series,
+                format!("No components in {}", name),
+                format!("{} = {}", name, format_value(resistance, "Ω")),
+            );
+        } else {
+            let names: Vec<&str> = parts.iter().map(|part| part.name.as_str()).collect();
+            let values: Vec<String> = parts.iter().map(|part| ohms(part.resistance)).collect();
+            self.push(
+                StepKind::Series,
+                format!("{} in series", enumerate(&names)),
+                format!(
+                    "{} = {} = {} = {}",
+                    name,
+                    names.join(" + "),
+                    values.join(" + "),
+                    ohms(resistance)
+                ),
+            );
+        }
+        Reduced {
+            name,
+            resistance,
+            force: series_equivalent(elements).1,
+            parts: Parts::Series(parts),
+        }
+    }
+
+    fn reduce_element(&mut self, element: &SeriesElement) -> Reduced {
+        match element {
+            SeriesElement::Component(component) => Reduced {
+                name: String::from(component.get_id().unwrap_or(component.name())),
+                resistance: component.get_resistance(),
+                force: component.get_electromotive_force(),
+                parts: Parts::Component,
+            },
+            SeriesElement::Parallel(branches) => {
+                let parts: Vec<Reduced> = branches
+                    .iter()
+                    .map(|branch| self.reduce_series(branch, None))
+                    .collect();
+                let name = self.next_name();
+                let resistance = calculate_parallel_resistance(branches);
+                let names: Vec<&str> = parts.iter().map(|part| part.name.as_str()).collect();
+                let inverses: Vec<String> = parts
+                    .iter()
+                    .map(|part| format!("1/{}", ohms(part.resistance)))
+                    .collect();
+                self.push(
+                    StepKind::Parallel,
+                    format!("{} in parallel", enumerate(&names)),
+                    format!(
+                        "{} = {} = 1 / ({}) = {}",
+                        name,
+                        names.join(" || "),
+                        inverses.join(" + "),
+                        ohms(resistance)
+                    ),
+                );
+                Reduced {
+                    name,
+                    resistance,
+                    force: series_equivalent(std::slice::from_ref(element)).1,
+                    parts: Parts::Parallel(parts),
+                }
+            }
+            SeriesElement::Subcircuit(subcircuit) => self.reduce_series(
+                subcircuit.get_series(),
+                Some(String::from(
+                    subcircuit.get_id().unwrap_or(subcircuit.get_definition()),
+                )),
+            ),
+        }
+    }
+
+    // Spreads the voltage across `reduced` and the current through it over
+    // its parts
+    fn distribute(&mut self, reduced: &Reduced, voltage: f64, current: f64) {
+        match &reduced.parts {
+            Parts::Component => {}
+            Parts::Series(parts) => {
+                // open elements share the voltage left by the others
+                let closed: Vec<f64> = parts
+                    .iter()
+                    .filter(|part| part.resistance.is_finite())
+                    .map(|part| current * part.resistance - part.force)
+                    .collect();
+                let open = parts.len() - closed.len();
+                let closed_voltage: f64 = closed.iter().sum();
+
+                for part in parts {
+                    if part.resistance.is_infinite() {
+                        let part_voltage = (voltage - closed_voltage) / open as f64;
+                        let left = format!(
+                            "{} - {}",
+                            format_value(voltage, "V"),
+                            format_value(closed_voltage, "V")
+                        );
+                        self.push(
+                            StepKind::Open,
+                            format!("{} is open and takes the voltage left", part.name),
+                            format!(
+                                "V({}) = {} = {}",
+                                part.name,
+                                match open {
+                                    1 => left,
+                                    open => format!("({}) / {}", left, open),
+                                },
+                                format_value(part_voltage, "V")
+                            ),
+                        );
+                        self.distribute(part, part_voltage, current);
+                        continue;
+                    }
+
+                    let part_voltage = current * part.resistance - part.force;
+                    // without sources, every element takes its share of the
+                    // voltage in proportion to its resistance
+                    if reduced.force == 0.0
+                        && reduced.resistance != 0.0
+                        && reduced.resistance.is_finite()
+                    {
+                        self.push(
+                            StepKind::VoltageDivider,
+                            format!("Voltage divider across {}", part.name),
+                            format!(
+                                "V({}) = {} × {} / {} = {}",
+                                part.name,
+                                format_value(voltage, "V"),
+                                format_value(part.resistance, "Ω"),
+                                ohms(reduced.resistance),
+                                format_value(part_voltage, "V")
+                            ),
+                        );
+                    } else {
+                        let force = if part.force == 0.0 {
+                            String::new()
+                        } else {
+                            format!(" {}", signed(-part.force, "V"))
+                        };
+                        self.push(
+                            StepKind::OhmsLaw,
+                            format!("Ohm's law for {}", part.name),
+                            format!(
+                                "V({}) = {} × {}{} = {}",
+                                part.name,
+                                format_value(current, "A"),
+                                format_value(part.resistance, "Ω"),
+                                force,
+                                format_value(part_voltage, "V")
+                            ),
+                        );
+                    }
+                    self.distribute(part, part_voltage, current);
+                }
+            }
+            Parts::Parallel(parts) => {
+                for (branch, part) in parts.iter().enumerate() {
+                    let part_current = (voltage + part.force) / part.resistance;
+                    let numerator = if part.force == 0.0 {
+                        format_value(voltage, "V")
+                    } else {
+                        format!(
+                            "({} {})",
+                            format_value(voltage, "V"),
+                            signed(part.force, "V")
+                        )
+                    };
+                    self.push(
+                        StepKind::OhmsLaw,
+                        format!("Ohm's law for branch {} of {}", branch, reduced.name),
+                        format!(
+                            "I({}) = {} / {} = {}",
+                            part.name,
+                            numerator,
+                            ohms(part.resistance),
+                            format_value(part_current, "A")
+                        ),
+                    );
+                    self.distribute(part, voltage, part_current);
+                }
+            }
+        }
+    }
+}
+
+/// Explains how `circuit` is solved: the reduction of its series and
+/// parallel groups to a single equivalent resistance, the current drawn from
+/// the power supply by Ohm's law, then the voltage dividers and branch
+/// currents giving the voltage across every element.
+///
+/// Groups of several elements are named `Req1`, `Req2`… and the whole circuit
+/// `Rtotal`; series holding a source use Ohm's law instead of a divider.
+///
+/// ```
+/// use circuit_sim::{analysis::explain, circuit::Circuit};
+///
+/// let explanation = explain(&Circuit::from_notation("12V: R1k + (R2k || R2k)").unwrap());
+/// let formulas: Vec<&str> = explanation.steps.iter().map(|step| step.formula.as_str()).collect();
+/// assert_eq!(formulas[0], "Req1 = R2 || R3 = 1 / (1/2kΩ + 1/2kΩ) = 1kΩ");
+/// assert_eq!(formulas[1], "Rtotal = R1 + Req1 = 1kΩ + 1kΩ = 2kΩ");
+/// assert_eq!(formulas[2], "I = V1 / Rtotal = 12V / 2kΩ = 6mA");
+/// assert_eq!(formulas[3], "V(R1) = 12V × 1kΩ / 2kΩ = 6V");
+/// ```
+pub fn explain(circuit: &Circuit) -> Explanation {
+    let mut explainer = Explainer::default();
+    let supply = circuit.get_power_supply();
+    let name = supply.get_id().unwrap_or("V");
+    if circuit.get_series().is_empty() {
+        explainer.push(
+            StepKind::Series,
+            String::from("No components"),
+            format!(
+                "Rtotal = open circuit, nothing is connected across {}",
+                name
+            ),
+        );
+        return Explanation {
+            notation: circuit.to_notation(),
+            steps: explainer.steps,
+        };
+    }
+
+    let total = explainer.reduce_series(circuit.get_series(), Some(String::from("Rtotal")));
+
+    let voltage = supply.get_voltage();
+    let current = (voltage + total.force) / total.resistance;
+    let formula = if total.force == 0.0 {
+        format!(
+            "I = {} / Rtotal = {} / {} = {}",
+            name,
+            format_value(voltage, "V"),
+            ohms(total.resistance),
+            format_value(current, "A")
+        )
+    } else {
+        format!(
+            "I = ({} + E) / Rtotal = ({} {}) / {} = {}",
+            name,
+            format_value(voltage, "V"),
+            signed(total.force, "V"),
+            ohms(total.resistance),
+            format_value(current, "A")
+        )
+    };
+    explainer.push(
+        StepKind::OhmsLaw,
+        format!("Ohm's law for the power supply {}", name),
+        formula,
+    );
+    explainer.distribute(&total, voltage, current);
+
+    Explanation {
+        notation: circuit.to_notation(),
+        steps: explainer.steps,
+    }
+}
+
+impl Explanation {
+    // Steps with their numbers, the reduction and the solving apart
+    fn sections(&self) -> [(&'static str, Vec<(usize, &ExplanationStep)>); 2] {
+        let (reduction, solving) = self
+            .steps
+            .iter()
+            .enumerate()
+            .map(|(index, step)| (index + 1, step))
+            .partition(|(_, step)| step.is_reduction());
+        [
+            ("Equivalent resistance", reduction),
+            ("Voltages and currents", solving),
+        ]
+    }
+
+    /// Markdown document listing the numbered steps, for reviews and
+    /// teaching material.
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = format!("# Step-by-step solution\n\nCircuit: `{}`\n", self.notation);
+        for (title, steps) in self.sections() {
+            if steps.is_empty() {
+                continue;
+            }
+            markdown.push_str(&format!("\n## {}\n\n", title));
+            for (number, step) in steps {
+                markdown.push_str(&format!(
+                    "{}. {}: `{}`\n",
+                    number, step.description, step.formula
+                ));
+            }
+        }
+        markdown
+    }
+}
+
+impl Display for ExplanationStep {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.description, self.formula)
+    }
+}
+
+impl Display for Explanation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Step-by-step solution of {}", self.notation)?;
+        for (title, steps) in self.sections() {
+            if steps.is_empty() {
+                continue;
+            }
+            writeln!(f, "{}", title)?;
+            for (number, step) in steps {
+                writeln!(f, "{:>3}. {}", number, step)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        analysis::solve,
+        circuit::{CircuitBuilder, FileFormat},
+    };
+
+    // Value given by the step of a quantity such as `V(R1)`
+    fn stated(explanation: &Explanation, quantity: &str) -> Option<f64> {
+        let prefix = format!("{} = ", quantity);
+        let step = explanation
+            .steps
+            .iter()
+            .find(|step| step.formula.starts_with(&prefix))?;
+        let (_, value) = step.formula.rsplit_once(" = ")?;
+        crate::units::parse_value(value).ok()
+    }
+
+    #[test]
+    fn reduces_nested_groups() {
+        let circuit = Circuit::from_notation("12V: R1k + (R2k || (R1k + R1k))").unwrap();
+        let explanation = explain(&circuit);
+        let kinds: Vec<StepKind> = explanation.steps.iter().map(|step| step.kind).collect();
+        assert_eq!(
+            kinds[..4],
+            [
+                StepKind::Series,
+                StepKind::Parallel,
+                StepKind::Series,
+                StepKind::OhmsLaw
+            ]
+        );
+        assert_eq!(
+            explanation.steps[0].formula,
+            "Req1 = R3 + R4 = 1kΩ + 1kΩ = 2kΩ"
+        );
+        assert_eq!(explanation.steps[1].description, "R2 and Req1 in parallel");
+        assert!(explanation
+            .steps
+            .iter()
+            .any(|step| step.formula == "I(Req1) = 6V / 2kΩ = 3mA"));
+
+        // R2 alone in its branch takes the voltage of the parallel
+        let solution = solve(&circuit);
+        for component in &solution.components {
+            let id = component.id.as_deref().unwrap();
+            match stated(&explanation, &format!("V({})", id)) {
+                Some(voltage) => assert!((voltage - component.voltage).abs() < 1e-2, "{}", id),
+                None => {
+                    let current = stated(&explanation, &format!("I({})", id)).unwrap();
+                    assert!((current - component.current).abs() < 1e-5, "{}", id);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn uses_ohms_law_around_sources() {
+        let circuit = Circuit::from_format(
+            r#"{ "version": 2, "design": { "supply": { "voltage": 12 }, "circuit": [
+                { "type": "resistor", "resistance": "480" },
+                { "type": "led", "forward_voltage": "2V", "resistance": 20 }
+            ] } }"#,
+            FileFormat::Json,
+        )
+        .unwrap();
+        let explanation = explain(&circuit);
+        assert!(explanation
+            .steps
+            .iter()
+            .all(|step| step.kind != StepKind::VoltageDivider));
+        assert!(explanation
+            .steps
+            .iter()
+            .any(|step| step.formula == "I = (V1 + E) / Rtotal = (12V - 2V) / 500Ω = 20mA"));
+        assert_eq!(stated(&explanation, "V(R1)"), Some(9.6));
+    }
+
+    #[test]
+    fn explains_empty_series() {
+        let circuit = Circuit::from_notation("12V:").unwrap();
+        let explanation = explain(&circuit);
+        assert_eq!(explanation.steps.len(), 1);
+        assert_eq!(explanation.steps[0].description, "No components");
+        assert!(!explanation.to_string().contains("inf"));
+
+        let circuit = CircuitBuilder::new()
+            .supply(12.0)
+            .resistor(100.0)
+            .parallel(|p| p.branch(|b| b.resistor(100.0)).branch(|b| b))
+            .build();
+        let explanation = explain(&circuit);
+        assert!(
+            explanation
+                .steps
+                .iter()
+                .any(|step| step.description == "No components in Req1"
+                    && step.formula == "Req1 = 0Ω")
+        );
+    }
+
+    #[test]
+    fn explains_blocking_leds_as_open() {
+        let circuit = Circuit::from_format(
+            r#"{ "version": 2, "design": { "supply": { "voltage": 1 }, "circuit": [
+                { "type": "resistor", "resistance": 100 },
+                { "type": "led", "forward_voltage": "2V", "resistance": 10 }
+            ] } }"#,
+            FileFormat::Json,
+        )
+        .unwrap();
+        let explanation = explain(&circuit);
+        assert_eq!(
+            explanation.steps[0].formula,
+            "Rtotal = R1 + D1 = 100Ω + open = open"
+        );
+        assert_eq!(stated(&explanation, "I"), Some(0.0));
+        assert_eq!(stated(&explanation, "V(R1)"), Some(0.0));
+        let step = explanation
+            .steps
+            .iter()
+            .find(|step| step.kind == StepKind::Open)
+            .unwrap();
+        assert_eq!(step.formula, "V(D1) = 1V - 0V = 1V");
+        assert!(!explanation.to_string().contains("NaN"));
+    }
+
+    #[test]
+    fn exports_markdown() {
+        let circuit = Circuit::from_notation("9V: R1k + R2k").unwrap();
+        let markdown = explain(&circuit).to_markdown();
+        assert!(markdown.starts_with("# Step-by-step solution\n\nCircuit: `9V: R1k + R2k`\n"));
+        assert!(markdown.contains("## Equivalent resistance\n\n1. R1 and R2 in series: `Rtotal = R1 + R2 = 1kΩ + 2kΩ = 3kΩ`\n"));
+        assert!(markdown.contains("## Voltages and currents\n\n2. Ohm's law for the power supply V1: `I = V1 / Rtotal = 9V / 3kΩ = 3mA`\n"));
+        assert!(markdown.contains("4. Voltage divider across R2: `V(R2) = 9V × 2kΩ / 3kΩ = 6V`\n"));
+    }
+}
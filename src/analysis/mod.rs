@@ -0,0 +1,28 @@
+//! Analyses run on a [`Circuit`](crate::circuit::Circuit).
+
+mod checks;
+mod diff;
+mod explain;
+mod monte_carlo;
+mod sensitivity;
+mod snap;
+mod solve;
+mod synthesis;
+mod temperature;
+
+pub use checks::{check_ratings, DesignChecks, Rating, RatingIssue, Severity, DEFAULT_MARGIN};
+pub use diff::{diff, Change, CircuitDiff, ComponentDiff, ComponentEntry, Delta, TextChange};
+pub use explain::{explain, Explanation, ExplanationStep, StepKind};
+pub use monte_carlo::{
+    monte_carlo, Measure, MonteCarloReport, Probe, ProbeSamples, HISTOGRAM_BINS,
+};
+pub use sensitivity::{
+    sensitivity, Corner, ResistorSensitivity, SensitivityReport, WorstCase,
+    EXHAUSTIVE_CORNERS_LIMIT,
+};
+pub use snap::{snap_resistances, Combination, ESeries, SnapReport, SnappedResistor};
+pub use solve::{solve, ComponentSolution, Solution, SubcircuitSolution};
+pub use synthesis::{
+    synthesize, Relation, SynthesisReport, SynthesizedResistor, Target, TargetResult,
+};
+pub use temperature::{temperature_sweep, ProbeSweep, TemperatureSweep};
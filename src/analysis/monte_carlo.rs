@@ -0,0 +1,462 @@
+use std::{fmt::Display, str::FromStr};
+
+use crate::{
+    analysis::{solve, Solution},
+    circuit::{Circuit, Distribution, ElectronicComponent, Series, SeriesElement},
+    units::format_value,
+};
+
+/// Number of histogram bins of the Monte Carlo reports.
+pub const HISTOGRAM_BINS: usize = 10;
+
+// Width of the longest histogram bar, in characters
+const BAR_WIDTH: usize = 40;
+
+/// Quantity observed by a [`Probe`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Measure {
+    /// Voltage, in volts.
+    Voltage,
+    /// Current, in amperes.
+    Current,
+    /// Power, in watts.
+    Power,
+}
+
+/// Voltage, current or power of a component, subcircuit instance or power
+/// supply, written like `V(R1)`, `I(X1)` or `P(V1)`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Probe {
+    /// Quantity observed.
+    pub measure: Measure,
+    /// Identifier of the observed element.
+    pub id: String,
+}
+
+impl Probe {
+    /// Probe of the voltage across the element with the given identifier.
+    pub fn voltage(id: &str) -> Self {
+        Probe {
+            measure: Measure::Voltage,
+            id: String::from(id),
+        }
+    }
+
+    /// Probe of the current through the element with the given identifier.
+    pub fn current(id: &str) -> Self {
+        Probe {
+            measure: Measure::Current,
+            id: String::from(id),
+        }
+    }
+
+    // Letter of the quantity, as in `V(R1)`
+    pub(super) fn symbol(&self) -> &'static str {
+        match self.measure {
+            Measure::Voltage => "V",
+            Measure::Current => "I",
+            Measure::Power => "P",
+        }
+    }
+
+    pub(super) fn unit(&self) -> &'static str {
+        match self.measure {
+            Measure::Voltage => "V",
+            Measure::Current => "A",
+            Measure::Power => "W",
+        }
+    }
+
+    // Value of the probe in a solution of `circuit`
+    pub(super) fn read(&self, circuit: &Circuit, solution: &Solution) -> Option<f64> {
+        let (voltage, current, power) = if circuit.get_power_supply().get_id() == Some(&self.id) {
+            (solution.voltage, solution.current, solution.power)
+        } else if let Some(component) = solution.component(&self.id) {
+            (component.voltage, component.current, component.power)
+        } else {
+            let subcircuit = solution
+                .subcircuits
+                .iter()
+                .find(|subcircuit| subcircuit.id.as_deref() == Some(&self.id))?;
+            (subcircuit.voltage, subcircuit.current, subcircuit.power)
+        };
+        Some(match self.measure {
+            Measure::Voltage => voltage,
+            Measure::Current => current,
+            Measure::Power => power,
+        })
+    }
+}
+
+impl FromStr for Probe {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid probe {}, expected V(ID), I(ID) or P(ID)", text);
+        let (measure, id) = text
+            .trim()
+            .strip_suffix(')')
+            .and_then(|text| text.split_once('('))
+            .ok_or_else(invalid)?;
+        let measure = match measure.to_ascii_uppercase().as_str() {
+            "V" => Measure::Voltage,
+            "I" => Measure::Current,
+            "P" => Measure::Power,
+            _ => return Err(invalid()),
+        };
+        match id.trim() {
+            "" => Err(invalid()),
+            id => Ok(Probe {
+                measure,
+                id: String::from(id),
+            }),
+        }
+    }
+}
+
+impl Display for Probe {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}({})", self.symbol(), self.id)
+    }
+}
+
+/// Values taken by a [`Probe`] over the runs of a Monte Carlo analysis.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct ProbeSamples {
+    /// Observed quantity.
+    pub probe: Probe,
+    /// Value with every component at its nominal value.
+    pub nominal: f64,
+    /// Value of every run, in run order.
+    pub values: Vec<f64>,
+}
+
+impl ProbeSamples {
+    /// Average of the values.
+    pub fn mean(&self) -> f64 {
+        self.values.iter().sum::<f64>() / self.values.len() as f64
+    }
+
+    /// Sample standard deviation of the values, zero for a single run.
+    pub fn std_dev(&self) -> f64 {
+        if self.values.len() < 2 {
+            return 0.0;
+        }
+        let mean = self.mean();
+        let squares: f64 = self.values.iter().map(|value| (value - mean).powi(2)).sum();
+        (squares / (self.values.len() - 1) as f64).sqrt()
+    }
+
+    /// Smallest value.
+    pub fn min(&self) -> f64 {
+        self.values.iter().copied().fold(f64::INFINITY, f64::min)
+    }
+
+    /// Largest value.
+    pub fn max(&self) -> f64 {
+        self.values
+            .iter()
+            .copied()
+            .fold(f64::NEG_INFINITY, f64::max)
+    }
+
+    /// Number of values in each of `bins` intervals of equal width between
+    /// the smallest and the largest value; every value falls in the first bin
+    /// when they are all equal.
+    pub fn histogram(&self, bins: usize) -> Vec<usize> {
+        let mut counts = vec![0; bins.max(1)];
+        let (min, width) = (self.min(), (self.max() - self.min()) / counts.len() as f64);
+        for value in &self.values {
+            let bin = if width > 0.0 {
+                ((value - min) / width) as usize
+            } else {
+                0
+            };
+            counts[bin.min(bins.max(1) - 1)] += 1;
+        }
+        counts
+    }
+}
+
+/// Statistics of the probes of a circuit whose components are drawn within
+/// their tolerances, as computed by [`monte_carlo`].
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct MonteCarloReport {
+    /// Number of times the circuit was solved.
+    pub runs: usize,
+    /// Seed of the random values, giving the same report when used again.
+    pub seed: u64,
+    /// Number of components varied within their tolerances.
+    pub varied: usize,
+    /// Values of every probe, in the order of the probes.
+    pub samples: Vec<ProbeSamples>,
+}
+
+// SplitMix64 generator: small, and reproducible from its seed whatever the
+// platform or the version of the dependencies
+struct Random(u64);
+
+impl Random {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    // Uniform in [0, 1)
+    fn uniform(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    // Relative deviation within [-1, 1], following `distribution`
+    fn deviation(&mut self, distribution: Distribution) -> f64 {
+        match distribution {
+            Distribution::Uniform => 2.0 * self.uniform() - 1.0,
+            Distribution::Normal => loop {
+                // Box-Muller transform, with the tolerance at three standard
+                // deviations
+                let radius = (-2.0 * (1.0 - self.uniform()).ln()).sqrt();
+                let deviation = radius * (2.0 * std::f64::consts::PI * self.uniform()).cos() / 3.0;
+                if deviation.abs() <= 1.0 {
+                    break deviation;
+                }
+            },
+        }
+    }
+}
+
+// Calls `visit` with every resistor of `series`, within parallels and
+// subcircuit instances as well
+pub(super) fn visit_resistors<F>(series: &mut Series, visit: &mut F)
+where
+    F: FnMut(&mut ElectronicComponent),
+{
+    for element in series.iter_mut() {
+        match element {
+            SeriesElement::Component(component @ ElectronicComponent::Resistor(_)) => {
+                visit(component)
+            }
+            SeriesElement::Component(_) => {}
+            SeriesElement::Parallel(branches) => {
+                for branch in branches.iter_mut() {
+                    visit_resistors(branch, visit);
+                }
+            }
+            SeriesElement::Subcircuit(subcircuit) => {
+                visit_resistors(subcircuit.get_mut_series(), visit);
+            }
+        }
+    }
+}
+
+// Draws the resistance of every resistor carrying a tolerance, returning the
+// number of resistors varied
+fn vary(series: &mut Series, random: &mut Random) -> usize {
+    let mut varied = 0;
+    visit_resistors(series, &mut |resistor| {
+        let info = resistor.get_info();
+        if let Some(tolerance) = info.tolerance {
+            let deviation = random.deviation(info.distribution.unwrap_or_default());
+            let resistance = resistor.value().0 * (1.0 + deviation * tolerance / 100.0);
+            if resistor.set_resistance(resistance).is_ok() {
+                varied += 1;
+            }
+        }
+    });
+    varied
+}
+
+/// Solves `circuit` `runs` times, drawing the resistance of every resistor
+/// within its tolerance, and gathers the values of `probes`.
+///
+/// Resistors follow the [`Distribution`] of their details; components without
+/// a tolerance and other kinds of components keep their nominal values. The
+/// same `seed` gives the same values.
+///
+/// ```
+/// use circuit_sim::{
+///     analysis::{monte_carlo, Probe},
+///     circuit::{Circuit, FileFormat},
+/// };
+///
+/// let circuit = Circuit::from_format(
+///     r#"{ "version": 2, "design": { "supply": { "voltage": 10 }, "circuit": [
+///         { "type": "resistor", "resistance": "1k", "tolerance": 5 },
+///         { "type": "resistor", "resistance": "1k", "tolerance": 5 }
+///     ] } }"#,
+///     FileFormat::Json,
+/// )
+/// .unwrap();
+///
+/// let report = monte_carlo(&circuit, &[Probe::voltage("R2")], 1000, 42).unwrap();
+/// let voltage = &report.samples[0];
+/// assert_eq!(voltage.nominal, 5.0);
+/// assert!((voltage.mean() - 5.0).abs() < 0.05);
+/// assert!(voltage.min() > 4.75 && voltage.max() < 5.25);
+/// ```
+pub fn monte_carlo(
+    circuit: &Circuit,
+    probes: &[Probe],
+    runs: usize,
+    seed: u64,
+) -> Result<MonteCarloReport, String> {
+    if runs == 0 {
+        return Err(String::from("the number of runs must be positive"));
+    }
+
+    let nominal = solve(circuit);
+    let mut samples = probes
+        .iter()
+        .map(|probe| {
+            let value = probe
+                .read(circuit, &nominal)
+                .ok_or_else(|| format!("no element with the identifier {}", probe.id))?;
+            Ok(ProbeSamples {
+                probe: probe.clone(),
+                nominal: value,
+                values: Vec::with_capacity(runs),
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let mut random = Random(seed);
+    let mut varied = 0;
+    for _ in 0..runs {
+        let mut sample = circuit.clone();
+        varied = vary(sample.get_mut_series(), &mut random);
+        sample.update();
+
+        let solution = solve(&sample);
+        for probe in samples.iter_mut() {
+            // identifiers are kept, so every probe found in the nominal
+            // circuit is found again
+            probe.values.extend(probe.probe.read(&sample, &solution));
+        }
+    }
+
+    Ok(MonteCarloReport {
+        runs,
+        seed,
+        varied,
+        samples,
+    })
+}
+
+impl Display for ProbeSamples {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let unit = self.probe.unit();
+        writeln!(
+            f,
+            "{}: nominal {}, mean {}, std-dev {}, min {}, max {}",
+            self.probe,
+            format_value(self.nominal, unit),
+            format_value(self.mean(), unit),
+            format_value(self.std_dev(), unit),
+            format_value(self.min(), unit),
+            format_value(self.max(), unit)
+        )?;
+
+        let histogram = self.histogram(HISTOGRAM_BINS);
+        let highest = histogram.iter().copied().max().unwrap_or_default().max(1);
+        let width = (self.max() - self.min()) / HISTOGRAM_BINS as f64;
+        if width <= 0.0 {
+            return Ok(());
+        }
+        for (bin, count) in histogram.iter().enumerate() {
+            writeln!(
+                f,
+                "  {:>10} {:<bar$} {}",
+                format_value(self.min() + bin as f64 * width, unit),
+                "█".repeat(count * BAR_WIDTH / highest),
+                count,
+                bar = BAR_WIDTH
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for MonteCarloReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Monte Carlo analysis: {} runs, seed {}, {} components varied",
+            self.runs, self.seed, self.varied
+        )?;
+        if self.varied == 0 {
+            writeln!(f, "No resistor carries a tolerance")?;
+        }
+        for samples in &self.samples {
+            write!(f, "{}", samples)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::FileFormat;
+
+    fn circuit() -> Circuit {
+        Circuit::from_format(
+            r#"{ "version": 2, "design": { "supply": { "voltage": 12 }, "circuit": [
+                { "type": "resistor", "resistance": "1k", "tolerance": 10,
+                  "distribution": "normal" },
+                { "type": "parallel", "branches": [
+                    [{ "type": "resistor", "resistance": "2k" }],
+                    [{ "type": "resistor", "resistance": "2k", "tolerance": 10 }]
+                ] }
+            ] } }"#,
+            FileFormat::Json,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn parses_probes() {
+        assert_eq!("V(R1)".parse(), Ok(Probe::voltage("R1")));
+        assert_eq!("i( X1.R2 )".parse(), Ok(Probe::current("X1.R2")));
+        assert_eq!(Probe::voltage("R1").to_string(), "V(R1)");
+        assert!("R1".parse::<Probe>().is_err());
+        assert!("Q(R1)".parse::<Probe>().is_err());
+        assert!("V()".parse::<Probe>().is_err());
+    }
+
+    #[test]
+    fn varies_resistors_within_tolerance() {
+        let circuit = circuit();
+        let probes = [Probe::current("R1"), Probe::voltage("R2")];
+        let report = monte_carlo(&circuit, &probes, 500, 7).unwrap();
+
+        assert_eq!(report.varied, 2);
+        let current = &report.samples[0];
+        assert_eq!(current.nominal, 6e-3);
+        assert_eq!(current.values.len(), 500);
+        // 900Ω to 1.1kΩ in series with 2kΩ || (1.8kΩ to 2.2kΩ)
+        let (low, high) = (900.0 + 3.6e6 / 3800.0, 1100.0 + 4.4e6 / 4200.0);
+        assert!(current.min() >= 12.0 / high && current.max() <= 12.0 / low);
+        assert!(current.std_dev() > 0.0);
+        assert_eq!(current.histogram(HISTOGRAM_BINS).iter().sum::<usize>(), 500);
+
+        // R2 keeps its nominal resistance
+        let voltage = &report.samples[1];
+        assert!(voltage.values.iter().all(|v| *v > 5.0 && *v < 7.0));
+    }
+
+    #[test]
+    fn seeds_reproduce_runs() {
+        let circuit = circuit();
+        let probes = [Probe::voltage("R1")];
+        let run = |seed| monte_carlo(&circuit, &probes, 50, seed).unwrap();
+
+        assert_eq!(run(1), run(1));
+        assert_ne!(run(1).samples, run(2).samples);
+        assert!(monte_carlo(&circuit, &[Probe::voltage("R9")], 50, 1).is_err());
+        assert!(monte_carlo(&circuit, &probes, 0, 1).is_err());
+    }
+}
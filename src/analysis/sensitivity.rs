@@ -0,0 +1,380 @@
+use std::fmt::Display;
+
+use crate::{
+    analysis::{monte_carlo::visit_resistors, solve, Probe},
+    circuit::Circuit,
+    units::format_value,
+};
+
+/// Largest number of toleranced resistors whose every corner is solved by
+/// [`sensitivity`]; beyond it, only the corners given by the signs of the
+/// sensitivities are.
+pub const EXHAUSTIVE_CORNERS_LIMIT: usize = 10;
+
+// Relative change of the resistances used to estimate the derivatives
+const STEP: f64 = 1e-6;
+
+/// Sensitivity of the output of a [`SensitivityReport`] to one resistor.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct ResistorSensitivity {
+    /// Identifier of the resistor.
+    pub id: String,
+    /// Nominal resistance, in ohms.
+    pub resistance: f64,
+    /// Tolerance of the resistance, in percent.
+    pub tolerance: Option<f64>,
+    /// Derivative of the output with respect to the resistance, in output
+    /// units per ohm.
+    pub derivative: f64,
+    /// Relative change of the output for a relative change of the resistance,
+    /// zero when the nominal output is zero.
+    pub normalized: f64,
+    /// Change of the output with the resistance at either end of its
+    /// tolerance, to first order; zero without a tolerance.
+    pub variation: f64,
+}
+
+/// Extreme value of the output over the tolerances of the resistors.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct Corner {
+    /// Value of the output.
+    pub value: f64,
+    /// Identifier of every toleranced resistor, along with its deviation from
+    /// its nominal resistance, in percent.
+    pub deviations: Vec<(String, f64)>,
+}
+
+/// Worst-case analysis of the output of a [`SensitivityReport`].
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct WorstCase {
+    /// Number of corners solved.
+    pub corners: usize,
+    /// Corner giving the lowest output.
+    pub minimum: Corner,
+    /// Corner giving the highest output.
+    pub maximum: Corner,
+}
+
+/// Sensitivity and worst-case analysis of an output of a circuit, as
+/// computed by [`sensitivity`].
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct SensitivityReport {
+    /// Observed output.
+    pub output: Probe,
+    /// Value of the output with every resistor at its nominal value.
+    pub nominal: f64,
+    /// Every resistor, the one with the largest variation first, then the
+    /// most sensitive ones.
+    pub resistors: Vec<ResistorSensitivity>,
+    /// Extremes of the output with the toleranced resistors at the ends of
+    /// their tolerances.
+    pub worst_case: WorstCase,
+}
+
+// Value of `output` with the given resistors set to the given resistances
+fn evaluate(circuit: &Circuit, output: &Probe, resistances: &[(&str, f64)]) -> Result<f64, String> {
+    let mut sample = circuit.clone();
+    visit_resistors(sample.get_mut_series(), &mut |resistor| {
+        let id = resistor.get_id().unwrap_or_default();
+        if let Some((_, resistance)) = resistances.iter().find(|(other, _)| *other == id) {
+            // resistors always accept new resistances
+            let _ = resistor.set_resistance(*resistance);
+        }
+    });
+    sample.update();
+    output
+        .read(&sample, &solve(&sample))
+        .ok_or_else(|| format!("no element with the identifier {}", output.id))
+}
+
+// Corner with the resistors deviating by their tolerances, upwards for the
+// `upwards` ones
+fn corner(
+    circuit: &Circuit,
+    output: &Probe,
+    toleranced: &[&ResistorSensitivity],
+    upwards: impl Fn(usize) -> bool,
+) -> Result<Corner, String> {
+    let deviations: Vec<(String, f64)> = toleranced
+        .iter()
+        .enumerate()
+        .map(|(i, resistor)| {
+            let tolerance = resistor.tolerance.unwrap_or_default();
+            let sign = if upwards(i) { 1.0 } else { -1.0 };
+            (resistor.id.clone(), sign * tolerance)
+        })
+        .collect();
+    let resistances: Vec<(&str, f64)> = toleranced
+        .iter()
+        .zip(&deviations)
+        .map(|(resistor, (_, deviation))| {
+            (
+                resistor.id.as_str(),
+                resistor.resistance * (1.0 + deviation / 100.0),
+            )
+        })
+        .collect();
+    Ok(Corner {
+        value: evaluate(circuit, output, &resistances)?,
+        deviations,
+    })
+}
+
+fn worst_case(
+    circuit: &Circuit,
+    output: &Probe,
+    resistors: &[ResistorSensitivity],
+) -> Result<WorstCase, String> {
+    let toleranced: Vec<&ResistorSensitivity> = resistors
+        .iter()
+        .filter(|resistor| resistor.tolerance.is_some())
+        .collect();
+
+    let corners = if toleranced.len() <= EXHAUSTIVE_CORNERS_LIMIT {
+        (0..1usize << toleranced.len())
+            .map(|bits| corner(circuit, output, &toleranced, |i| bits & (1 << i) != 0))
+            .collect::<Result<Vec<_>, String>>()?
+    } else {
+        // the output is taken as monotonic in every resistance
+        let rising = |i: usize| toleranced[i].derivative >= 0.0;
+        vec![
+            corner(circuit, output, &toleranced, |i| !rising(i))?,
+            corner(circuit, output, &toleranced, rising)?,
+        ]
+    };
+
+    let extreme = |pick_first: fn(f64, f64) -> bool| {
+        corners
+            .iter()
+            .fold(&corners[0], |best, corner| {
+                if pick_first(corner.value, best.value) {
+                    corner
+                } else {
+                    best
+                }
+            })
+            .clone()
+    };
+    Ok(WorstCase {
+        corners: corners.len(),
+        minimum: extreme(|value, best| value < best),
+        maximum: extreme(|value, best| value > best),
+    })
+}
+
+/// Computes the derivative of `output` with respect to every resistance of
+/// `circuit`, ranking the resistors by the variation of the output within
+/// their tolerances, and the extremes of the output over the corners of the
+/// tolerances.
+///
+/// ```
+/// use circuit_sim::{
+///     analysis::{sensitivity, Probe},
+///     circuit::{Circuit, FileFormat},
+/// };
+///
+/// let circuit = Circuit::from_format(
+///     r#"{ "version": 2, "design": { "supply": { "voltage": 10 }, "circuit": [
+///         { "type": "resistor", "resistance": "1k", "tolerance": 1 },
+///         { "type": "resistor", "resistance": "1k", "tolerance": 5 }
+///     ] } }"#,
+///     FileFormat::Json,
+/// )
+/// .unwrap();
+///
+/// let report = sensitivity(&circuit, &Probe::voltage("R2")).unwrap();
+/// // V(R2) = 10 R2 / (R1 + R2): R2 dominates with its wider tolerance
+/// assert_eq!(report.resistors[0].id, "R2");
+/// assert!((report.resistors[0].derivative - 2.5e-3).abs() < 1e-9);
+/// assert!((report.worst_case.maximum.value - 10.0 * 1050.0 / 2040.0).abs() < 1e-9);
+/// ```
+pub fn sensitivity(circuit: &Circuit, output: &Probe) -> Result<SensitivityReport, String> {
+    let nominal = evaluate(circuit, output, &[])?;
+
+    let mut nominals = Vec::new();
+    visit_resistors(circuit.clone().get_mut_series(), &mut |resistor| {
+        if let Some(id) = resistor.get_id() {
+            let info = resistor.get_info();
+            nominals.push((String::from(id), resistor.value().0, info.tolerance));
+        }
+    });
+
+    let mut resistors = Vec::new();
+    for (id, resistance, tolerance) in nominals {
+        // central difference, whose error is of the order of the step squared
+        let step = resistance * STEP;
+        let above = evaluate(circuit, output, &[(&id, resistance + step)])?;
+        let below = evaluate(circuit, output, &[(&id, resistance - step)])?;
+        let derivative = (above - below) / (2.0 * step);
+        resistors.push(ResistorSensitivity {
+            normalized: if nominal == 0.0 {
+                0.0
+            } else {
+                derivative * resistance / nominal
+            },
+            variation: (derivative * resistance * tolerance.unwrap_or_default() / 100.0).abs(),
+            id,
+            resistance,
+            tolerance,
+            derivative,
+        });
+    }
+    resistors.sort_by(|a, b| {
+        b.variation
+            .total_cmp(&a.variation)
+            .then(b.normalized.abs().total_cmp(&a.normalized.abs()))
+    });
+
+    Ok(SensitivityReport {
+        output: output.clone(),
+        nominal,
+        worst_case: worst_case(circuit, output, &resistors)?,
+        resistors,
+    })
+}
+
+impl Display for Corner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let deviations: Vec<String> = self
+            .deviations
+            .iter()
+            .map(|(id, deviation)| format!("{} {:+}%", id, deviation))
+            .collect();
+        write!(f, "{}", deviations.join(", "))
+    }
+}
+
+impl Display for SensitivityReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let unit = self.output.unit();
+        writeln!(
+            f,
+            "Sensitivity of {} (nominal {})",
+            self.output,
+            format_value(self.nominal, unit)
+        )?;
+        if self.resistors.is_empty() {
+            return writeln!(f, "The circuit has no resistor");
+        }
+
+        let derivative = format!("∂{}/∂R", self.output.symbol());
+        writeln!(
+            f,
+            "{:>3}  {:<10} {:>11} {:>10} {:>13} {:>11} {:>11}",
+            "#", "Resistor", "Resistance", "Tolerance", derivative, "Normalized", "Variation"
+        )?;
+        for (rank, resistor) in self.resistors.iter().enumerate() {
+            let tolerance = resistor
+                .tolerance
+                .map_or(String::from("-"), |tolerance| format!("±{}%", tolerance));
+            let variation = match resistor.tolerance {
+                Some(_) => format!("±{}", format_value(resistor.variation, unit)),
+                None => String::from("-"),
+            };
+            writeln!(
+                f,
+                "{:>3}  {:<10} {:>11} {:>10} {:>13} {:>11.3} {:>11}",
+                rank + 1,
+                resistor.id,
+                format_value(resistor.resistance, "Ω"),
+                tolerance,
+                format_value(resistor.derivative, &format!("{}/Ω", unit)),
+                resistor.normalized,
+                variation
+            )?;
+        }
+
+        let worst_case = &self.worst_case;
+        if worst_case.minimum.deviations.is_empty() {
+            return writeln!(f, "No resistor carries a tolerance");
+        }
+        writeln!(
+            f,
+            "Worst case over {} corners: {} to {}",
+            worst_case.corners,
+            format_value(worst_case.minimum.value, unit),
+            format_value(worst_case.maximum.value, unit)
+        )?;
+        writeln!(
+            f,
+            "  minimum {}: {}",
+            format_value(worst_case.minimum.value, unit),
+            worst_case.minimum
+        )?;
+        writeln!(
+            f,
+            "  maximum {}: {}",
+            format_value(worst_case.maximum.value, unit),
+            worst_case.maximum
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::FileFormat;
+
+    fn divider(tolerances: [u32; 3]) -> Circuit {
+        let text = format!(
+            r#"{{ "version": 2, "design": {{ "supply": {{ "voltage": 12 }}, "circuit": [
+                {{ "type": "resistor", "resistance": "1k", "tolerance": {} }},
+                {{ "type": "parallel", "branches": [
+                    [{{ "type": "resistor", "resistance": "2k", "tolerance": {} }}],
+                    [{{ "type": "resistor", "resistance": "2k", "tolerance": {} }}]
+                ] }}
+            ] }} }}"#,
+            tolerances[0], tolerances[1], tolerances[2]
+        );
+        Circuit::from_format(&text, FileFormat::Json).unwrap()
+    }
+
+    #[test]
+    fn ranks_resistors_by_variation() {
+        let report = sensitivity(&divider([1, 10, 5]), &Probe::voltage("R1")).unwrap();
+        assert_eq!(report.nominal, 6.0);
+
+        let ids: Vec<&str> = report.resistors.iter().map(|r| r.id.as_str()).collect();
+        assert_eq!(ids, ["R2", "R3", "R1"]);
+        // V(R1) = 12 R1 / (R1 + R2 || R3): ∂V/∂R1 = 12 (R2 || R3) / (R1 + R2 || R3)²
+        let r1 = &report.resistors[2];
+        assert!((r1.derivative - 12.0 * 1000.0 / 4e6).abs() < 1e-9);
+        assert!((r1.normalized - 0.5).abs() < 1e-6);
+        assert!(report.resistors[0].derivative < 0.0);
+    }
+
+    #[test]
+    fn finds_worst_case_corners() {
+        let circuit = divider([5, 5, 5]);
+        let report = sensitivity(&circuit, &Probe::voltage("R1")).unwrap();
+        let worst_case = &report.worst_case;
+        assert_eq!(worst_case.corners, 8);
+
+        let high = 12.0 * 1050.0 / (1050.0 + 950.0);
+        assert!((worst_case.maximum.value - high).abs() < 1e-9);
+        assert_eq!(
+            worst_case.maximum.deviations,
+            [
+                (String::from("R1"), 5.0),
+                (String::from("R2"), -5.0),
+                (String::from("R3"), -5.0)
+            ]
+        );
+        assert!(worst_case.minimum.value < report.nominal);
+    }
+
+    #[test]
+    fn reports_resistors_without_tolerance() {
+        let circuit = Circuit::from_notation("12V: R1k + R2k").unwrap();
+        let report = sensitivity(&circuit, &Probe::current("R1")).unwrap();
+
+        assert!(report.resistors.iter().all(|r| r.variation == 0.0));
+        assert_eq!(report.worst_case.corners, 1);
+        assert_eq!(report.worst_case.maximum.value, report.nominal);
+        assert!(sensitivity(&circuit, &Probe::voltage("R3")).is_err());
+    }
+}
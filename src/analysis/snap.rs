@@ -0,0 +1,477 @@
+use std::{fmt::Display, str::FromStr};
+
+use crate::{
+    analysis::{solve, Delta},
+    circuit::{Circuit, ElectronicComponent, Resistor, Series, SeriesElement},
+    units::format_value,
+};
+
+const E6: [f64; 6] = [1.0, 1.5, 2.2, 3.3, 4.7, 6.8];
+
+const E12: [f64; 12] = [1.0, 1.2, 1.5, 1.8, 2.2, 2.7, 3.3, 3.9, 4.7, 5.6, 6.8, 8.2];
+
+const E24: [f64; 24] = [
+    1.0, 1.1, 1.2, 1.3, 1.5, 1.6, 1.8, 2.0, 2.2, 2.4, 2.7, 3.0, 3.3, 3.6, 3.9, 4.3, 4.7, 5.1, 5.6,
+    6.2, 6.8, 7.5, 8.2, 9.1,
+];
+
+const E48: [f64; 48] = [
+    1.00, 1.05, 1.10, 1.15, 1.21, 1.27, 1.33, 1.40, 1.47, 1.54, 1.62, 1.69, 1.78, 1.87, 1.96, 2.05,
+    2.15, 2.26, 2.37, 2.49, 2.61, 2.74, 2.87, 3.01, 3.16, 3.32, 3.48, 3.65, 3.83, 4.02, 4.22, 4.42,
+    4.64, 4.87, 5.11, 5.36, 5.62, 5.90, 6.19, 6.49, 6.81, 7.15, 7.50, 7.87, 8.25, 8.66, 9.09, 9.53,
+];
+
+const E96: [f64; 96] = [
+    1.00, 1.02, 1.05, 1.07, 1.10, 1.13, 1.15, 1.18, 1.21, 1.24, 1.27, 1.30, 1.33, 1.37, 1.40, 1.43,
+    1.47, 1.50, 1.54, 1.58, 1.62, 1.65, 1.69, 1.74, 1.78, 1.82, 1.87, 1.91, 1.96, 2.00, 2.05, 2.10,
+    2.15, 2.21, 2.26, 2.32, 2.37, 2.43, 2.49, 2.55, 2.61, 2.67, 2.74, 2.80, 2.87, 2.94, 3.01, 3.09,
+    3.16, 3.24, 3.32, 3.40, 3.48, 3.57, 3.65, 3.74, 3.83, 3.92, 4.02, 4.12, 4.22, 4.32, 4.42, 4.53,
+    4.64, 4.75, 4.87, 4.99, 5.11, 5.23, 5.36, 5.49, 5.62, 5.76, 5.90, 6.04, 6.19, 6.34, 6.49, 6.65,
+    6.81, 6.98, 7.15, 7.32, 7.50, 7.68, 7.87, 8.06, 8.25, 8.45, 8.66, 8.87, 9.09, 9.31, 9.53, 9.76,
+];
+
+/// Series of standard resistor values, such as E24.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ESeries {
+    /// 6 values per decade, for ±20% parts.
+    E6,
+    /// 12 values per decade, for ±10% parts.
+    E12,
+    /// 24 values per decade, for ±5% parts.
+    E24,
+    /// 48 values per decade, for ±2% parts.
+    E48,
+    /// 96 values per decade, for ±1% parts.
+    E96,
+}
+
+/// Standard value, or pair of standard values, replacing a resistance.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Combination {
+    /// A single resistor.
+    Single(f64),
+    /// Two resistors in series.
+    Series(f64, f64),
+    /// Two resistors in parallel.
+    Parallel(f64, f64),
+}
+
+// Distance between two resistances, the same for a ratio and its inverse
+fn distance(value: f64, target: f64) -> f64 {
+    (value / target).ln().abs()
+}
+
+impl ESeries {
+    /// Values of the series within a decade, from 1 to below 10.
+    pub fn values(&self) -> &'static [f64] {
+        match self {
+            ESeries::E6 => &E6,
+            ESeries::E12 => &E12,
+            ESeries::E24 => &E24,
+            ESeries::E48 => &E48,
+            ESeries::E96 => &E96,
+        }
+    }
+
+    // Values of the series from `low` to `high`, in increasing order
+    fn range(&self, low: f64, high: f64) -> Vec<f64> {
+        let first = low.log10().floor() as i32;
+        let last = high.log10().floor() as i32;
+        (first..=last)
+            .flat_map(|decade| {
+                let scale = 10f64.powi(decade);
+                // rounded so that 4.7 × 1000 is exactly 4700
+                self.values()
+                    .iter()
+                    .map(move |value| (value * scale * 1e6).round() / 1e6)
+            })
+            .filter(|value| (low..=high).contains(value))
+            .collect()
+    }
+
+    /// Value of the series closest to `resistance`, in ratio.
+    ///
+    /// ```
+    /// use circuit_sim::analysis::ESeries;
+    ///
+    /// assert_eq!(ESeries::E12.nearest(4630.0), 4700.0);
+    /// assert_eq!(ESeries::E96.nearest(4630.0), 4640.0);
+    /// assert_eq!(ESeries::E6.nearest(0.9), 1.0);
+    /// ```
+    pub fn nearest(&self, resistance: f64) -> f64 {
+        self.range(resistance / 10.0, resistance * 10.0)
+            .into_iter()
+            .min_by(|a, b| distance(*a, resistance).total_cmp(&distance(*b, resistance)))
+            .unwrap_or(resistance)
+    }
+
+    /// Single value or pair of values of the series, in series or in
+    /// parallel, closest to `resistance`; pairs are only used when they are
+    /// closer than the nearest single value.
+    ///
+    /// ```
+    /// use circuit_sim::analysis::{Combination, ESeries};
+    ///
+    /// assert_eq!(ESeries::E12.combination(4700.0), Combination::Single(4700.0));
+    /// assert_eq!(ESeries::E6.combination(5700.0), Combination::Series(4700.0, 1000.0));
+    /// ```
+    pub fn combination(&self, resistance: f64) -> Combination {
+        let mut best = Combination::Single(self.nearest(resistance));
+        let mut consider = |combination: Combination| {
+            if distance(combination.resistance(), resistance)
+                < distance(best.resistance(), resistance)
+            {
+                best = combination;
+            }
+        };
+
+        // the larger resistor of a series pair is at least half the total
+        for first in self.range(resistance / 2.0, resistance) {
+            if first < resistance {
+                consider(Combination::Series(first, self.nearest(resistance - first)));
+            }
+        }
+        // the smaller resistor of a parallel pair is at most twice the total
+        for first in self.range(resistance, 2.0 * resistance) {
+            if first > resistance {
+                let second = 1.0 / (1.0 / resistance - 1.0 / first);
+                consider(Combination::Parallel(first, self.nearest(second)));
+            }
+        }
+        best
+    }
+}
+
+impl FromStr for ESeries {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text.trim().to_ascii_uppercase().as_str() {
+            "E6" => Ok(ESeries::E6),
+            "E12" => Ok(ESeries::E12),
+            "E24" => Ok(ESeries::E24),
+            "E48" => Ok(ESeries::E48),
+            "E96" => Ok(ESeries::E96),
+            _ => Err(format!(
+                "unknown series {}, expected E6, E12, E24, E48 or E96",
+                text.trim()
+            )),
+        }
+    }
+}
+
+impl Display for ESeries {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "E{}", self.values().len())
+    }
+}
+
+impl Combination {
+    /// Resistance of the combination, in ohms.
+    pub fn resistance(&self) -> f64 {
+        match *self {
+            Combination::Single(value) => value,
+            Combination::Series(first, second) => first + second,
+            Combination::Parallel(first, second) => first * second / (first + second),
+        }
+    }
+
+    // Elements replacing `resistor`, the first one keeping its details but
+    // its catalog part, whose value no longer matches
+    fn elements(&self, resistor: &ElectronicComponent) -> Vec<SeriesElement> {
+        let with_value = |value: f64, first: bool| {
+            let mut info = resistor.get_info().clone();
+            info.part = None;
+            if !first {
+                info.id = None;
+                info.label = None;
+                info.notes = None;
+            }
+            let mut resistor = Resistor::new(value);
+            resistor.set_info(info);
+            SeriesElement::new(ElectronicComponent::Resistor(resistor))
+        };
+        match *self {
+            Combination::Single(value) => vec![with_value(value, true)],
+            Combination::Series(first, second) => {
+                vec![with_value(first, true), with_value(second, false)]
+            }
+            Combination::Parallel(first, second) => vec![SeriesElement::new_parallel(vec![
+                vec![with_value(first, true)],
+                vec![with_value(second, false)],
+            ])],
+        }
+    }
+}
+
+impl Display for Combination {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            Combination::Single(value) => write!(f, "{}", format_value(value, "Ω")),
+            Combination::Series(first, second) => write!(
+                f,
+                "{} + {}",
+                format_value(first, "Ω"),
+                format_value(second, "Ω")
+            ),
+            Combination::Parallel(first, second) => write!(
+                f,
+                "{} || {}",
+                format_value(first, "Ω"),
+                format_value(second, "Ω")
+            ),
+        }
+    }
+}
+
+/// Resistor replaced by [`snap_resistances`].
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct SnappedResistor {
+    /// Identifier of the resistor.
+    pub id: String,
+    /// Resistance before snapping, in ohms.
+    pub ideal: f64,
+    /// Standard values replacing it.
+    pub combination: Combination,
+}
+
+impl SnappedResistor {
+    /// Relative error of the standard values, 0.01 for 1% above the ideal
+    /// resistance.
+    pub fn error(&self) -> f64 {
+        self.combination.resistance() / self.ideal - 1.0
+    }
+}
+
+/// Circuit whose resistances were snapped to standard values, as computed by
+/// [`snap_resistances`].
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct SnapReport {
+    /// Series the values were taken from.
+    pub series: ESeries,
+    /// Circuit with the standard values.
+    pub circuit: Circuit,
+    /// Every resistor snapped, in circuit order.
+    pub resistors: Vec<SnappedResistor>,
+    /// Voltage of every component of the original circuit, before and after
+    /// snapping, in circuit order; a resistor replaced by a pair is compared
+    /// with the pair.
+    pub voltages: Vec<(String, Delta)>,
+}
+
+impl SnapReport {
+    /// Component whose voltage changed the most.
+    pub fn worst_voltage(&self) -> Option<&(String, Delta)> {
+        self.voltages
+            .iter()
+            .max_by(|a, b| a.1.change().abs().total_cmp(&b.1.change().abs()))
+    }
+}
+
+// Rebuilds `series` with the selected resistors replaced by standard values;
+// subcircuit instances follow their definitions and are kept
+fn snap_series(
+    series: &Series,
+    selected: &dyn Fn(&str) -> bool,
+    pick: &dyn Fn(f64) -> Combination,
+    snapped: &mut Vec<SnappedResistor>,
+) -> Series {
+    let mut result = Vec::new();
+    for element in series {
+        match element {
+            SeriesElement::Component(resistor @ ElectronicComponent::Resistor(_))
+                if selected(resistor.get_id().unwrap_or_default()) =>
+            {
+                let ideal = resistor.value().0;
+                let combination = pick(ideal);
+                result.extend(combination.elements(resistor));
+                snapped.push(SnappedResistor {
+                    id: String::from(resistor.get_id().unwrap_or_default()),
+                    ideal,
+                    combination,
+                });
+            }
+            SeriesElement::Parallel(branches) => {
+                result.push(SeriesElement::new_parallel(
+                    branches
+                        .iter()
+                        .map(|branch| snap_series(branch, selected, pick, snapped))
+                        .collect(),
+                ));
+            }
+            element => result.push(element.clone()),
+        }
+    }
+    result
+}
+
+/// Replaces the resistance of the resistor with the identifier `id`, or of
+/// every resistor outside subcircuit instances without one, by the nearest
+/// value of `series`, or by the closest pair of values when `pairs` is set,
+/// and compares the voltages of the components before and after.
+///
+/// ```
+/// use circuit_sim::{
+///     analysis::{snap_resistances, ESeries},
+///     circuit::Circuit,
+/// };
+///
+/// let circuit = Circuit::from_notation("10V: R1k + R2.2k + R5.7k").unwrap();
+///
+/// let report = snap_resistances(&circuit, ESeries::E6, false, None).unwrap();
+/// assert_eq!(report.resistors[2].combination.resistance(), 6800.0);
+///
+/// let report = snap_resistances(&circuit, ESeries::E6, true, Some("R3")).unwrap();
+/// assert_eq!(report.resistors.len(), 1);
+/// assert_eq!(report.circuit.to_notation(), "10V: R1k + R2.2k + R4.7k + R1k");
+/// ```
+pub fn snap_resistances(
+    circuit: &Circuit,
+    series: ESeries,
+    pairs: bool,
+    id: Option<&str>,
+) -> Result<SnapReport, String> {
+    let selected = |other: &str| id.is_none_or(|id| id == other);
+    let pick = |resistance: f64| {
+        if pairs {
+            series.combination(resistance)
+        } else {
+            Combination::Single(series.nearest(resistance))
+        }
+    };
+
+    let mut resistors = Vec::new();
+    let mut snapped = circuit.clone();
+    *snapped.get_mut_series() = snap_series(circuit.get_series(), &selected, &pick, &mut resistors);
+    if let (Some(id), true) = (id, resistors.is_empty()) {
+        return Err(format!("no resistor with the identifier {}", id));
+    }
+    snapped.assign_ids();
+    snapped.update();
+
+    let after = solve(&snapped);
+    let voltages = solve(circuit)
+        .components
+        .into_iter()
+        .filter_map(|component| {
+            let id = component.id?;
+            let mut after = after.component(&id)?.voltage;
+            // the first resistor of a series pair only takes its share
+            if let Some(Combination::Series(first, second)) = resistors
+                .iter()
+                .find(|resistor| resistor.id == id)
+                .map(|resistor| resistor.combination)
+            {
+                after *= (first + second) / first;
+            }
+            Some((
+                id,
+                Delta {
+                    before: component.voltage,
+                    after,
+                },
+            ))
+        })
+        .collect();
+
+    Ok(SnapReport {
+        series,
+        circuit: snapped,
+        resistors,
+        voltages,
+    })
+}
+
+// Change of a value, along with its relative change when there is one
+fn change(delta: &Delta, unit: &str) -> String {
+    let sign = if delta.change() < 0.0 { "-" } else { "+" };
+    let mut text = format!("{}{}", sign, format_value(delta.change().abs(), unit));
+    if delta.before != 0.0 {
+        text = format!("{} ({:+.2}%)", text, 100.0 * delta.change() / delta.before);
+    }
+    text
+}
+
+impl Display for SnapReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Resistances snapped to {}", self.series)?;
+        if self.resistors.is_empty() {
+            return writeln!(f, "The circuit has no resistor to snap");
+        }
+        for resistor in &self.resistors {
+            writeln!(
+                f,
+                "  {}: {} → {} ({:+.2}%)",
+                resistor.id,
+                format_value(resistor.ideal, "Ω"),
+                resistor.combination,
+                100.0 * resistor.error()
+            )?;
+        }
+
+        writeln!(f, "Voltages")?;
+        for (id, delta) in &self.voltages {
+            writeln!(
+                f,
+                "  {}: {} → {}, {}",
+                id,
+                format_value(delta.before, "V"),
+                format_value(delta.after, "V"),
+                change(delta, "V")
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_nearest_values() {
+        assert_eq!(ESeries::E24.nearest(1000.0), 1000.0);
+        assert_eq!(ESeries::E24.nearest(9.6), 10.0);
+        assert_eq!(ESeries::E12.nearest(0.05), 0.047);
+        assert_eq!(ESeries::E48.nearest(1e6 / 3.0), 332_000.0);
+        assert_eq!("e96".parse(), Ok(ESeries::E96));
+        assert!("E7".parse::<ESeries>().is_err());
+    }
+
+    #[test]
+    fn combines_pairs_of_values() {
+        // 10k || 10k = 5k, closer than 4.7k or any series pair
+        let pair = ESeries::E6.combination(5000.0);
+        assert_eq!(pair, Combination::Parallel(10_000.0, 10_000.0));
+        assert_eq!(pair.to_string(), "10kΩ || 10kΩ");
+
+        for value in [123.0, 4567.0, 89_000.0] {
+            let single = ESeries::E24.nearest(value);
+            let pair = ESeries::E24.combination(value).resistance();
+            assert!(distance(pair, value) <= distance(single, value));
+            assert!(distance(pair, value) < 0.01);
+        }
+    }
+
+    #[test]
+    fn reports_voltage_errors() {
+        let circuit = Circuit::from_notation("12V: R1k + (R4.63k || R10k)").unwrap();
+        let report = snap_resistances(&circuit, ESeries::E12, true, None).unwrap();
+
+        assert_eq!(report.resistors.len(), 3);
+        assert_eq!(report.voltages.len(), 3);
+        let (_, delta) = report.worst_voltage().unwrap();
+        assert!(delta.change().abs() < 0.1);
+        assert!(snap_resistances(&circuit, ESeries::E12, false, Some("R9")).is_err());
+
+        // the details of a snapped resistor stay on its first part
+        let circuit = Circuit::from_notation("10V: R4.3k + R5.7k").unwrap();
+        let snapped = snap_resistances(&circuit, ESeries::E6, true, Some("R2")).unwrap();
+        assert_eq!(snapped.circuit.to_notation(), "10V: R4.3k + R4.7k + R1k");
+        let (id, delta) = &snapped.voltages[1];
+        assert_eq!(id, "R2");
+        assert!((delta.after - 5.7).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,146 @@
+use crate::circuit::{
+    calculate_total_resistance, Circuit, ElectronicComponentTrait, Series, SeriesElement,
+};
+
+/// Operating point of a whole circuit, as computed by [`solve`].
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct Solution {
+    /// Equivalent resistance seen by the power supply, in ohms.
+    pub total_resistance: f64,
+    /// Voltage of the power supply, in volts.
+    pub voltage: f64,
+    /// Current delivered by the power supply, in amperes.
+    pub current: f64,
+    /// Power delivered by the power supply, in watts.
+    pub power: f64,
+    /// Operating point of every component, in circuit order.
+    pub components: Vec<ComponentSolution>,
+    /// Totals of every subcircuit instance, in circuit order.
+    pub subcircuits: Vec<SubcircuitSolution>,
+}
+
+/// Operating point of a single component.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct ComponentSolution {
+    /// Identifier of the component, such as `R1`.
+    pub id: Option<String>,
+    /// Path of the series holding the component, see [`Circuit::get_series_at`].
+    pub path: Vec<usize>,
+    /// Index of the component in its series.
+    pub index: usize,
+    /// Resistance of the component, in ohms.
+    pub resistance: f64,
+    /// Voltage across the component, in volts.
+    pub voltage: f64,
+    /// Current through the component, in amperes.
+    pub current: f64,
+    /// Power dissipated by the component, in watts.
+    pub power: f64,
+}
+
+/// Totals of a subcircuit instance, whose components are listed in
+/// [`Solution::components`] with identifiers such as `X1.R1`.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct SubcircuitSolution {
+    /// Identifier of the instance, such as `X1`.
+    pub id: Option<String>,
+    /// Name of the subcircuit definition.
+    pub definition: String,
+    /// Equivalent resistance of the instance, in ohms.
+    pub resistance: f64,
+    /// Voltage between the terminals, in volts.
+    pub voltage: f64,
+    /// Current through the instance, in amperes.
+    pub current: f64,
+    /// Power dissipated by the components of the instance, in watts.
+    pub power: f64,
+}
+
+impl Solution {
+    /// Returns the operating point of the component with the given identifier.
+    pub fn component(&self, id: &str) -> Option<&ComponentSolution> {
+        self.components
+            .iter()
+            .find(|component| component.id.as_deref() == Some(id))
+    }
+}
+
+fn collect_components(
+    series: &Series,
+    path: &mut Vec<usize>,
+    components: &mut Vec<ComponentSolution>,
+    subcircuits: &mut Vec<SubcircuitSolution>,
+) {
+    for (index, element) in series.iter().enumerate() {
+        match element {
+            SeriesElement::Component(component) => components.push(ComponentSolution {
+                id: component.get_id().map(String::from),
+                path: path.clone(),
+                index,
+                resistance: component.get_resistance(),
+                voltage: component.get_tension(),
+                current: component.get_current(),
+                power: component.get_tension() * component.get_current(),
+            }),
+            SeriesElement::Parallel(branches) => {
+                for (branch, series) in branches.iter().enumerate() {
+                    path.extend([index, branch]);
+                    collect_components(series, path, components, subcircuits);
+                    path.truncate(path.len() - 2);
+                }
+            }
+            SeriesElement::Subcircuit(subcircuit) => {
+                let first = components.len();
+                path.extend([index, 0]);
+                collect_components(subcircuit.get_series(), path, components, subcircuits);
+                path.truncate(path.len() - 2);
+                subcircuits.push(SubcircuitSolution {
+                    id: subcircuit.get_id().map(String::from),
+                    definition: String::from(subcircuit.get_definition()),
+                    resistance: calculate_total_resistance(subcircuit.get_series()),
+                    voltage: subcircuit.get_voltage(),
+                    current: subcircuit.get_current(),
+                    power: components[first..].iter().map(|c| c.power).sum(),
+                });
+            }
+        }
+    }
+}
+
+/// Computes the voltages, currents and powers of every component of `circuit`.
+///
+/// ```
+/// use circuit_sim::{analysis::solve, circuit::Circuit};
+///
+/// let circuit = Circuit::from_notation("12V: R10 + (R20 || R20)").unwrap();
+/// let solution = solve(&circuit);
+///
+/// assert_eq!(solution.total_resistance, 20.0);
+/// assert_eq!(solution.current, 0.6);
+/// assert_eq!(solution.component("R2").unwrap().voltage, 6.0);
+/// ```
+pub fn solve(circuit: &Circuit) -> Solution {
+    let mut components = Vec::new();
+    let mut subcircuits = Vec::new();
+    collect_components(
+        circuit.get_series(),
+        &mut Vec::new(),
+        &mut components,
+        &mut subcircuits,
+    );
+
+    let voltage = circuit.get_power_supply().get_voltage();
+    let current = circuit.get_intensity();
+
+    Solution {
+        total_resistance: calculate_total_resistance(circuit.get_series()),
+        voltage,
+        current,
+        power: voltage * current,
+        components,
+        subcircuits,
+    }
+}
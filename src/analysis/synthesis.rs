@@ -0,0 +1,563 @@
+use std::{fmt::Display, str::FromStr};
+
+use crate::{
+    analysis::{snap_resistances, solve, Combination, ESeries, Probe},
+    circuit::{Circuit, ElectronicComponent, Series, SeriesElement},
+    units::{format_value, parse_value},
+};
+
+// Range of the resistances tried by the optimizer, in ohms
+const MIN_RESISTANCE: f64 = 0.1;
+const MAX_RESISTANCE: f64 = 100e6;
+
+// Largest number of circuits solved by the optimizer
+const MAX_EVALUATIONS: usize = 5000;
+
+// Relative error below which a target is met
+const TOLERANCE: f64 = 1e-6;
+
+/// Relation asked of the value of a [`Target`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Relation {
+    /// The value must equal the target, written `=`.
+    Equal,
+    /// The value must not exceed the target, written `<=` or `≤`.
+    AtMost,
+    /// The value must not fall below the target, written `>=` or `≥`.
+    AtLeast,
+}
+
+/// Goal on the value of a [`Probe`], written like `V(R3)=3.3V` or
+/// `I(V1)<=20mA`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Target {
+    /// Observed quantity.
+    pub probe: Probe,
+    /// Relation asked of the value.
+    pub relation: Relation,
+    /// Value aimed at, in volts, amperes or watts.
+    pub value: f64,
+}
+
+impl Target {
+    /// Distance of `value` from the target relative to the target, zero when
+    /// the relation holds.
+    pub fn error(&self, value: f64) -> f64 {
+        let scale = if self.value == 0.0 {
+            1.0
+        } else {
+            self.value.abs()
+        };
+        let error = match self.relation {
+            Relation::Equal => value - self.value,
+            Relation::AtMost => (value - self.value).max(0.0),
+            Relation::AtLeast => (self.value - value).max(0.0),
+        };
+        error.abs() / scale
+    }
+
+    /// Whether `value` meets the target, up to rounding errors.
+    pub fn is_met(&self, value: f64) -> bool {
+        self.error(value) <= TOLERANCE
+    }
+}
+
+impl FromStr for Target {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let relations = [
+            ("<=", Relation::AtMost),
+            ("≤", Relation::AtMost),
+            (">=", Relation::AtLeast),
+            ("≥", Relation::AtLeast),
+            ("=", Relation::Equal),
+        ];
+        let (probe, relation, value) = relations
+            .iter()
+            .find_map(|(symbol, relation)| {
+                let (probe, value) = text.split_once(symbol)?;
+                Some((probe, *relation, value))
+            })
+            .ok_or_else(|| {
+                format!(
+                    "invalid target {}, expected a probe, =, <= or >= and a value, such as V(R1)=3.3V",
+                    text
+                )
+            })?;
+        Ok(Target {
+            probe: probe.parse()?,
+            relation,
+            value: parse_value(value)?,
+        })
+    }
+}
+
+impl Display for Target {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let relation = match self.relation {
+            Relation::Equal => "=",
+            Relation::AtMost => "≤",
+            Relation::AtLeast => "≥",
+        };
+        write!(
+            f,
+            "{} {} {}",
+            self.probe,
+            relation,
+            format_value(self.value, self.probe.unit())
+        )
+    }
+}
+
+/// Resistance found by [`synthesize`] for one of the unknown resistors.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct SynthesizedResistor {
+    /// Identifier of the resistor.
+    pub id: String,
+    /// Resistance before the synthesis, in ohms.
+    pub initial: f64,
+    /// Resistance meeting the targets best, in ohms.
+    pub ideal: f64,
+    /// Standard values replacing the ideal resistance, when the results are
+    /// snapped.
+    pub combination: Option<Combination>,
+}
+
+/// Value of a [`Target`] in the synthesized circuit.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct TargetResult {
+    /// Goal set on the probe.
+    pub target: Target,
+    /// Value with the ideal resistances.
+    pub ideal: f64,
+    /// Value in the synthesized circuit, with the standard values when the
+    /// results are snapped.
+    pub value: f64,
+}
+
+/// Resistances meeting a set of targets, as computed by [`synthesize`].
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct SynthesisReport {
+    /// Circuit with the synthesized resistances.
+    pub circuit: Circuit,
+    /// Every unknown resistor, in the order they were given.
+    pub resistors: Vec<SynthesizedResistor>,
+    /// Every target, in the order they were given.
+    pub targets: Vec<TargetResult>,
+    /// Series the resistances were snapped to, if any.
+    pub series: Option<ESeries>,
+    /// Number of circuits solved by the optimizer.
+    pub evaluations: usize,
+}
+
+impl SynthesisReport {
+    /// Whether the ideal resistances meet every target.
+    pub fn is_met(&self) -> bool {
+        self.targets
+            .iter()
+            .all(|result| result.target.is_met(result.ideal))
+    }
+}
+
+// Calls `visit` with every resistor of `series` outside subcircuit
+// instances, whose elements follow their definitions
+fn visit_own_resistors<F>(series: &mut Series, visit: &mut F)
+where
+    F: FnMut(&mut ElectronicComponent),
+{
+    for element in series.iter_mut() {
+        match element {
+            SeriesElement::Component(component @ ElectronicComponent::Resistor(_)) => {
+                visit(component)
+            }
+            SeriesElement::Parallel(branches) => {
+                for branch in branches.iter_mut() {
+                    visit_own_resistors(branch, visit);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+// Circuit with the unknown resistors set to `resistances`
+fn with_resistances(circuit: &Circuit, unknowns: &[String], resistances: &[f64]) -> Circuit {
+    let mut sample = circuit.clone();
+    visit_own_resistors(sample.get_mut_series(), &mut |resistor| {
+        let id = resistor.get_id().unwrap_or_default();
+        if let Some(index) = unknowns.iter().position(|unknown| unknown == id) {
+            // resistors always accept new resistances
+            let _ = resistor.set_resistance(resistances[index]);
+        }
+    });
+    sample.update();
+    sample
+}
+
+// Values of the probes of `targets` in `circuit`
+fn read_targets(circuit: &Circuit, targets: &[Target]) -> Result<Vec<f64>, String> {
+    let solution = solve(circuit);
+    targets
+        .iter()
+        .map(|target| {
+            target
+                .probe
+                .read(circuit, &solution)
+                .ok_or_else(|| format!("no element with the identifier {}", target.probe.id))
+        })
+        .collect()
+}
+
+// Minimizes `cost` with the Nelder-Mead method from `start`, returning the
+// best point and its cost once the simplex collapses or the evaluations run
+// out
+fn nelder_mead(
+    start: Vec<f64>,
+    step: f64,
+    cost: &mut dyn FnMut(&[f64]) -> f64,
+    evaluations: &mut usize,
+) -> (Vec<f64>, f64) {
+    let mut evaluate = |point: &[f64], evaluations: &mut usize| {
+        *evaluations += 1;
+        cost(point)
+    };
+
+    let mut simplex: Vec<(Vec<f64>, f64)> = (0..=start.len())
+        .map(|vertex| {
+            let mut point = start.clone();
+            if vertex > 0 {
+                point[vertex - 1] += step;
+            }
+            let cost = evaluate(&point, evaluations);
+            (point, cost)
+        })
+        .collect();
+
+    while *evaluations < MAX_EVALUATIONS {
+        simplex.sort_by(|a, b| a.1.total_cmp(&b.1));
+        let (best, worst) = (simplex[0].1, simplex[simplex.len() - 1].1);
+        let size = simplex[1..]
+            .iter()
+            .flat_map(|(point, _)| point.iter().zip(&simplex[0].0).map(|(a, b)| (a - b).abs()))
+            .fold(0.0, f64::max);
+        if best == 0.0 || size < 1e-12 || worst - best <= 1e-30 {
+            break;
+        }
+
+        // centroid of every vertex but the worst one
+        let count = simplex.len() - 1;
+        let centroid: Vec<f64> = (0..start.len())
+            .map(|i| {
+                simplex[..count]
+                    .iter()
+                    .map(|(point, _)| point[i])
+                    .sum::<f64>()
+                    / count as f64
+            })
+            .collect();
+        let towards = |factor: f64, worst: &[f64]| -> Vec<f64> {
+            centroid
+                .iter()
+                .zip(worst)
+                .map(|(c, w)| c + factor * (w - c))
+                .collect()
+        };
+        let worst_point = simplex[count].0.clone();
+
+        let reflected = towards(-1.0, &worst_point);
+        let reflected_cost = evaluate(&reflected, evaluations);
+        if reflected_cost < best {
+            let expanded = towards(-2.0, &worst_point);
+            let expanded_cost = evaluate(&expanded, evaluations);
+            simplex[count] = if expanded_cost < reflected_cost {
+                (expanded, expanded_cost)
+            } else {
+                (reflected, reflected_cost)
+            };
+        } else if reflected_cost < simplex[count - 1].1 {
+            simplex[count] = (reflected, reflected_cost);
+        } else {
+            let contracted = towards(0.5, &worst_point);
+            let contracted_cost = evaluate(&contracted, evaluations);
+            if contracted_cost < worst {
+                simplex[count] = (contracted, contracted_cost);
+            } else {
+                // shrinks the simplex towards the best vertex
+                let best_point = simplex[0].0.clone();
+                for (point, cost) in simplex[1..].iter_mut() {
+                    for (value, best) in point.iter_mut().zip(&best_point) {
+                        *value = best + 0.5 * (*value - best);
+                    }
+                    *cost = evaluate(point, evaluations);
+                }
+            }
+        }
+    }
+
+    simplex
+        .into_iter()
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .expect("the simplex has at least one vertex")
+}
+
+/// Solves for the resistances of the resistors `unknowns` so that the circuit
+/// meets `targets`, then snaps them to the nearest values of `series`, or
+/// pairs of values when `pairs` is set, if a series is given.
+///
+/// The resistances are searched from their current values with the
+/// Nelder-Mead method, between 0.1Ω and 100MΩ, minimizing the squared
+/// relative errors of the targets; when the targets can't all be met, the
+/// report holds the closest resistances found. Resistors of subcircuit
+/// instances follow their definitions and can't be unknowns.
+///
+/// ```
+/// use circuit_sim::{
+///     analysis::{synthesize, Target},
+///     circuit::Circuit,
+/// };
+///
+/// let circuit = Circuit::from_notation("12V: R1k + R1k").unwrap();
+/// let target: Target = "V(R2)=3.3V".parse().unwrap();
+///
+/// let report = synthesize(&circuit, &[String::from("R1")], &[target], None).unwrap();
+/// assert!(report.is_met());
+/// // 12 × 1k / (R1 + 1k) = 3.3
+/// assert!((report.resistors[0].ideal - 1000.0 * 8.7 / 3.3).abs() < 1e-2);
+/// ```
+pub fn synthesize(
+    circuit: &Circuit,
+    unknowns: &[String],
+    targets: &[Target],
+    snap: Option<(ESeries, bool)>,
+) -> Result<SynthesisReport, String> {
+    if unknowns.is_empty() {
+        return Err(String::from("no resistance to solve for"));
+    }
+    if targets.is_empty() {
+        return Err(String::from("no target to meet"));
+    }
+
+    let mut initial = vec![None; unknowns.len()];
+    visit_own_resistors(circuit.clone().get_mut_series(), &mut |resistor| {
+        let id = resistor.get_id().unwrap_or_default();
+        if let Some(index) = unknowns.iter().position(|unknown| unknown == id) {
+            initial[index] = Some(resistor.value().0);
+        }
+    });
+    let initial = initial
+        .into_iter()
+        .zip(unknowns)
+        .map(|(resistance, id)| {
+            resistance.ok_or_else(|| format!("no resistor with the identifier {}", id))
+        })
+        .collect::<Result<Vec<f64>, String>>()?;
+    // the probes are checked once before the search
+    read_targets(circuit, targets)?;
+
+    // the search runs on logarithms so that resistances stay positive and
+    // steps are relative
+    let (low, high) = (MIN_RESISTANCE.ln(), MAX_RESISTANCE.ln());
+    let resistances = |point: &[f64]| -> Vec<f64> {
+        point
+            .iter()
+            .map(|value| value.clamp(low, high).exp())
+            .collect()
+    };
+    let mut cost = |point: &[f64]| {
+        let sample = with_resistances(circuit, unknowns, &resistances(point));
+        match read_targets(&sample, targets) {
+            Ok(values) => targets
+                .iter()
+                .zip(values)
+                .map(|(target, value)| target.error(value).powi(2))
+                .sum(),
+            Err(_) => f64::INFINITY,
+        }
+    };
+    let start: Vec<f64> = initial.iter().map(|resistance| resistance.ln()).collect();
+    let mut evaluations = 0;
+    let (point, _) = nelder_mead(start, 2f64.ln(), &mut cost, &mut evaluations);
+    let ideal = resistances(&point);
+
+    let solved = with_resistances(circuit, unknowns, &ideal);
+    let ideal_values = read_targets(&solved, targets)?;
+    let mut resistors: Vec<SynthesizedResistor> = unknowns
+        .iter()
+        .zip(initial)
+        .zip(&ideal)
+        .map(|((id, initial), ideal)| SynthesizedResistor {
+            id: id.clone(),
+            initial,
+            ideal: *ideal,
+            combination: None,
+        })
+        .collect();
+
+    let mut result = solved;
+    if let Some((series, pairs)) = snap {
+        for resistor in resistors.iter_mut() {
+            let report = snap_resistances(&result, series, pairs, Some(&resistor.id))?;
+            resistor.combination = report.resistors.first().map(|snapped| snapped.combination);
+            result = report.circuit;
+        }
+    }
+    let values = read_targets(&result, targets)?;
+
+    Ok(SynthesisReport {
+        targets: targets
+            .iter()
+            .zip(ideal_values)
+            .zip(values)
+            .map(|((target, ideal), value)| TargetResult {
+                target: target.clone(),
+                ideal,
+                value,
+            })
+            .collect(),
+        circuit: result,
+        resistors,
+        series: snap.map(|(series, _)| series),
+        evaluations,
+    })
+}
+
+impl Display for SynthesisReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_met() {
+            writeln!(f, "Targets met after {} evaluations", self.evaluations)?;
+        } else {
+            writeln!(
+                f,
+                "The targets can't all be met, closest resistances after {} evaluations",
+                self.evaluations
+            )?;
+        }
+
+        writeln!(f, "Resistances")?;
+        for resistor in &self.resistors {
+            write!(
+                f,
+                "  {}: {} → {}",
+                resistor.id,
+                format_value(resistor.initial, "Ω"),
+                format_value(resistor.ideal, "Ω")
+            )?;
+            match (resistor.combination, self.series) {
+                (Some(combination), Some(series)) => writeln!(
+                    f,
+                    ", {} {} ({:+.2}%)",
+                    series,
+                    combination,
+                    100.0 * (combination.resistance() / resistor.ideal - 1.0)
+                )?,
+                _ => writeln!(f)?,
+            }
+        }
+
+        writeln!(f, "Targets")?;
+        for result in &self.targets {
+            let unit = result.target.probe.unit();
+            let status = if result.target.is_met(result.ideal) {
+                "met"
+            } else {
+                "missed"
+            };
+            write!(
+                f,
+                "  {}: {} {}",
+                result.target,
+                format_value(result.ideal, unit),
+                status
+            )?;
+            if let Some(series) = self.series {
+                write!(f, ", {} with {}", format_value(result.value, unit), series)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|id| String::from(*id)).collect()
+    }
+
+    #[test]
+    fn parses_targets() {
+        let target: Target = "I(V1) <= 20mA".parse().unwrap();
+        assert_eq!(target.probe, Probe::current("V1"));
+        assert_eq!(target.relation, Relation::AtMost);
+        assert_eq!(target.value, 0.02);
+        assert_eq!(target.to_string(), "I(V1) ≤ 20mA");
+        assert_eq!(target.error(0.01), 0.0);
+        assert!((target.error(0.03) - 0.5).abs() < 1e-12);
+
+        assert_eq!(
+            "V(R1)≥1".parse::<Target>().unwrap().relation,
+            Relation::AtLeast
+        );
+        assert!("V(R1)".parse::<Target>().is_err());
+        assert!("R1=3V".parse::<Target>().is_err());
+    }
+
+    #[test]
+    fn solves_several_resistances() {
+        // a divider giving 3.3V out of 12V while drawing 1mA
+        let circuit = Circuit::from_notation("12V: R1k + R1k").unwrap();
+        let targets = ["V(R2)=3.3V".parse().unwrap(), "I(V1)=1mA".parse().unwrap()];
+        let report = synthesize(&circuit, &ids(&["R1", "R2"]), &targets, None).unwrap();
+
+        assert!(report.is_met());
+        assert!((report.resistors[0].ideal - 8700.0).abs() < 0.1);
+        assert!((report.resistors[1].ideal - 3300.0).abs() < 0.1);
+        assert_eq!(
+            report.circuit.find_component("R2").unwrap().value().0,
+            report.resistors[1].ideal
+        );
+    }
+
+    #[test]
+    fn snaps_the_resistances() {
+        let circuit = Circuit::from_notation("12V: R1k + R1k").unwrap();
+        let targets = ["V(R2)=3.3V".parse().unwrap(), "I(V1)=1mA".parse().unwrap()];
+        let report = synthesize(
+            &circuit,
+            &ids(&["R1", "R2"]),
+            &targets,
+            Some((ESeries::E24, false)),
+        )
+        .unwrap();
+
+        assert_eq!(
+            report.resistors[0].combination,
+            Some(Combination::Single(9100.0))
+        );
+        assert_eq!(
+            report.resistors[1].combination,
+            Some(Combination::Single(3300.0))
+        );
+        assert_eq!(report.circuit.to_notation(), "12V: R9.1k + R3.3k");
+        assert!((report.targets[0].value - 12.0 * 3.3 / 12.4).abs() < 1e-9);
+    }
+
+    #[test]
+    fn reports_unmet_targets() {
+        let circuit = Circuit::from_notation("12V: R1k + R1k").unwrap();
+        // R2 can't take more than the supply voltage
+        let targets = ["V(R2)>=15V".parse().unwrap()];
+        let report = synthesize(&circuit, &ids(&["R2"]), &targets, None).unwrap();
+        assert!(!report.is_met());
+        assert!(report.targets[0].ideal < 12.0);
+
+        assert!(synthesize(&circuit, &ids(&["R3"]), &targets, None).is_err());
+        assert!(synthesize(&circuit, &[], &targets, None).is_err());
+    }
+}
@@ -0,0 +1,282 @@
+use std::fmt::Display;
+
+use crate::{
+    analysis::{solve, Probe},
+    circuit::{Circuit, REFERENCE_TEMPERATURE},
+    units::format_value,
+};
+
+// Width of the drift plots, in characters
+const PLOT_WIDTH: usize = 40;
+
+// Relative spread below which values are plotted as flat, so that the
+// floating point noise of the solver isn't stretched across the plot
+const FLAT_SPREAD: f64 = 1e-9;
+
+/// Values taken by a [`Probe`] over a [`TemperatureSweep`].
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct ProbeSweep {
+    /// Observed quantity.
+    pub probe: Probe,
+    /// Value at [`REFERENCE_TEMPERATURE`].
+    pub reference: f64,
+    /// Value at every temperature of the sweep.
+    pub values: Vec<f64>,
+    /// Average drift over the sweep relative to the reference value, in
+    /// ppm/°C; zero when the reference value is zero.
+    pub coefficient: f64,
+}
+
+impl ProbeSweep {
+    /// Change of the value from the first to the last temperature.
+    pub fn drift(&self) -> f64 {
+        match (self.values.first(), self.values.last()) {
+            (Some(first), Some(last)) => last - first,
+            _ => 0.0,
+        }
+    }
+}
+
+/// Probes of a circuit solved across a range of ambient temperatures, as
+/// computed by [`temperature_sweep`].
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct TemperatureSweep {
+    /// Ambient temperatures, in °C, in increasing order.
+    pub temperatures: Vec<f64>,
+    /// Values of every probe, in the order of the probes.
+    pub probes: Vec<ProbeSweep>,
+}
+
+fn probe_value(circuit: &Circuit, probe: &Probe, temperature: f64) -> Result<f64, String> {
+    let mut sample = circuit.clone();
+    sample.set_ambient_temperature(Some(temperature));
+    probe
+        .read(&sample, &solve(&sample))
+        .ok_or_else(|| format!("no element with the identifier {}", probe.id))
+}
+
+/// Solves `circuit` at ambient temperatures from `from` to `to` °C by steps of
+/// `step`, resistors following their temperature coefficients, and gathers
+/// the values of `probes`.
+///
+/// ```
+/// use circuit_sim::{
+///     analysis::{temperature_sweep, Probe},
+///     circuit::{Circuit, FileFormat},
+/// };
+///
+/// let circuit = Circuit::from_format(
+///     r#"{ "version": 2, "design": { "supply": { "voltage": 10 }, "circuit": [
+///         { "type": "resistor", "resistance": "1k", "temperature_coefficient": 100 },
+///         { "type": "resistor", "resistance": "1k" }
+///     ] } }"#,
+///     FileFormat::Json,
+/// )
+/// .unwrap();
+///
+/// let sweep = temperature_sweep(&circuit, &[Probe::current("R1")], -25.0, 75.0, 25.0).unwrap();
+/// assert_eq!(sweep.temperatures, [-25.0, 0.0, 25.0, 50.0, 75.0]);
+/// // R1 is 995Ω at -25°C and 1.005kΩ at 75°C
+/// let current = &sweep.probes[0];
+/// assert!((current.values[0] - 10.0 / 1995.0).abs() < 1e-12);
+/// assert!((current.values[4] - 10.0 / 2005.0).abs() < 1e-12);
+/// assert!(current.drift() < 0.0);
+/// ```
+pub fn temperature_sweep(
+    circuit: &Circuit,
+    probes: &[Probe],
+    from: f64,
+    to: f64,
+    step: f64,
+) -> Result<TemperatureSweep, String> {
+    if step <= 0.0 || step.is_nan() {
+        return Err(String::from("the temperature step must be positive"));
+    }
+    if from > to {
+        return Err(String::from(
+            "the sweep must start below the temperature it ends at",
+        ));
+    }
+
+    // the last temperature is kept despite rounding errors
+    let count = ((to - from) / step + 1e-9).floor() as usize + 1;
+    let temperatures: Vec<f64> = (0..count).map(|i| from + i as f64 * step).collect();
+
+    let probes = probes
+        .iter()
+        .map(|probe| {
+            let reference = probe_value(circuit, probe, REFERENCE_TEMPERATURE)?;
+            let values = temperatures
+                .iter()
+                .map(|temperature| probe_value(circuit, probe, *temperature))
+                .collect::<Result<Vec<_>, String>>()?;
+            let mut sweep = ProbeSweep {
+                probe: probe.clone(),
+                reference,
+                values,
+                coefficient: 0.0,
+            };
+            let span = temperatures[count - 1] - temperatures[0];
+            if reference != 0.0 && span > 0.0 {
+                sweep.coefficient = 1e6 * sweep.drift() / (reference * span);
+            }
+            Ok(sweep)
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    Ok(TemperatureSweep {
+        temperatures,
+        probes,
+    })
+}
+
+impl TemperatureSweep {
+    fn plot(&self, probe: &ProbeSweep, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let unit = probe.probe.unit();
+        let min = probe.values.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = probe
+            .values
+            .iter()
+            .copied()
+            .fold(f64::NEG_INFINITY, f64::max);
+
+        let flat = max - min <= FLAT_SPREAD * min.abs().max(max.abs());
+        for (temperature, value) in self.temperatures.iter().zip(&probe.values) {
+            let position = if flat {
+                0
+            } else {
+                ((value - min) / (max - min) * (PLOT_WIDTH - 1) as f64).round() as usize
+            };
+            writeln!(
+                f,
+                "  {:>7}°C {:>10} │{}●",
+                temperature,
+                format_value(*value, unit),
+                " ".repeat(position)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for TemperatureSweep {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (first, last) = match (self.temperatures.first(), self.temperatures.last()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return Ok(()),
+        };
+        writeln!(
+            f,
+            "Temperature sweep from {}°C to {}°C ({} points)",
+            first,
+            last,
+            self.temperatures.len()
+        )?;
+
+        for probe in &self.probes {
+            let unit = probe.probe.unit();
+            let sign = if probe.drift() < 0.0 { "-" } else { "+" };
+            writeln!(
+                f,
+                "{}: {} at {}°C, drift {}{} ({:+.1}ppm/°C)",
+                probe.probe,
+                format_value(probe.reference, unit),
+                REFERENCE_TEMPERATURE,
+                sign,
+                format_value(probe.drift().abs(), unit),
+                probe.coefficient
+            )?;
+            self.plot(probe, f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::{Edit, ElectronicComponentTrait, FileFormat, History};
+
+    fn circuit(temperature: &str) -> Circuit {
+        let text = format!(
+            r#"{{ "version": 2, "design": {{ "supply": {{ "voltage": 12 }},{} "circuit": [
+                {{ "type": "resistor", "resistance": "1k", "temperature_coefficient": 200 }},
+                {{ "type": "resistor", "resistance": "2k", "temperature_coefficient": -100 }}
+            ] }} }}"#,
+            temperature
+        );
+        Circuit::from_format(&text, FileFormat::Json).unwrap()
+    }
+
+    #[test]
+    fn applies_the_ambient_temperature() {
+        let warm = circuit(r#" "temperature": 125,"#);
+        assert_eq!(warm.get_ambient_temperature(), Some(125.0));
+        let r1 = warm.find_component("R1").unwrap();
+        assert!((r1.get_resistance() - 1020.0).abs() < 1e-9);
+        assert_eq!(r1.value().0, 1000.0);
+
+        // files keep the values at the reference temperature
+        let text = warm.to_format(FileFormat::Json).unwrap();
+        assert!(text.contains(r#""temperature": 125.0"#));
+        assert_eq!(Circuit::from_format(&text, FileFormat::Json).unwrap(), warm);
+        assert_eq!(warm.clone(), warm);
+
+        let reference = circuit("");
+        assert_eq!(reference.get_ambient_temperature(), None);
+        assert_eq!(reference.get_intensity(), 4e-3);
+    }
+
+    #[test]
+    fn solves_again_when_a_coefficient_is_edited() {
+        let mut circuit = circuit(r#" "temperature": 125,"#);
+        let mut info = circuit.find_component("R1").unwrap().get_info().clone();
+        info.temperature_coefficient = Some(1000.0);
+        let edit = Edit::SetInfo {
+            path: vec![],
+            index: 0,
+            info,
+        };
+        History::new().apply(&mut circuit, edit).unwrap();
+
+        // R1 is 1.1kΩ at 125°C, R2 still 1.98kΩ
+        assert!((circuit.get_intensity() - 12.0 / 3080.0).abs() < 1e-12);
+        let r1 = circuit.find_component("R1").unwrap();
+        assert!((r1.get_tension() - 12.0 * 1100.0 / 3080.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn sweeps_temperatures() {
+        let sweep =
+            temperature_sweep(&circuit(""), &[Probe::voltage("R1")], -40.0, 125.0, 15.0).unwrap();
+        assert_eq!(sweep.temperatures.len(), 12);
+        assert_eq!(sweep.temperatures[11], 125.0);
+
+        let voltage = &sweep.probes[0];
+        assert_eq!(voltage.reference, 4.0);
+        // R1 grows while R2 shrinks
+        assert!(voltage.values.windows(2).all(|pair| pair[1] > pair[0]));
+        assert!(voltage.coefficient > 0.0);
+
+        // values only differing by floating point noise are plotted flat
+        let flat = TemperatureSweep {
+            temperatures: vec![0.0, 50.0, 100.0],
+            probes: vec![ProbeSweep {
+                probe: Probe::voltage("R1"),
+                reference: 4.0,
+                values: vec![4.0, 4.0 + 1e-15, 4.0 - 1e-15],
+                coefficient: 0.0,
+            }],
+        };
+        assert!(flat
+            .to_string()
+            .lines()
+            .filter(|line| line.contains('●'))
+            .all(|line| line.ends_with("│●")));
+
+        assert!(temperature_sweep(&circuit(""), &[], 10.0, 0.0, 1.0).is_err());
+        assert!(temperature_sweep(&circuit(""), &[], 0.0, 10.0, 0.0).is_err());
+    }
+}
@@ -0,0 +1,207 @@
+use super::{Circuit, ElectronicComponent, PowerSupply, Series, SeriesElement};
+
+/// Fluent construction of a [`Circuit`].
+///
+/// ```
+/// use circuit_sim::circuit::CircuitBuilder;
+///
+/// let circuit = CircuitBuilder::new()
+///     .supply(12.0)
+///     .resistor(10.0)
+///     .parallel(|p| {
+///         p.branch(|b| b.resistor(20.0).resistor(20.0))
+///             .branch(|b| b.resistor(320.0))
+///     })
+///     .build();
+///
+/// assert_eq!(circuit.to_notation(), "12V: R10 + (R20 + R20 || R320)");
+/// ```
+#[derive(Debug, Default)]
+pub struct CircuitBuilder {
+    power_supply: Option<PowerSupply>,
+    series: SeriesBuilder,
+}
+
+/// Fluent construction of a [`Series`], used for the branches of a parallel.
+#[derive(Debug, Default)]
+pub struct SeriesBuilder {
+    series: Series,
+}
+
+/// Fluent construction of the branches of a parallel.
+#[derive(Debug, Default)]
+pub struct ParallelBuilder {
+    branches: Vec<Series>,
+}
+
+impl CircuitBuilder {
+    /// Starts an empty circuit fed by a 0 V power supply.
+    pub fn new() -> Self {
+        CircuitBuilder::default()
+    }
+
+    /// Sets the voltage of the power supply, in volts.
+    pub fn supply(mut self, voltage: f64) -> Self {
+        self.power_supply = Some(PowerSupply::new(voltage));
+        self
+    }
+
+    /// Sets the power supply, along with its identifier.
+    pub fn power_supply(mut self, power_supply: PowerSupply) -> Self {
+        self.power_supply = Some(power_supply);
+        self
+    }
+
+    /// Appends a resistor of `resistance` ohms.
+    pub fn resistor(mut self, resistance: f64) -> Self {
+        self.series = self.series.resistor(resistance);
+        self
+    }
+
+    /// Appends a component, keeping its identifier and details.
+    pub fn component(mut self, component: ElectronicComponent) -> Self {
+        self.series = self.series.component(component);
+        self
+    }
+
+    /// Appends a group of parallel branches built by `build`.
+    pub fn parallel(mut self, build: impl FnOnce(ParallelBuilder) -> ParallelBuilder) -> Self {
+        self.series = self.series.parallel(build);
+        self
+    }
+
+    /// Creates and solves the circuit, see [`Circuit::new`].
+    pub fn build(self) -> Circuit {
+        Circuit::new(
+            self.power_supply.unwrap_or_else(|| PowerSupply::new(0.0)),
+            self.series.build(),
+        )
+    }
+}
+
+impl SeriesBuilder {
+    /// Starts an empty series.
+    pub fn new() -> Self {
+        SeriesBuilder::default()
+    }
+
+    /// Appends a resistor of `resistance` ohms.
+    pub fn resistor(self, resistance: f64) -> Self {
+        self.component(ElectronicComponent::new_resistor(resistance))
+    }
+
+    /// Appends a component, keeping its identifier and details.
+    pub fn component(mut self, component: ElectronicComponent) -> Self {
+        self.series.push(SeriesElement::new(component));
+        self
+    }
+
+    /// Appends a group of parallel branches built by `build`.
+    pub fn parallel(mut self, build: impl FnOnce(ParallelBuilder) -> ParallelBuilder) -> Self {
+        self.series.push(SeriesElement::new_parallel(
+            build(ParallelBuilder::new()).branches,
+        ));
+        self
+    }
+
+    /// Returns the series.
+    pub fn build(self) -> Series {
+        self.series
+    }
+}
+
+impl ParallelBuilder {
+    /// Starts a parallel without branches.
+    pub fn new() -> Self {
+        ParallelBuilder::default()
+    }
+
+    /// Adds a branch built by `build`.
+    pub fn branch(mut self, build: impl FnOnce(SeriesBuilder) -> SeriesBuilder) -> Self {
+        self.branches.push(build(SeriesBuilder::new()).build());
+        self
+    }
+}
+
+/// Declares a [`Circuit`](crate::circuit::Circuit): the power supply voltage,
+/// then the elements in series.
+///
+/// `R(resistance)` is a resistor and `parallel[[..], [..]]` a group of
+/// parallel branches, each written as a list of elements. The structure is
+/// checked at compile time.
+///
+/// ```
+/// use circuit_sim::circuit;
+///
+/// let circuit = circuit!(12.0; R(10), R(30), parallel[[R(20), R(20)], [R(320), R(20)]]);
+///
+/// assert_eq!(circuit.to_notation(), "12V: R10 + R30 + (R20 + R20 || R320 + R20)");
+/// ```
+#[macro_export]
+macro_rules! circuit {
+    ($voltage:expr $(; $($element:tt)*)?) => {
+        $crate::circuit::Circuit::new(
+            $crate::circuit::PowerSupply::new(::std::primitive::f64::from($voltage)),
+            $crate::__circuit_series!($($($element)*)?),
+        )
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __circuit_series {
+    ($($kind:ident $arguments:tt),* $(,)?) => {
+        ::std::vec![$($crate::__circuit_element!($kind $arguments)),*]
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __circuit_element {
+    (R ($resistance:expr)) => {
+        $crate::circuit::SeriesElement::new($crate::circuit::ElectronicComponent::new_resistor(
+            ::std::primitive::f64::from($resistance),
+        ))
+    };
+    (parallel [$([$($branch:tt)*]),* $(,)?]) => {
+        $crate::circuit::SeriesElement::new_parallel(::std::vec![
+            $($crate::__circuit_series!($($branch)*)),*
+        ])
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builder_and_macro_match_the_notation() {
+        let notation = "12V: R10 + R30 + R20 + (R20 + R20 || R320 + R20)";
+
+        let built = CircuitBuilder::new()
+            .supply(12.0)
+            .resistor(10.0)
+            .resistor(30.0)
+            .resistor(20.0)
+            .parallel(|p| {
+                p.branch(|b| b.resistor(20.0).resistor(20.0))
+                    .branch(|b| b.resistor(320.0).resistor(20.0))
+            })
+            .build();
+        let declared = crate::circuit!(12; R(10), R(30), R(20), parallel[
+            [R(20), R(20)],
+            [R(320), R(20)],
+        ]);
+
+        assert_eq!(built.to_notation(), notation);
+        assert_eq!(declared.to_notation(), notation);
+        assert_eq!(declared.to_json(), built.to_json());
+    }
+
+    #[test]
+    fn nests_parallels() {
+        let circuit = crate::circuit!(5; parallel[[R(1), parallel[[R(2)], [R(3)]]], [R(4)]]);
+        assert_eq!(circuit.to_notation(), "5V: (R1 + (R2 || R3) || R4)");
+        assert_eq!(crate::circuit!(3.3).to_notation(), "3.3V:");
+    }
+}
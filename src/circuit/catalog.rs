@@ -0,0 +1,263 @@
+// Catalog of real parts, read from a local file: every part is a component
+// written as in circuit files, along with its part number and ratings.
+
+use std::{fmt::Display, fs};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::units::format_value;
+
+use super::{file_format::component_from_value, ElectronicComponent, FileFormat};
+
+/// Real part of a [`Catalog`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Part {
+    /// Manufacturer part number, such as "CRCW06034K70FKEA".
+    pub part_number: String,
+    /// Manufacturer of the part.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub manufacturer: Option<String>,
+    /// Short description, searched along with the part number.
+    #[serde(default)]
+    pub description: String,
+    /// Tolerance of the main value, in percent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tolerance: Option<f64>,
+    /// Maximum power the part can dissipate, in watts.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "crate::units::deserialize_optional_value"
+    )]
+    pub power_rating: Option<f64>,
+    /// Maximum voltage across the part, in volts.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "crate::units::deserialize_optional_value"
+    )]
+    pub max_voltage: Option<f64>,
+    // type tag and parameters of the component, as in circuit files
+    #[serde(flatten)]
+    component: Map<String, Value>,
+}
+
+/// Parts library, searched when adding components to a circuit.
+///
+/// ```
+/// use circuit_sim::circuit::{Catalog, FileFormat};
+///
+/// let catalog = Catalog::from_format(
+///     r#"
+///     [[parts]]
+///     part_number = "CRCW06034K70FKEA"
+///     description = "Thick film resistor 0603"
+///     type = "resistor"
+///     resistance = "4k7"
+///     tolerance = 1
+///     power_rating = "100m"
+///     "#,
+///     FileFormat::Toml,
+/// )
+/// .unwrap();
+///
+/// let part = catalog.search("4k7 0603")[0];
+/// let resistor = part.component().unwrap();
+/// assert_eq!(resistor.value().0, 4700.0);
+/// assert_eq!(resistor.get_info().part.as_deref(), Some("CRCW06034K70FKEA"));
+/// assert_eq!(resistor.get_info().power_rating, Some(0.1));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Catalog {
+    /// Parts of the catalog, in file order.
+    pub parts: Vec<Part>,
+}
+
+impl Part {
+    /// Creates the component described by the part, referencing its part
+    /// number and ratings.
+    pub fn component(&self) -> Result<ElectronicComponent, String> {
+        let mut component = component_from_value(Value::Object(self.component.clone()))
+            .map_err(|e| format!("invalid part {}: {}", self.part_number, e))?;
+        let info = component.get_mut_info();
+        info.part = Some(self.part_number.clone());
+        info.tolerance = self.tolerance;
+        info.power_rating = self.power_rating;
+        info.max_voltage = self.max_voltage;
+        Ok(component)
+    }
+
+    /// Type tag of the component, such as "resistor" or "led"; catalogs
+    /// reject parts without one.
+    pub fn type_tag(&self) -> Option<&str> {
+        self.component.get("type").and_then(Value::as_str)
+    }
+
+    // Text searched by `Catalog::search`, in lower case
+    fn search_text(&self) -> String {
+        let mut text = format!(
+            "{} {} {}",
+            self.part_number,
+            self.description,
+            self.type_tag().unwrap_or_default()
+        );
+        if let Some(manufacturer) = &self.manufacturer {
+            text = format!("{} {}", text, manufacturer);
+        }
+        if let Ok(component) = self.component() {
+            let (value, unit) = component.value();
+            text = format!(
+                "{} {} {}",
+                text,
+                format_value(value, unit),
+                format_value(value, "")
+            );
+        }
+        text.to_lowercase()
+    }
+}
+
+impl Display for Part {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.part_number)?;
+        if !self.description.is_empty() {
+            write!(f, " - {}", self.description)?;
+        }
+        if let Ok(component) = self.component() {
+            let (value, unit) = component.value();
+            write!(f, ", {}", format_value(value, unit))?;
+        }
+        if let Some(tolerance) = self.tolerance {
+            write!(f, ", ±{}%", tolerance)?;
+        }
+        if let Some(power_rating) = self.power_rating {
+            write!(f, ", {}", format_value(power_rating, "W"))?;
+        }
+        Ok(())
+    }
+}
+
+impl Catalog {
+    /// Reads a catalog written in `format`, with its parts under `parts`.
+    pub fn from_format(text: &str, format: FileFormat) -> Result<Self, String> {
+        let catalog: Catalog =
+            serde_json::from_value(format.parse(text)?).map_err(|e| e.to_string())?;
+        // reject broken parts when loading rather than when inserting them
+        for part in &catalog.parts {
+            part.component()?;
+        }
+        Ok(catalog)
+    }
+
+    /// Reads the catalog file at `path`, in the format given by its extension.
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        Catalog::from_format(&text, FileFormat::from_path(path))
+    }
+
+    /// Parts whose part number, manufacturer, description, type or value
+    /// contain every word of `query`, ignoring case; all the parts for an
+    /// empty query.
+    pub fn search(&self, query: &str) -> Vec<&Part> {
+        let words: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+        self.parts
+            .iter()
+            .filter(|part| {
+                let text = part.search_text();
+                words.iter().all(|word| text.contains(word.as_str()))
+            })
+            .collect()
+    }
+
+    /// Returns the part with the given part number, ignoring case.
+    pub fn get(&self, part_number: &str) -> Option<&Part> {
+        self.parts
+            .iter()
+            .find(|part| part.part_number.eq_ignore_ascii_case(part_number))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::{CircuitBuilder, ElectronicComponentTrait};
+
+    const CATALOG: &str = r#"{ "parts": [
+        { "part_number": "RC0805FR-07330RL", "manufacturer": "Yageo",
+          "description": "Thick film resistor 0805", "type": "resistor",
+          "resistance": 330, "tolerance": 1, "power_rating": "125m" },
+        { "part_number": "WP7113ID", "manufacturer": "Kingbright",
+          "description": "Red LED 5 mm", "type": "led",
+          "forward_voltage": "2V", "resistance": 20 },
+        { "part_number": "CR2032", "description": "Lithium coin cell",
+          "type": "battery", "voltage": 3, "resistance": 15 }
+    ] }"#;
+
+    #[test]
+    fn searches_parts_by_words() {
+        let catalog = Catalog::from_format(CATALOG, FileFormat::Json).unwrap();
+
+        let numbers = |query| {
+            catalog
+                .search(query)
+                .iter()
+                .map(|part| part.part_number.as_str())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(numbers("led red"), ["WP7113ID"]);
+        assert_eq!(numbers("330Ω"), ["RC0805FR-07330RL"]);
+        assert_eq!(numbers("kingbright 0805"), Vec::<&str>::new());
+        assert_eq!(numbers("").len(), 3);
+        assert!(catalog.get("cr2032").is_some());
+    }
+
+    #[test]
+    fn solves_circuits_built_from_parts() {
+        let catalog = Catalog::from_format(CATALOG, FileFormat::Json).unwrap();
+        let part = |number| catalog.get(number).unwrap().component().unwrap();
+
+        // coin cell lighting a LED through its resistor
+        let circuit = CircuitBuilder::new()
+            .supply(0.0)
+            .component(part("CR2032"))
+            .component(part("RC0805FR-07330RL"))
+            .component(part("WP7113ID"))
+            .build();
+        assert!((circuit.get_intensity() - 1.0 / 365.0).abs() < 1e-12);
+
+        let led = circuit.find_component("D1").unwrap();
+        assert_eq!(led.get_info().part.as_deref(), Some("WP7113ID"));
+        assert!((led.get_tension() - (2.0 + 20.0 / 365.0)).abs() < 1e-12);
+
+        let text = circuit.to_format(FileFormat::Yaml).unwrap();
+        let loaded = crate::circuit::Circuit::from_format(&text, FileFormat::Yaml).unwrap();
+        assert_eq!(loaded, circuit);
+        assert_eq!(
+            loaded.find_component("R1").unwrap().get_info().power_rating,
+            Some(0.125)
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_parts() {
+        let text = r#"{ "parts": [{ "part_number": "X", "type": "led", "resistance": 1 }] }"#;
+        match Catalog::from_format(text, FileFormat::Json) {
+            Err(error) => assert!(error.contains("invalid part X")),
+            Ok(_) => panic!("parts without a forward voltage should be rejected"),
+        }
+
+        let catalog = Catalog::from_format(CATALOG, FileFormat::Json).unwrap();
+        assert_eq!(catalog.parts[1].type_tag(), Some("led"));
+        let mut untyped = catalog.parts[0].clone();
+        untyped.component.remove("type");
+        assert_eq!(untyped.type_tag(), None);
+        assert!(untyped.component().is_err());
+
+        let text = r#"{ "parts": [{ "part_number": "Y", "resistance": 1 }] }"#;
+        match Catalog::from_format(text, FileFormat::Json) {
+            Err(error) => assert!(error.contains("invalid part Y")),
+            Ok(_) => panic!("parts without a type should be rejected"),
+        }
+    }
+}
@@ -1,112 +1,342 @@
-use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
-#[derive(Serialize, Deserialize)]
+use schemars::JsonSchema;
+use serde::{de::Error, Deserialize, Deserializer, Serialize};
+
+use crate::units::deserialize_value;
+
+use super::{PluginComponent, Subcircuit, SubcircuitDefinition};
+
+/// Ideal voltage source feeding a circuit.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct PowerSupply {
+    /// Unique identifier, such as "V1"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+    /// Voltage in volts
+    #[serde(deserialize_with = "crate::units::deserialize_value")]
+    #[schemars(schema_with = "crate::units::value_schema")]
     voltage: f64,
 }
 
-#[derive(Serialize, Deserialize)]
+/// Temperature at which the values of components are given, in °C.
+pub const REFERENCE_TEMPERATURE: f64 = 25.0;
+
+/// Distribution of the actual values of a component within its tolerance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Distribution {
+    /// Every value within the tolerance is equally likely.
+    #[default]
+    Uniform,
+    /// Gaussian around the nominal value, the tolerance being three standard
+    /// deviations; values beyond the tolerance are drawn again.
+    Normal,
+}
+
+/// Identifier, free-form details and catalog reference of a component.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default, JsonSchema)]
+pub struct ComponentInfo {
+    /// Unique identifier, such as "R1": a letter followed by letters, digits,
+    /// _ or .; assigned automatically when missing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(regex(pattern = r"^[A-Za-z][A-Za-z0-9_.]*$"))]
+    pub id: Option<String>,
+    /// Short free-form label
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Free-form notes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+    /// Part number of the catalog part the component was taken from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub part: Option<String>,
+    /// Tolerance of the main value, in percent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tolerance: Option<f64>,
+    /// Distribution of the actual value within the tolerance; uniform when
+    /// missing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub distribution: Option<Distribution>,
+    /// Temperature coefficient of the resistance of resistors, in ppm/°C
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature_coefficient: Option<f64>,
+    /// Maximum power the component can dissipate, in watts
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "crate::units::deserialize_optional_value"
+    )]
+    #[schemars(schema_with = "crate::units::value_schema")]
+    pub power_rating: Option<f64>,
+    /// Maximum voltage across the component, in volts
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "crate::units::deserialize_optional_value"
+    )]
+    #[schemars(schema_with = "crate::units::value_schema")]
+    pub max_voltage: Option<f64>,
+}
+
+/// Resistor, along with the voltage across it in its circuit.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Resistor {
+    #[serde(flatten)]
+    info: ComponentInfo,
+    #[serde(deserialize_with = "deserialize_resistance")]
     resistance: f64,
     tension_in_circuit: f64,
+    // temperature above the reference one, set by the circuit
+    #[serde(skip)]
+    temperature_rise: f64,
 }
 
-#[derive(Serialize, Deserialize)]
+/// Element of a series: a component or a group of parallel branches.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum SeriesElement {
+    /// A single component.
     Component(ElectronicComponent),
+    /// Branches connected in parallel.
     Parallel(Vec<Series>),
+    /// Instance of a subcircuit, whose elements are connected in series.
+    Subcircuit(Subcircuit),
 }
 
+/// Elements connected in series, in circuit order.
 pub type Series = Vec<SeriesElement>;
 
-#[derive(Serialize, Deserialize)]
+/// Circuit made of a power supply feeding a series of elements.
+///
+/// The circuit is solved whenever it changes, so the voltages and current it
+/// reports are always up to date.
+#[derive(Debug, Clone, PartialEq)]
 pub struct Circuit {
     power_supply: PowerSupply,
     intensity: f64,
     circuit: Series,
+    subcircuits: BTreeMap<String, SubcircuitDefinition>,
+    libraries: Vec<String>,
+    temperature: Option<f64>,
 }
 
-#[derive(Serialize, Deserialize)]
+/// Component of a circuit.
+///
+/// Components other than resistors are provided by
+/// [`ComponentModel`](super::ComponentModel) plugins.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
 pub enum ElectronicComponent {
+    /// Resistor.
     Resistor(Resistor),
+    /// Component provided by a plugin.
+    Plugin(PluginComponent),
 }
 
 impl ElectronicComponent {
+    /// Creates a resistor of `resistance` ohms.
     pub fn new_resistor(resistance: f64) -> Self {
         ElectronicComponent::Resistor(Resistor::new(resistance))
     }
 
-    pub fn set_resistance(&mut self, resistance: f64) {
+    pub(crate) fn set_resistance(&mut self, resistance: f64) -> Result<(), String> {
+        check_resistance(resistance)?;
+        match self {
+            ElectronicComponent::Resistor(resistor) => {
+                resistor.resistance = resistance;
+                Ok(())
+            }
+            ElectronicComponent::Plugin(component) => {
+                component.get_mut_model().set_resistance(resistance)
+            }
+        }
+    }
+
+    /// Name of the kind of component, such as "Resistor".
+    pub fn name(&self) -> &'static str {
+        match self {
+            ElectronicComponent::Resistor(_) => "Resistor",
+            ElectronicComponent::Plugin(component) => component.get_model().name(),
+        }
+    }
+
+    /// Main value of the component and its unit, such as its resistance in ohms.
+    pub fn value(&self) -> (f64, &'static str) {
+        match self {
+            ElectronicComponent::Resistor(resistor) => (resistor.get_nominal_resistance(), "Ω"),
+            ElectronicComponent::Plugin(component) => component.get_model().value(),
+        }
+    }
+
+    /// Prefix of the identifiers assigned to this kind of component, such as `R`.
+    pub fn id_prefix(&self) -> &'static str {
+        match self {
+            ElectronicComponent::Resistor(_) => "R",
+            ElectronicComponent::Plugin(component) => component.get_model().id_prefix(),
+        }
+    }
+
+    /// Identifier and details of the component.
+    pub fn get_info(&self) -> &ComponentInfo {
+        match self {
+            ElectronicComponent::Resistor(resistor) => resistor.get_info(),
+            ElectronicComponent::Plugin(component) => component.get_info(),
+        }
+    }
+
+    pub(crate) fn get_mut_info(&mut self) -> &mut ComponentInfo {
         match self {
-            ElectronicComponent::Resistor(resistor) => resistor.resistance = resistance,
+            ElectronicComponent::Resistor(resistor) => &mut resistor.info,
+            ElectronicComponent::Plugin(component) => component.get_mut_info(),
         }
     }
+
+    /// Identifier of the component, such as `R1`.
+    pub fn get_id(&self) -> Option<&str> {
+        self.get_info().id.as_deref()
+    }
 }
 
 impl ElectronicComponentTrait for ElectronicComponent {
     fn get_resistance(&self) -> f64 {
         match self {
             ElectronicComponent::Resistor(resistor) => resistor.get_resistance(),
+            ElectronicComponent::Plugin(component) => component.get_resistance(),
+        }
+    }
+
+    fn get_electromotive_force(&self) -> f64 {
+        match self {
+            ElectronicComponent::Resistor(_) => 0.0,
+            ElectronicComponent::Plugin(component) => component.get_electromotive_force(),
         }
     }
 
     fn get_tension(&self) -> f64 {
         match self {
             ElectronicComponent::Resistor(resistor) => resistor.get_tension(),
+            ElectronicComponent::Plugin(component) => component.get_tension(),
         }
     }
 
     fn set_tension(&mut self, tension: f64) {
         match self {
             ElectronicComponent::Resistor(resistor) => resistor.set_tension(tension),
+            ElectronicComponent::Plugin(component) => component.set_tension(tension),
         }
     }
 }
 
+/// Electrical quantities of a component.
+///
+/// A component is modelled by its Thévenin equivalent: an electromotive force
+/// in series with a resistance. The voltage across it is the drop in the
+/// direction of the current, `current × resistance − electromotive force`.
 pub trait ElectronicComponentTrait {
+    /// Resistance in ohms.
     fn get_resistance(&self) -> f64;
+    /// Electromotive force in volts, pushing current along the series; zero
+    /// for passive components.
+    fn get_electromotive_force(&self) -> f64 {
+        0.0
+    }
+    /// Voltage across the component in its circuit, in volts.
     fn get_tension(&self) -> f64;
+    /// Sets the voltage across the component.
     fn set_tension(&mut self, tension: f64);
+    /// Current through the component, in amperes.
+    fn get_current(&self) -> f64 {
+        (self.get_tension() + self.get_electromotive_force()) / self.get_resistance()
+    }
+    /// Resistor with the same resistance and voltage, without details.
     fn to_resistor(&self) -> Resistor {
         Resistor {
+            info: ComponentInfo::default(),
             resistance: self.get_resistance(),
             tension_in_circuit: self.get_tension(),
+            temperature_rise: 0.0,
         }
     }
 }
 
 impl SeriesElement {
+    /// Creates a group of parallel branches.
     pub fn new_parallel(parallel: Vec<Series>) -> Self {
         SeriesElement::Parallel(parallel)
     }
 
+    /// Wraps a single component.
     pub fn new(component: ElectronicComponent) -> Self {
         SeriesElement::Component(component)
     }
 }
 
 impl PowerSupply {
+    /// Creates a power supply of `voltage` volts.
     pub fn new(voltage: f64) -> Self {
-        PowerSupply { voltage }
+        PowerSupply { id: None, voltage }
+    }
+
+    /// Identifier of the power supply, such as `V1`.
+    pub fn get_id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+
+    /// Sets the identifier of the power supply.
+    pub fn set_id(&mut self, id: Option<String>) {
+        self.id = id;
     }
 
+    /// Voltage in volts.
     pub fn get_voltage(&self) -> f64 {
         self.voltage
     }
+
+    /// Sets the voltage in volts; use [`Circuit::set_voltage`] on a circuit.
+    pub fn set_voltage(&mut self, voltage: f64) {
+        self.voltage = voltage;
+    }
 }
 
 impl Resistor {
+    /// Creates a resistor of `resistance` ohms.
     pub fn new(resistance: f64) -> Self {
         Resistor {
+            info: ComponentInfo::default(),
             resistance,
             tension_in_circuit: 0.0,
+            temperature_rise: 0.0,
         }
     }
 
+    /// Identifier and details of the resistor.
+    pub fn get_info(&self) -> &ComponentInfo {
+        &self.info
+    }
+
+    /// Sets the identifier and details of the resistor.
+    pub fn set_info(&mut self, info: ComponentInfo) {
+        self.info = info;
+    }
+
+    /// Resistance in ohms at the temperature of its circuit, following the
+    /// temperature coefficient of its details.
     pub fn get_resistance(&self) -> f64 {
+        let coefficient = self.info.temperature_coefficient.unwrap_or_default();
+        self.resistance * (1.0 + coefficient * 1e-6 * self.temperature_rise)
+    }
+
+    /// Resistance in ohms at [`REFERENCE_TEMPERATURE`], as written in circuit
+    /// files.
+    pub fn get_nominal_resistance(&self) -> f64 {
         self.resistance
     }
 
+    pub(crate) fn set_temperature(&mut self, temperature: f64) {
+        self.temperature_rise = temperature - REFERENCE_TEMPERATURE;
+    }
+
+    /// Voltage across the resistor in its circuit, in volts.
     pub fn get_tension(&self) -> f64 {
         self.tension_in_circuit
     }
@@ -114,7 +344,7 @@ impl Resistor {
 
 impl ElectronicComponentTrait for Resistor {
     fn get_resistance(&self) -> f64 {
-        self.resistance
+        Resistor::get_resistance(self)
     }
     fn get_tension(&self) -> f64 {
         self.tension_in_circuit
@@ -124,7 +354,31 @@ impl ElectronicComponentTrait for Resistor {
     }
 }
 
-fn calculate_parallel_resistance(series: &[Series]) -> f64 {
+// Largest number of times a circuit is solved while its LEDs switch between
+// conducting and blocking
+const MAX_SWITCHING_PASSES: usize = 32;
+
+// Resistances must be finite and positive for circuits to be solved
+pub(crate) fn check_resistance(resistance: f64) -> Result<f64, String> {
+    if resistance.is_finite() && resistance > 0.0 {
+        Ok(resistance)
+    } else {
+        Err(format!(
+            "Invalid resistance {}: resistances must be finite and positive",
+            resistance
+        ))
+    }
+}
+
+// Same as `deserialize_value`, rejecting what `check_resistance` rejects
+pub(crate) fn deserialize_resistance<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<f64, D::Error> {
+    check_resistance(deserialize_value(deserializer)?).map_err(D::Error::custom)
+}
+
+/// Equivalent resistance of parallel branches, in ohms.
+pub fn calculate_parallel_resistance(series: &[Series]) -> f64 {
     series
         .iter()
         .map(|s| 1.0 / calculate_total_resistance(s))
@@ -132,84 +386,262 @@ fn calculate_parallel_resistance(series: &[Series]) -> f64 {
         .recip()
 }
 
-fn calculate_total_resistance(elements: &[SeriesElement]) -> f64 {
+/// Equivalent resistance of elements in series, in ohms.
+pub fn calculate_total_resistance(elements: &[SeriesElement]) -> f64 {
     elements.iter().fold(0.0, |acc, element| {
         acc + match element {
             SeriesElement::Component(component) => component.get_resistance(),
             SeriesElement::Parallel(parallel_series) => {
                 calculate_parallel_resistance(parallel_series)
             }
+            SeriesElement::Subcircuit(subcircuit) => {
+                calculate_total_resistance(subcircuit.get_series())
+            }
         }
     })
 }
 
-fn set_tensions_in_circuit(elements: &mut [SeriesElement], voltage: f64) {
-    let total_resistance = calculate_total_resistance(elements);
+// Thévenin equivalent of parallel branches, as (resistance, electromotive
+// force), following Millman's theorem
+fn parallel_equivalent(series: &[Series]) -> (f64, f64) {
+    let resistance = calculate_parallel_resistance(series);
+    // branches that are all open carry no current, whatever their sources
+    if resistance.is_infinite() {
+        return (resistance, 0.0);
+    }
+    let force = resistance
+        * series
+            .iter()
+            .map(|s| series_equivalent(s))
+            .map(|(r, e)| e / r)
+            .sum::<f64>();
+    (resistance, force)
+}
+
+// Thévenin equivalent of elements in series, as (resistance, electromotive
+// force)
+pub(crate) fn series_equivalent(elements: &[SeriesElement]) -> (f64, f64) {
+    elements.iter().fold((0.0, 0.0), |(r, e), element| {
+        let (resistance, force) = match element {
+            SeriesElement::Component(component) => (
+                component.get_resistance(),
+                component.get_electromotive_force(),
+            ),
+            SeriesElement::Parallel(parallel_series) => parallel_equivalent(parallel_series),
+            SeriesElement::Subcircuit(subcircuit) => series_equivalent(subcircuit.get_series()),
+        };
+        (r + resistance, e + force)
+    })
+}
 
-    elements.iter_mut().for_each(|element| match element {
-        SeriesElement::Component(component) => {
-            component.set_tension((voltage / total_resistance) * component.get_resistance());
+// Sets the voltages of a series carrying `current` with `voltage` across it;
+// open elements, such as blocking LEDs, share the voltage left by the others
+fn set_tensions_in_circuit(elements: &mut [SeriesElement], current: f64, voltage: f64) {
+    let voltages: Vec<Option<f64>> = elements
+        .iter()
+        .map(|element| {
+            let (resistance, force) = series_equivalent(std::slice::from_ref(element));
+            Some(current * resistance - force).filter(|_| resistance.is_finite())
+        })
+        .collect();
+    let open = voltages.iter().filter(|voltage| voltage.is_none()).count();
+    let remainder = (voltage - voltages.iter().flatten().sum::<f64>()) / open.max(1) as f64;
+
+    for (element, element_voltage) in elements.iter_mut().zip(voltages) {
+        let element_voltage = element_voltage.unwrap_or(remainder);
+        match element {
+            SeriesElement::Component(component) => component.set_tension(element_voltage),
+            SeriesElement::Parallel(parallel_series) => {
+                parallel_series.iter_mut().for_each(|series| {
+                    let (resistance, force) = series_equivalent(series);
+                    set_tensions_in_circuit(
+                        series,
+                        (element_voltage + force) / resistance,
+                        element_voltage,
+                    );
+                });
+            }
+            SeriesElement::Subcircuit(subcircuit) => {
+                set_tensions_in_circuit(subcircuit.get_mut_series(), current, element_voltage);
+            }
+        }
+    }
+}
+
+// Switches the components conducting one way only, such as LEDs, to blocking
+// when the circuit drives current backwards through them, and back to
+// conducting when it drives current forwards; returns whether any switched
+fn update_blocking(series: &mut Series) -> bool {
+    let mut switched = false;
+    for element in series.iter_mut() {
+        match element {
+            SeriesElement::Component(ElectronicComponent::Plugin(component))
+                if component.get_model().blocks_reverse_current() =>
+            {
+                let forward = component.get_tension() + component.get_model().electromotive_force();
+                let blocking = if component.is_blocking() {
+                    forward <= 0.0
+                } else {
+                    component.get_current() < 0.0
+                };
+                if blocking != component.is_blocking() {
+                    component.set_blocking(blocking);
+                    switched = true;
+                }
+            }
+            SeriesElement::Component(_) => {}
+            SeriesElement::Parallel(branches) => {
+                for branch in branches.iter_mut() {
+                    switched |= update_blocking(branch);
+                }
+            }
+            SeriesElement::Subcircuit(subcircuit) => {
+                switched |= update_blocking(subcircuit.get_mut_series());
+            }
+        }
+    }
+    switched
+}
+
+fn set_temperatures(series: &mut Series, temperature: f64) {
+    for element in series.iter_mut() {
+        match element {
+            SeriesElement::Component(ElectronicComponent::Resistor(resistor)) => {
+                resistor.set_temperature(temperature)
+            }
+            SeriesElement::Component(_) => {}
+            SeriesElement::Parallel(branches) => branches
+                .iter_mut()
+                .for_each(|branch| set_temperatures(branch, temperature)),
+            SeriesElement::Subcircuit(subcircuit) => {
+                set_temperatures(subcircuit.get_mut_series(), temperature)
+            }
         }
-        SeriesElement::Parallel(parallel_series) => {
-            let parallel_voltage =
-                voltage * calculate_parallel_resistance(parallel_series) / total_resistance;
-            parallel_series.iter_mut().for_each(|series| {
-                set_tensions_in_circuit(series, parallel_voltage);
-            });
+    }
+}
+
+// Voltage across a series, read from the voltages of its components
+pub(crate) fn calculate_series_voltage(elements: &[SeriesElement]) -> f64 {
+    elements.iter().fold(0.0, |acc, element| {
+        acc + match element {
+            SeriesElement::Component(component) => component.get_tension(),
+            SeriesElement::Parallel(parallel_series) => parallel_series
+                .first()
+                .map_or(0.0, |series| calculate_series_voltage(series)),
+            SeriesElement::Subcircuit(subcircuit) => {
+                calculate_series_voltage(subcircuit.get_series())
+            }
         }
-    });
+    })
 }
 
+/// Current delivered by the power supply of `circuit`, in amperes.
 pub fn calculate_current(circuit: &Circuit) -> f64 {
-    let total_resistance = calculate_total_resistance(&circuit.circuit);
-    circuit.power_supply.get_voltage() / total_resistance
+    let (resistance, force) = series_equivalent(&circuit.circuit);
+    (circuit.power_supply.get_voltage() + force) / resistance
 }
 
 impl Circuit {
+    /// Creates and solves a circuit, giving an identifier to every component
+    /// without one.
     pub fn new(power_supply: PowerSupply, circuit: Series) -> Self {
         let mut new_circuit = Circuit {
             power_supply,
             circuit,
             intensity: 0.0,
+            subcircuits: BTreeMap::new(),
+            libraries: Vec::new(),
+            temperature: None,
         };
 
+        new_circuit.assign_ids();
         new_circuit.update_tensions();
         new_circuit.update_intensity();
 
         new_circuit
     }
 
-    pub fn to_json(&self) -> String {
-        serde_json::to_string(&self).unwrap()
-    }
-
-    pub fn update_intensity(&mut self) -> f64 {
+    pub(crate) fn update_intensity(&mut self) -> f64 {
         self.intensity = calculate_current(self);
         self.intensity
     }
 
-    pub fn update_tensions(&mut self) {
-        set_tensions_in_circuit(&mut self.circuit, self.power_supply.get_voltage());
+    pub(crate) fn update_tensions(&mut self) {
+        let temperature = self.get_temperature();
+        set_temperatures(&mut self.circuit, temperature);
+        // every switch of a blocking component changes the operating point,
+        // which is solved again until none switches
+        for _ in 0..MAX_SWITCHING_PASSES {
+            let current = calculate_current(self);
+            let voltage = self.power_supply.get_voltage();
+            set_tensions_in_circuit(&mut self.circuit, current, voltage);
+            if !update_blocking(&mut self.circuit) {
+                break;
+            }
+        }
     }
 
-    pub fn update(&mut self) {
+    pub(crate) fn update(&mut self) {
         self.update_tensions();
         self.update_intensity();
     }
 
+    /// Ambient temperature of the circuit, in °C; components are at
+    /// [`REFERENCE_TEMPERATURE`] when none is given.
+    pub fn get_ambient_temperature(&self) -> Option<f64> {
+        self.temperature
+    }
+
+    /// Sets the ambient temperature and solves the circuit again.
+    pub fn set_ambient_temperature(&mut self, temperature: Option<f64>) {
+        self.temperature = temperature;
+        self.update();
+    }
+
+    // Temperature of the components
+    fn get_temperature(&self) -> f64 {
+        self.temperature.unwrap_or(REFERENCE_TEMPERATURE)
+    }
+
+    /// Current delivered by the power supply, in amperes.
     pub fn get_intensity(&self) -> f64 {
         self.intensity
     }
 
+    /// Elements connected across the power supply.
     pub fn get_series(&self) -> &Series {
         &self.circuit
     }
 
-    pub fn get_mut_series(&mut self) -> &mut Series {
+    pub(crate) fn get_mut_series(&mut self) -> &mut Series {
         &mut self.circuit
     }
 
+    /// Power supply of the circuit.
     pub fn get_power_supply(&self) -> &PowerSupply {
         &self.power_supply
     }
+
+    pub(crate) fn get_mut_power_supply(&mut self) -> &mut PowerSupply {
+        &mut self.power_supply
+    }
+
+    /// Subcircuit definitions available to the circuit, by name.
+    pub fn get_subcircuits(&self) -> &BTreeMap<String, SubcircuitDefinition> {
+        &self.subcircuits
+    }
+
+    pub(crate) fn get_mut_subcircuits(&mut self) -> &mut BTreeMap<String, SubcircuitDefinition> {
+        &mut self.subcircuits
+    }
+
+    /// Library files the subcircuit definitions are read from, relative to
+    /// the circuit file.
+    pub fn get_libraries(&self) -> &[String] {
+        &self.libraries
+    }
+
+    pub(crate) fn set_libraries(&mut self, libraries: Vec<String>) {
+        self.libraries = libraries;
+    }
 }
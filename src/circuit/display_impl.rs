@@ -1,35 +1,101 @@
 use std::fmt::Display;
 
-use crate::circuit::{Circuit, ElectronicComponentTrait, PowerSupply, Resistor, SeriesElement};
+use crate::{
+    circuit::{
+        Circuit, ComponentInfo, Distribution, ElectronicComponentTrait, PluginComponent,
+        PowerSupply, Resistor, SeriesElement, Subcircuit,
+    },
+    units::format_value,
+};
 
 use super::{ElectronicComponent, Series};
 
 impl Display for PowerSupply {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Power Supply (Voltage: {}V)", self.get_voltage())
+        if let Some(id) = self.get_id() {
+            write!(f, "{}: ", id)?;
+        }
+        write!(
+            f,
+            "Power Supply (Voltage: {})",
+            format_value(self.get_voltage(), "V")
+        )
+    }
+}
+
+impl Display for Distribution {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Distribution::Uniform => write!(f, "uniform"),
+            Distribution::Normal => write!(f, "normal"),
+        }
+    }
+}
+
+// Writes the "R1 (label): " prefix of an identified component
+impl Display for ComponentInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (&self.id, &self.label) {
+            (Some(id), Some(label)) => write!(f, "{} ({}): ", id, label),
+            (Some(id), None) => write!(f, "{}: ", id),
+            (None, Some(label)) => write!(f, "({}): ", label),
+            (None, None) => Ok(()),
+        }
     }
 }
 
 impl Display for Resistor {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let tension_rounded = (self.get_tension() * 100.0).round() / 100.0;
         write!(
             f,
-            "Resistor (Resistance: {}Ω, Tension in Circuit: {}V)",
-            self.get_resistance(),
-            tension_rounded
+            "{}Resistor (Resistance: {}, Tension in Circuit: {})",
+            self.get_info(),
+            format_value(self.get_resistance(), "Ω"),
+            format_value(self.get_tension(), "V")
         )
     }
 }
 
 impl Display for dyn ElectronicComponentTrait {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let tension_rounded = (self.get_tension() * 100.0).round() / 100.0;
         write!(
             f,
-            "Resistor (Resistance: {}Ω, Tension in Circuit: {}V)",
-            self.get_resistance(),
-            tension_rounded
+            "Component (Resistance: {}, Tension in Circuit: {})",
+            format_value(self.get_resistance(), "Ω"),
+            format_value(self.get_tension(), "V")
+        )
+    }
+}
+
+impl Display for PluginComponent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}{} ({}, Tension in Circuit: {}{})",
+            self.get_info(),
+            self.get_model().name(),
+            self.get_model().describe(),
+            format_value(self.get_tension(), "V"),
+            if self.is_blocking() { ", blocking" } else { "" }
+        )
+    }
+}
+
+impl Display for Subcircuit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}Subcircuit {} (",
+            self.get_info(),
+            self.get_definition()
+        )?;
+        for (name, value) in self.get_arguments() {
+            write!(f, "{}: {}, ", name, format_value(*value, ""))?;
+        }
+        write!(
+            f,
+            "Tension in Circuit: {})",
+            format_value(self.get_voltage(), "V")
         )
     }
 }
@@ -38,6 +104,7 @@ impl Display for ElectronicComponent {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             ElectronicComponent::Resistor(resistor) => write!(f, "{}", resistor),
+            ElectronicComponent::Plugin(component) => write!(f, "{}", component),
         }
     }
 }
@@ -52,13 +119,33 @@ impl Display for SeriesElement {
                 write!(f, "Parallel : ({} ...)", first_element)?;
                 Ok(())
             }
+            SeriesElement::Subcircuit(subcircuit) => write!(f, "{}", subcircuit),
         }
     }
 }
 
-fn display_series(series: &Series, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+fn display_series(
+    series: &Series,
+    depth: usize,
+    f: &mut std::fmt::Formatter<'_>,
+) -> std::fmt::Result {
+    let indent = "  ".repeat(depth);
+
     for element in series {
-        write!(f, "{}", element)?;
+        match element {
+            SeriesElement::Component(component) => writeln!(f, "{}{}", indent, component)?,
+            SeriesElement::Parallel(branches) => {
+                writeln!(f, "{}Parallel:", indent)?;
+                for (i, branch) in branches.iter().enumerate() {
+                    writeln!(f, "{}  Branch {}:", indent, i)?;
+                    display_series(branch, depth + 2, f)?;
+                }
+            }
+            SeriesElement::Subcircuit(subcircuit) => {
+                writeln!(f, "{}{}", indent, subcircuit)?;
+                display_series(subcircuit.get_series(), depth + 1, f)?;
+            }
+        }
     }
     Ok(())
 }
@@ -66,21 +153,12 @@ fn display_series(series: &Series, f: &mut std::fmt::Formatter<'_>) -> std::fmt:
 impl Display for Circuit {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         // print circuit voltage
-        let voltage = self.get_power_supply().get_voltage();
-        let voltage_rounded = (voltage * 100.0).round() / 100.0;
-        writeln!(f, "Power Supply (Voltage: {}V)", voltage_rounded)?;
-
-        // print circuit components
-        for series in self.get_series() {
-            match series {
-                SeriesElement::Component(component) => writeln!(f, "{}", component)?,
-                SeriesElement::Parallel(series) => {
-                    display_series(series.first().unwrap(), f)?;
-                    writeln!(f, "")?;
-                }
-            }
+        writeln!(f, "{}", self.get_power_supply())?;
+        if let Some(temperature) = self.get_ambient_temperature() {
+            writeln!(f, "Ambient temperature: {}°C", temperature)?;
         }
 
-        Ok(())
+        // print circuit components
+        display_series(self.get_series(), 0, f)
     }
 }
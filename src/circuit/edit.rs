@@ -0,0 +1,381 @@
+use super::{Circuit, Series, SeriesElement};
+
+// A series is addressed by the path of (element index, branch index) pairs
+// leading to it from the top-level series; the empty path is the circuit itself.
+// The series of a subcircuit instance is its single branch, and can't be edited.
+
+const INVALID_PATH: &str = "Invalid position in the circuit";
+const SUBCIRCUIT_EDIT: &str =
+    "The elements of a subcircuit instance are edited through its definition";
+
+fn series_at<'a>(series: &'a Series, path: &[usize]) -> Option<&'a Series> {
+    match path {
+        [] => Some(series),
+        [element, branch, rest @ ..] => match series.get(*element)? {
+            SeriesElement::Parallel(branches) => series_at(branches.get(*branch)?, rest),
+            SeriesElement::Subcircuit(subcircuit) if *branch == 0 => {
+                series_at(subcircuit.get_series(), rest)
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn series_at_mut<'a>(series: &'a mut Series, path: &[usize]) -> Option<&'a mut Series> {
+    match path {
+        [] => Some(series),
+        [element, branch, rest @ ..] => match series.get_mut(*element)? {
+            SeriesElement::Parallel(branches) => series_at_mut(branches.get_mut(*branch)?, rest),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+// Removes branches left empty by an edit, and parallels left without branches,
+// as an empty branch would short-circuit its parallel.
+fn prune_empty(series: &mut Series) {
+    series.iter_mut().for_each(|element| {
+        if let SeriesElement::Parallel(branches) = element {
+            branches.iter_mut().for_each(prune_empty);
+            branches.retain(|branch| !branch.is_empty());
+        }
+    });
+    series.retain(|element| match element {
+        SeriesElement::Parallel(branches) => !branches.is_empty(),
+        _ => true,
+    });
+}
+
+impl Circuit {
+    pub(super) fn series_mut(&mut self, path: &[usize]) -> Result<&mut Series, String> {
+        if self.get_series_at(path).is_none() {
+            return Err(String::from(INVALID_PATH));
+        }
+        series_at_mut(self.get_mut_series(), path).ok_or_else(|| String::from(SUBCIRCUIT_EDIT))
+    }
+
+    fn branches_mut(&mut self, path: &[usize], index: usize) -> Result<&mut Vec<Series>, String> {
+        match self.series_mut(path)?.get_mut(index) {
+            Some(SeriesElement::Parallel(branches)) => Ok(branches),
+            _ => Err(String::from("The selected element is not a parallel")),
+        }
+    }
+
+    fn after_edit(&mut self) {
+        prune_empty(self.get_mut_series());
+        self.assign_ids();
+        self.update();
+    }
+
+    /// Returns the series at `path`.
+    ///
+    /// A path lists, for every nested parallel, the index of the parallel in its
+    /// series followed by the index of the branch; the empty path is the series
+    /// connected across the power supply. The elements of a subcircuit
+    /// instance are its branch 0.
+    pub fn get_series_at(&self, path: &[usize]) -> Option<&Series> {
+        series_at(self.get_series(), path)
+    }
+
+    /// Sets the voltage of the power supply and solves the circuit again.
+    pub fn set_voltage(&mut self, voltage: f64) {
+        self.get_mut_power_supply().set_voltage(voltage);
+        self.update();
+    }
+
+    /// Sets the resistance of the component at `index` of the series at `path`.
+    pub fn set_resistance(
+        &mut self,
+        path: &[usize],
+        index: usize,
+        resistance: f64,
+    ) -> Result<(), String> {
+        match self.series_mut(path)?.get_mut(index) {
+            Some(SeriesElement::Component(component)) => component.set_resistance(resistance)?,
+            _ => return Err(String::from("The selected element is not a component")),
+        }
+        self.update();
+        Ok(())
+    }
+
+    /// Inserts `element` at `index` of the series at `path`.
+    pub fn insert_element(
+        &mut self,
+        path: &[usize],
+        index: usize,
+        element: SeriesElement,
+    ) -> Result<(), String> {
+        let series = self.series_mut(path)?;
+        if index > series.len() {
+            return Err(String::from(INVALID_PATH));
+        }
+        series.insert(index, element);
+        self.after_edit();
+        Ok(())
+    }
+
+    /// Removes and returns the element at `index` of the series at `path`.
+    ///
+    /// Parallels left without branches are removed as well.
+    pub fn remove_element(
+        &mut self,
+        path: &[usize],
+        index: usize,
+    ) -> Result<SeriesElement, String> {
+        let series = self.series_mut(path)?;
+        if index >= series.len() {
+            return Err(String::from(INVALID_PATH));
+        }
+        let element = series.remove(index);
+        self.after_edit();
+        Ok(element)
+    }
+
+    /// Moves the element at `index` of the series at `path` to `target`.
+    pub fn move_element(
+        &mut self,
+        path: &[usize],
+        index: usize,
+        target: usize,
+    ) -> Result<(), String> {
+        let series = self.series_mut(path)?;
+        if index >= series.len() || target >= series.len() {
+            return Err(String::from("The element can't be moved further"));
+        }
+        let element = series.remove(index);
+        series.insert(target, element);
+        self.after_edit();
+        Ok(())
+    }
+
+    /// Replaces `count` elements starting at `index` by a parallel whose only
+    /// branch holds them; further branches are added with [`Circuit::add_branch`].
+    pub fn wrap_in_parallel(
+        &mut self,
+        path: &[usize],
+        index: usize,
+        count: usize,
+    ) -> Result<(), String> {
+        let series = self.series_mut(path)?;
+        if count == 0 || index + count > series.len() {
+            return Err(String::from("Invalid selection to wrap"));
+        }
+        let branch: Series = series.drain(index..index + count).collect();
+        series.insert(index, SeriesElement::new_parallel(vec![branch]));
+        self.after_edit();
+        Ok(())
+    }
+
+    /// Adds a branch to the parallel at `index` of the series at `path`; the
+    /// branch can't be empty, as it would short-circuit the parallel.
+    pub fn add_branch(
+        &mut self,
+        path: &[usize],
+        index: usize,
+        branch: Series,
+    ) -> Result<(), String> {
+        if branch.is_empty() {
+            return Err(String::from("Can't add an empty branch"));
+        }
+        self.branches_mut(path, index)?.push(branch);
+        self.after_edit();
+        Ok(())
+    }
+
+    /// Removes and returns a branch of the parallel at `index` of the series at
+    /// `path`; the last branch can't be removed.
+    pub fn remove_branch(
+        &mut self,
+        path: &[usize],
+        index: usize,
+        branch: usize,
+    ) -> Result<Series, String> {
+        let branches = self.branches_mut(path, index)?;
+        if branch >= branches.len() {
+            return Err(String::from(INVALID_PATH));
+        }
+        if branches.len() == 1 {
+            return Err(String::from(
+                "Can't remove the last branch, delete the parallel instead",
+            ));
+        }
+        let removed = branches.remove(branch);
+        self.after_edit();
+        Ok(removed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::{ElectronicComponent, ElectronicComponentTrait, PowerSupply};
+
+    fn resistor(resistance: f64) -> SeriesElement {
+        SeriesElement::new(ElectronicComponent::new_resistor(resistance))
+    }
+
+    fn parallel(branches: Vec<Series>) -> SeriesElement {
+        SeriesElement::new_parallel(branches)
+    }
+
+    // "R10 + (R20 || R30)" description of the resistances of a series
+    fn describe(series: &Series) -> String {
+        let elements: Vec<String> = series
+            .iter()
+            .map(|element| match element {
+                SeriesElement::Component(component) => format!("R{}", component.get_resistance()),
+                SeriesElement::Parallel(branches) => {
+                    let branches: Vec<String> = branches.iter().map(describe).collect();
+                    format!("({})", branches.join(" || "))
+                }
+                SeriesElement::Subcircuit(subcircuit) => {
+                    format!("({})", describe(subcircuit.get_series()))
+                }
+            })
+            .collect();
+        elements.join(" + ")
+    }
+
+    #[test]
+    fn edits_the_top_level_series() {
+        let mut circuit =
+            Circuit::new(PowerSupply::new(12.0), vec![resistor(10.0), resistor(20.0)]);
+
+        circuit.insert_element(&[], 2, resistor(30.0)).unwrap();
+        assert_eq!(describe(circuit.get_series()), "R10 + R20 + R30");
+        assert_eq!(circuit.get_intensity(), 0.2);
+
+        circuit.move_element(&[], 2, 0).unwrap();
+        assert_eq!(describe(circuit.get_series()), "R30 + R10 + R20");
+
+        circuit.wrap_in_parallel(&[], 1, 2).unwrap();
+        assert_eq!(describe(circuit.get_series()), "R30 + (R10 + R20)");
+
+        circuit.add_branch(&[], 1, vec![resistor(30.0)]).unwrap();
+        assert_eq!(describe(circuit.get_series()), "R30 + (R10 + R20 || R30)");
+        assert_eq!(circuit.get_intensity(), 12.0 / 45.0);
+
+        let removed = circuit.remove_branch(&[], 1, 0).unwrap();
+        assert_eq!(removed.len(), 2);
+        assert_eq!(describe(circuit.get_series()), "R30 + (R30)");
+
+        circuit.remove_element(&[], 0).unwrap();
+        assert_eq!(describe(circuit.get_series()), "(R30)");
+    }
+
+    #[test]
+    fn edits_nested_series() {
+        // R10 + (R20 + (R30 || R40) || R50)
+        let mut circuit = Circuit::new(
+            PowerSupply::new(12.0),
+            vec![
+                resistor(10.0),
+                parallel(vec![
+                    vec![
+                        resistor(20.0),
+                        parallel(vec![vec![resistor(30.0)], vec![resistor(40.0)]]),
+                    ],
+                    vec![resistor(50.0)],
+                ]),
+            ],
+        );
+        let inner = [1, 0, 1, 1];
+
+        circuit.set_resistance(&inner, 0, 60.0).unwrap();
+        circuit.insert_element(&inner, 0, resistor(5.0)).unwrap();
+        assert_eq!(
+            describe(circuit.get_series()),
+            "R10 + (R20 + (R30 || R5 + R60) || R50)"
+        );
+        assert_eq!(circuit.get_series_at(&inner).unwrap().len(), 2);
+
+        circuit.move_element(&[1, 0], 1, 0).unwrap();
+        assert_eq!(
+            describe(circuit.get_series()),
+            "R10 + ((R30 || R5 + R60) + R20 || R50)"
+        );
+        assert_eq!(
+            describe(circuit.get_series_at(&[1, 0, 0, 1]).unwrap()),
+            "R5 + R60"
+        );
+
+        // components keep their identifiers wherever they move
+        let position = |id| {
+            let (_, path, index) = circuit.locate_component(id).unwrap();
+            (path, index)
+        };
+        assert_eq!(position("R2"), (vec![1, 0], 1));
+        assert_eq!(position("R6"), (vec![1, 0, 0, 1], 0));
+        assert_eq!(position("R4"), (vec![1, 0, 0, 1], 1));
+    }
+
+    #[test]
+    fn prunes_branches_left_empty() {
+        // R10 + (R20 || R30 + (R40 || R50))
+        let mut circuit = Circuit::new(
+            PowerSupply::new(12.0),
+            vec![
+                resistor(10.0),
+                parallel(vec![
+                    vec![resistor(20.0)],
+                    vec![
+                        resistor(30.0),
+                        parallel(vec![vec![resistor(40.0)], vec![resistor(50.0)]]),
+                    ],
+                ]),
+            ],
+        );
+
+        // removing the last element of a branch removes the branch
+        circuit.remove_element(&[1, 0], 0).unwrap();
+        assert_eq!(describe(circuit.get_series()), "R10 + (R30 + (R40 || R50))");
+        circuit.remove_element(&[1, 0, 1, 0], 0).unwrap();
+        assert_eq!(describe(circuit.get_series()), "R10 + (R30 + (R50))");
+
+        // and a parallel left without branches is removed as well
+        circuit.remove_element(&[1, 0, 1, 0], 0).unwrap();
+        assert_eq!(describe(circuit.get_series()), "R10 + (R30)");
+        circuit.remove_element(&[1, 0], 0).unwrap();
+        assert_eq!(describe(circuit.get_series()), "R10");
+    }
+
+    #[test]
+    fn rejects_invalid_edits() {
+        let mut circuit = Circuit::new(
+            PowerSupply::new(12.0),
+            vec![
+                resistor(10.0),
+                parallel(vec![vec![resistor(20.0)], vec![resistor(30.0)]]),
+            ],
+        );
+
+        assert_eq!(
+            circuit.remove_element(&[2, 0], 0).err().as_deref(),
+            Some(INVALID_PATH)
+        );
+        assert!(circuit.remove_element(&[1, 2], 0).is_err());
+        assert!(circuit.insert_element(&[], 3, resistor(1.0)).is_err());
+        assert!(circuit.move_element(&[], 1, 2).is_err());
+        assert!(circuit.wrap_in_parallel(&[], 1, 0).is_err());
+        assert!(circuit.wrap_in_parallel(&[], 1, 2).is_err());
+        assert!(circuit.add_branch(&[], 0, vec![resistor(1.0)]).is_err());
+        assert_eq!(
+            circuit.add_branch(&[], 1, Vec::new()).err().as_deref(),
+            Some("Can't add an empty branch")
+        );
+        assert!(circuit.set_resistance(&[], 1, 5.0).is_err());
+        for resistance in [0.0, -10.0, f64::INFINITY, f64::NAN] {
+            assert!(circuit.set_resistance(&[], 0, resistance).is_err());
+        }
+        assert!(circuit.remove_branch(&[], 1, 2).is_err());
+        assert_eq!(describe(circuit.get_series()), "R10 + (R20 || R30)");
+
+        circuit.wrap_in_parallel(&[], 0, 1).unwrap();
+        assert_eq!(
+            circuit.remove_branch(&[], 0, 0).err().as_deref(),
+            Some("Can't remove the last branch, delete the parallel instead")
+        );
+    }
+}
@@ -0,0 +1,782 @@
+use std::{collections::BTreeMap, fmt::Display, fs, path::Path};
+
+use schemars::{schema_for, JsonSchema};
+use serde::{de::Error, Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{
+    ids::check_ids,
+    subcircuit::{default_terminals, Subcircuit, SubcircuitDefinition},
+    Circuit, ComponentInfo, ElectronicComponent, ElectronicComponentTrait, PluginComponent,
+    PowerSupply, Resistor, Series, SeriesElement,
+};
+
+/// Version of the circuit files written by [`Circuit::to_format`].
+pub const FORMAT_VERSION: u64 = 2;
+
+/// Electronic circuit simulator file
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[schemars(title = "Circuit")]
+struct CircuitFile {
+    /// Version of the file format
+    #[schemars(range(min = 2, max = 2))]
+    version: u64,
+    design: Design,
+    #[serde(default, skip_deserializing, skip_serializing_if = "Option::is_none")]
+    results: Option<Results>,
+}
+
+/// Inputs of the circuit
+#[derive(Serialize, Deserialize, JsonSchema)]
+struct Design {
+    /// Files holding further subcircuit definitions, relative to this file
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    libraries: Vec<String>,
+    /// Subcircuit definitions, by name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    subcircuits: BTreeMap<String, DefinitionFile>,
+    supply: PowerSupply,
+    /// Ambient temperature in °C; components are at 25°C when missing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    temperature: Option<f64>,
+    /// Elements connected in series across the power supply
+    circuit: Vec<DesignElement>,
+}
+
+/// Reusable block of elements, instantiated by subcircuit elements
+#[derive(Serialize, Deserialize, JsonSchema)]
+struct DefinitionFile {
+    /// Parameters with their default values, used as "$name" values in the
+    /// elements
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    parameters: BTreeMap<String, Quantity>,
+    /// Names of the input and output terminals
+    #[serde(default = "default_terminals")]
+    terminals: [String; 2],
+    /// Elements connected in series between the terminals
+    #[schemars(with = "Vec<DesignElement>")]
+    circuit: Vec<Value>,
+}
+
+/// Library of subcircuit definitions, shared by several circuit files
+#[derive(Serialize, Deserialize)]
+struct LibraryFile {
+    version: u64,
+    subcircuits: BTreeMap<String, DefinitionFile>,
+}
+
+/// Circuit file written before the format was versioned, read only to be
+/// migrated
+#[derive(Deserialize)]
+struct LegacyFile {
+    power_supply: PowerSupply,
+    circuit: Series,
+}
+
+/// Value given as a number or in engineering notation
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Copy)]
+#[serde(transparent)]
+struct Quantity(
+    #[serde(deserialize_with = "crate::units::deserialize_value")]
+    #[schemars(schema_with = "crate::units::value_schema")]
+    f64,
+);
+
+/// Element of a series: a component or a group of parallel branches
+#[derive(Serialize, JsonSchema)]
+#[serde(untagged)]
+enum DesignElement {
+    Builtin(BuiltinElement),
+    Plugin(PluginComponent),
+}
+
+/// Resistor, group of parallel branches or subcircuit instance
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum BuiltinElement {
+    Resistor {
+        #[serde(flatten)]
+        info: ComponentInfo,
+        /// Resistance in ohms
+        #[serde(deserialize_with = "crate::circuit::deserialize_resistance")]
+        #[schemars(schema_with = "crate::units::value_schema")]
+        resistance: f64,
+    },
+    Parallel {
+        /// Series connected in parallel
+        branches: Vec<Vec<DesignElement>>,
+    },
+    Subcircuit {
+        #[serde(flatten)]
+        info: ComponentInfo,
+        /// Name of the subcircuit definition
+        definition: String,
+        /// Values of the parameters of the definition
+        #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+        arguments: BTreeMap<String, Quantity>,
+    },
+}
+
+// Built-in elements are read on their own, for their error messages
+impl<'de> Deserialize<'de> for DesignElement {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let value = Value::deserialize(deserializer)?;
+        match value.get("type").and_then(Value::as_str) {
+            Some("resistor") | Some("parallel") | Some("subcircuit") | None => {
+                BuiltinElement::deserialize(value)
+                    .map(DesignElement::Builtin)
+                    .map_err(D::Error::custom)
+            }
+            Some(_) => PluginComponent::deserialize(value)
+                .map(DesignElement::Plugin)
+                .map_err(D::Error::custom),
+        }
+    }
+}
+
+/// Computed values, ignored when loading the file and left out when the
+/// circuit has no finite solution
+#[derive(Serialize, Deserialize, JsonSchema)]
+struct Results {
+    /// Current delivered by the power supply, in amperes
+    intensity: f64,
+    components: Vec<ComponentResult>,
+    /// Totals of the subcircuit instances
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    subcircuits: Vec<SubcircuitResult>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+struct ComponentResult {
+    id: Option<String>,
+    /// Voltage across the component, in volts
+    voltage: f64,
+    /// Current through the component, in amperes
+    current: f64,
+    /// Dissipated power, in watts
+    power: f64,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+struct SubcircuitResult {
+    id: Option<String>,
+    definition: String,
+    /// Voltage between the terminals, in volts
+    voltage: f64,
+    /// Current through the instance, in amperes
+    current: f64,
+    /// Power dissipated by the elements of the instance, in watts
+    power: f64,
+}
+
+/// JSON Schema of the circuit files, for editor validation and completion.
+pub fn json_schema() -> String {
+    serde_json::to_string_pretty(&schema_for!(CircuitFile)).unwrap()
+}
+
+fn design_series(series: &Series) -> Vec<DesignElement> {
+    series
+        .iter()
+        .map(|element| match element {
+            SeriesElement::Component(ElectronicComponent::Resistor(resistor)) => {
+                DesignElement::Builtin(BuiltinElement::Resistor {
+                    info: resistor.get_info().clone(),
+                    resistance: resistor.get_nominal_resistance(),
+                })
+            }
+            SeriesElement::Component(ElectronicComponent::Plugin(component)) => {
+                DesignElement::Plugin(component.clone())
+            }
+            SeriesElement::Parallel(branches) => DesignElement::Builtin(BuiltinElement::Parallel {
+                branches: branches.iter().map(design_series).collect(),
+            }),
+            SeriesElement::Subcircuit(subcircuit) => {
+                DesignElement::Builtin(BuiltinElement::Subcircuit {
+                    info: subcircuit.get_info().clone(),
+                    definition: String::from(subcircuit.get_definition()),
+                    arguments: subcircuit
+                        .get_arguments()
+                        .iter()
+                        .map(|(name, value)| (name.clone(), Quantity(*value)))
+                        .collect(),
+                })
+            }
+        })
+        .collect()
+}
+
+// Reads a single component written as in circuit files
+pub(crate) fn component_from_value(value: Value) -> Result<ElectronicComponent, String> {
+    match serde_json::from_value(value).map_err(|e| e.to_string())? {
+        DesignElement::Builtin(BuiltinElement::Resistor { info, resistance }) => {
+            let mut resistor = Resistor::new(resistance);
+            resistor.set_info(info);
+            Ok(ElectronicComponent::Resistor(resistor))
+        }
+        DesignElement::Plugin(component) => Ok(ElectronicComponent::Plugin(component)),
+        DesignElement::Builtin(_) => Err(String::from("expected a single component")),
+    }
+}
+
+// Design elements of `series`, as stored in subcircuit definitions
+pub(crate) fn design_values(series: &Series) -> Vec<Value> {
+    design_series(series)
+        .iter()
+        .map(|element| serde_json::to_value(element).unwrap())
+        .collect()
+}
+
+// `stack` holds the definitions being expanded, to reject recursive ones
+fn build_series(
+    elements: Vec<DesignElement>,
+    definitions: &BTreeMap<String, SubcircuitDefinition>,
+    stack: &mut Vec<String>,
+) -> Result<Series, String> {
+    elements
+        .into_iter()
+        .map(|element| match element {
+            DesignElement::Builtin(BuiltinElement::Resistor { info, resistance }) => {
+                let mut resistor = Resistor::new(resistance);
+                resistor.set_info(info);
+                Ok(SeriesElement::new(ElectronicComponent::Resistor(resistor)))
+            }
+            DesignElement::Builtin(BuiltinElement::Parallel { branches }) => branches
+                .into_iter()
+                .map(|branch| build_series(branch, definitions, stack))
+                .collect::<Result<_, _>>()
+                .map(SeriesElement::new_parallel),
+            DesignElement::Builtin(BuiltinElement::Subcircuit {
+                info,
+                definition,
+                arguments,
+            }) => instantiate(
+                definitions,
+                &definition,
+                arguments
+                    .into_iter()
+                    .map(|(name, value)| (name, value.0))
+                    .collect(),
+                info,
+                stack,
+            ),
+            DesignElement::Plugin(component) => {
+                Ok(SeriesElement::new(ElectronicComponent::Plugin(component)))
+            }
+        })
+        .collect()
+}
+
+// Expands the definition `name` into an instance, with identifiers local to
+// the instance until it gets its own
+pub(crate) fn instantiate(
+    definitions: &BTreeMap<String, SubcircuitDefinition>,
+    name: &str,
+    arguments: BTreeMap<String, f64>,
+    info: ComponentInfo,
+    stack: &mut Vec<String>,
+) -> Result<SeriesElement, String> {
+    let definition = definitions
+        .get(name)
+        .ok_or_else(|| format!("unknown subcircuit {}", name))?;
+    if stack.iter().any(|expanding| expanding == name) {
+        return Err(format!("the subcircuit {} contains itself", name));
+    }
+
+    let elements = definition
+        .expand(&arguments)?
+        .into_iter()
+        .map(serde_json::from_value)
+        .collect::<Result<Vec<DesignElement>, _>>()
+        .map_err(|e| format!("invalid subcircuit {}: {}", name, e))?;
+    stack.push(String::from(name));
+    let series = build_series(elements, definitions, stack);
+    stack.pop();
+    let mut series = series?;
+    super::ids::assign_local_ids(&mut series);
+
+    Ok(SeriesElement::Subcircuit(Subcircuit::new(
+        definition, arguments, info, series,
+    )))
+}
+
+fn collect_results(
+    series: &Series,
+    components: &mut Vec<ComponentResult>,
+    subcircuits: &mut Vec<SubcircuitResult>,
+) {
+    for element in series {
+        match element {
+            SeriesElement::Component(component) => components.push(ComponentResult {
+                id: component.get_id().map(String::from),
+                voltage: component.get_tension(),
+                current: component.get_current(),
+                power: component.get_tension() * component.get_current(),
+            }),
+            SeriesElement::Parallel(branches) => branches
+                .iter()
+                .for_each(|branch| collect_results(branch, components, subcircuits)),
+            SeriesElement::Subcircuit(subcircuit) => {
+                let first = components.len();
+                collect_results(subcircuit.get_series(), components, subcircuits);
+                subcircuits.push(SubcircuitResult {
+                    id: subcircuit.get_id().map(String::from),
+                    definition: String::from(subcircuit.get_definition()),
+                    voltage: subcircuit.get_voltage(),
+                    current: subcircuit.get_current(),
+                    power: components[first..].iter().map(|c| c.power).sum(),
+                });
+            }
+        }
+    }
+}
+
+impl Results {
+    fn is_finite(&self) -> bool {
+        self.intensity.is_finite()
+            && self.components.iter().all(|component| {
+                [component.voltage, component.current, component.power]
+                    .iter()
+                    .all(|value| value.is_finite())
+            })
+            && self.subcircuits.iter().all(|subcircuit| {
+                [subcircuit.voltage, subcircuit.current, subcircuit.power]
+                    .iter()
+                    .all(|value| value.is_finite())
+            })
+    }
+}
+
+impl DefinitionFile {
+    fn into_definition(self, name: &str, library: Option<&str>) -> SubcircuitDefinition {
+        SubcircuitDefinition {
+            name: String::from(name),
+            parameters: self
+                .parameters
+                .into_iter()
+                .map(|(name, value)| (name, value.0))
+                .collect(),
+            terminals: self.terminals,
+            circuit: self.circuit,
+            library: library.map(String::from),
+        }
+    }
+
+    fn from_definition(definition: &SubcircuitDefinition) -> Self {
+        DefinitionFile {
+            parameters: definition
+                .parameters
+                .iter()
+                .map(|(name, value)| (name.clone(), Quantity(*value)))
+                .collect(),
+            terminals: definition.terminals.clone(),
+            circuit: definition.circuit.clone(),
+        }
+    }
+}
+
+// Reads the definitions of the library at `path`, relative to `base`
+fn read_library(
+    base: Option<&Path>,
+    path: &str,
+    definitions: &mut BTreeMap<String, SubcircuitDefinition>,
+) -> Result<(), String> {
+    let full_path = base.map_or_else(|| Path::new(path).to_path_buf(), |base| base.join(path));
+    let text = fs::read_to_string(&full_path)
+        .map_err(|e| format!("cannot read the library {}: {}", path, e))?;
+    let value = FileFormat::from_path(path).parse(&text)?;
+    let library: LibraryFile =
+        serde_json::from_value(value).map_err(|e| format!("invalid library {}: {}", path, e))?;
+    if library.version != FORMAT_VERSION {
+        return Err(format!(
+            "unsupported file format version {} in the library {}",
+            library.version, path
+        ));
+    }
+
+    for (name, definition) in library.subcircuits {
+        let definition = definition.into_definition(&name, Some(path));
+        definitions.insert(name, definition);
+    }
+    Ok(())
+}
+
+/// Serialization format of a circuit file, picked from its extension.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FileFormat {
+    /// JSON, the default.
+    Json,
+    /// YAML, for `.yaml` and `.yml` files.
+    Yaml,
+    /// TOML, for `.toml` files.
+    Toml,
+}
+
+impl FileFormat {
+    /// Format of the file at `path`, JSON unless the extension says otherwise.
+    pub fn from_path(path: &str) -> FileFormat {
+        match Path::new(path)
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(str::to_lowercase)
+            .as_deref()
+        {
+            Some("yaml") | Some("yml") => FileFormat::Yaml,
+            Some("toml") => FileFormat::Toml,
+            _ => FileFormat::Json,
+        }
+    }
+
+    pub(crate) fn parse(self, text: &str) -> Result<Value, String> {
+        match self {
+            FileFormat::Json => serde_json::from_str(text).map_err(|e| e.to_string()),
+            FileFormat::Yaml => serde_yaml::from_str(text).map_err(|e| e.to_string()),
+            FileFormat::Toml => toml::from_str(text).map_err(|e| e.to_string()),
+        }
+    }
+}
+
+impl Display for FileFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FileFormat::Json => write!(f, "JSON"),
+            FileFormat::Yaml => write!(f, "YAML"),
+            FileFormat::Toml => write!(f, "TOML"),
+        }
+    }
+}
+
+impl Circuit {
+    fn to_file(&self) -> CircuitFile {
+        let mut components = Vec::new();
+        let mut subcircuits = Vec::new();
+        collect_results(self.get_series(), &mut components, &mut subcircuits);
+
+        CircuitFile {
+            version: FORMAT_VERSION,
+            design: Design {
+                libraries: self.get_libraries().to_vec(),
+                subcircuits: self
+                    .get_subcircuits()
+                    .iter()
+                    .filter(|(_, definition)| definition.library.is_none())
+                    .map(|(name, definition)| {
+                        (name.clone(), DefinitionFile::from_definition(definition))
+                    })
+                    .collect(),
+                supply: self.get_power_supply().clone(),
+                temperature: self.get_ambient_temperature(),
+                circuit: design_series(self.get_series()),
+            },
+            results: Some(Results {
+                intensity: self.get_intensity(),
+                components,
+                subcircuits,
+            })
+            .filter(Results::is_finite),
+        }
+    }
+
+    /// Writes the circuit file as pretty-printed JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(&self.to_file()).unwrap()
+    }
+
+    /// Writes the circuit file, along with the solved values, in `format`.
+    pub fn to_format(&self, format: FileFormat) -> Result<String, String> {
+        match format {
+            FileFormat::Json => Ok(self.to_json()),
+            FileFormat::Yaml => serde_yaml::to_string(&self.to_file()).map_err(|e| e.to_string()),
+            FileFormat::Toml => toml::to_string(&self.to_file()).map_err(|e| e.to_string()),
+        }
+    }
+
+    // Reads a circuit file, migrating files written before the format was
+    // versioned; libraries are looked up relative to `base`
+    fn from_value(value: Value, base: Option<&Path>) -> Result<Self, String> {
+        let mut circuit: Circuit = match value.get("version").map(Value::as_u64) {
+            None => {
+                let legacy: LegacyFile =
+                    serde_json::from_value(value).map_err(|e| e.to_string())?;
+                check_ids(&legacy.power_supply, &legacy.circuit)?;
+                Circuit::new(legacy.power_supply, legacy.circuit)
+            }
+            Some(Some(FORMAT_VERSION)) => {
+                let file: CircuitFile = serde_json::from_value(value).map_err(|e| e.to_string())?;
+                let design = file.design;
+
+                let mut definitions = BTreeMap::new();
+                for library in &design.libraries {
+                    read_library(base, library, &mut definitions)?;
+                }
+                for (name, definition) in design.subcircuits {
+                    let definition = definition.into_definition(&name, None);
+                    definitions.insert(name, definition);
+                }
+
+                let series = build_series(design.circuit, &definitions, &mut Vec::new())?;
+                check_ids(&design.supply, &series)?;
+                let mut circuit = Circuit::new(design.supply, series);
+                *circuit.get_mut_subcircuits() = definitions;
+                circuit.set_libraries(design.libraries);
+                circuit.set_ambient_temperature(design.temperature);
+                circuit
+            }
+            Some(version) => {
+                return Err(format!(
+                    "unsupported file format version {}",
+                    version.map_or(String::from("?"), |version| version.to_string())
+                ))
+            }
+        };
+
+        circuit.assign_ids();
+        circuit.update();
+        Ok(circuit)
+    }
+
+    /// Reads a circuit file, migrating files written before the format was
+    /// versioned. Libraries of subcircuits are looked up relative to the working
+    /// directory.
+    pub fn from_format(text: &str, format: FileFormat) -> Result<Self, String> {
+        Circuit::from_value(format.parse(text)?, None)
+    }
+
+    /// Reads the circuit file at `path`, in the format given by its extension;
+    /// libraries of subcircuits are looked up relative to its directory.
+    pub fn from_file(path: &str) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let base = Path::new(path).parent();
+        Circuit::from_value(FileFormat::from_path(path).parse(&text)?, base)
+    }
+
+    /// Whether `text` is a circuit file written before the format was versioned.
+    pub fn is_legacy(text: &str, format: FileFormat) -> bool {
+        format
+            .parse(text)
+            .is_ok_and(|value| value.get("version").is_none())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::CircuitBuilder;
+
+    const FORMATS: [FileFormat; 3] = [FileFormat::Json, FileFormat::Yaml, FileFormat::Toml];
+
+    const LEGACY: &str = r#"{
+        "power_supply": { "voltage": 12.0 },
+        "intensity": 0.0,
+        "circuit": [
+            { "Component": { "Resistor": { "resistance": 10.0, "tension_in_circuit": 0.0 } } },
+            { "Parallel": [
+                [{ "Component": { "Resistor": { "resistance": 20.0, "tension_in_circuit": 0.0 } } }],
+                [{ "Component": { "Resistor": { "resistance": 30.0, "tension_in_circuit": 0.0 } } }]
+            ] }
+        ]
+    }"#;
+
+    fn sample() -> Circuit {
+        let mut resistor = Resistor::new(4700.0);
+        resistor.set_info(ComponentInfo {
+            id: Some(String::from("R10")),
+            label: Some(String::from("Pull-up")),
+            notes: Some(String::from("0.25 W")),
+            ..ComponentInfo::default()
+        });
+
+        CircuitBuilder::new()
+            .supply(9.0)
+            .component(ElectronicComponent::Resistor(resistor))
+            .parallel(|p| {
+                p.branch(|b| {
+                    b.resistor(100.0)
+                        .parallel(|p| p.branch(|b| b.resistor(10.0)).branch(|b| b.resistor(22.0)))
+                })
+                .branch(|b| b.resistor(330.0))
+            })
+            .build()
+    }
+
+    #[test]
+    fn round_trips_through_every_format() {
+        let circuit = sample();
+
+        for format in FORMATS {
+            let text = circuit.to_format(format).unwrap();
+            let loaded = Circuit::from_format(&text, format).unwrap();
+            assert_eq!(loaded.to_json(), circuit.to_json(), "{} round trip", format);
+        }
+    }
+
+    #[test]
+    fn round_trips_circuits_without_a_solution() {
+        // an empty circuit draws an infinite current
+        let circuit = Circuit::new(PowerSupply::new(12.0), Vec::new());
+        assert!(!circuit.get_intensity().is_finite());
+
+        for format in FORMATS {
+            let text = circuit.to_format(format).unwrap();
+            assert!(!text.contains("results"), "{} results", format);
+            let loaded = Circuit::from_format(&text, format).unwrap();
+            assert_eq!(loaded.to_json(), circuit.to_json(), "{} round trip", format);
+        }
+    }
+
+    #[test]
+    fn ignores_the_results_when_loading() {
+        let text = r#"{ "version": 2, "design": { "supply": { "voltage": 12 }, "circuit": [] },
+            "results": { "intensity": null, "components": [] } }"#;
+        assert!(Circuit::from_format(text, FileFormat::Json).is_ok());
+    }
+
+    #[test]
+    fn converts_between_formats() {
+        let circuit = sample();
+        let yaml = circuit.to_format(FileFormat::Yaml).unwrap();
+        let from_yaml = Circuit::from_format(&yaml, FileFormat::Yaml).unwrap();
+        let toml = from_yaml.to_format(FileFormat::Toml).unwrap();
+        let from_toml = Circuit::from_format(&toml, FileFormat::Toml).unwrap();
+
+        assert_eq!(from_toml.to_json(), circuit.to_json());
+    }
+
+    #[test]
+    fn migrates_legacy_files_in_every_format() {
+        let legacy = Circuit::from_format(LEGACY, FileFormat::Json).unwrap();
+        assert!(Circuit::is_legacy(LEGACY, FileFormat::Json));
+
+        for format in FORMATS {
+            let value: Value = serde_json::from_str(LEGACY).unwrap();
+            let text = match format {
+                FileFormat::Json => serde_json::to_string(&value).unwrap(),
+                FileFormat::Yaml => serde_yaml::to_string(&value).unwrap(),
+                FileFormat::Toml => toml::to_string(&value).unwrap(),
+            };
+            let loaded = Circuit::from_format(&text, format).unwrap();
+            assert_eq!(loaded.to_json(), legacy.to_json(), "{} migration", format);
+        }
+    }
+
+    #[test]
+    fn reads_engineering_notation_in_every_format() {
+        let sources = [
+            (
+                FileFormat::Json,
+                r#"{ "version": 2, "design": { "supply": { "voltage": "5V" },
+                    "circuit": [{ "type": "resistor", "resistance": "4k7" }] } }"#,
+            ),
+            (
+                FileFormat::Yaml,
+                "version: 2\ndesign:\n  supply:\n    voltage: 5V\n  circuit:\n  - type: resistor\n    resistance: 4k7\n",
+            ),
+            (
+                FileFormat::Toml,
+                "version = 2\n[design.supply]\nvoltage = \"5V\"\n[[design.circuit]]\ntype = \"resistor\"\nresistance = \"4k7\"\n",
+            ),
+        ];
+
+        let expected = Circuit::new(
+            PowerSupply::new(5.0),
+            vec![SeriesElement::new(ElectronicComponent::new_resistor(
+                4700.0,
+            ))],
+        );
+
+        for (format, source) in sources {
+            let loaded = Circuit::from_format(source, format).unwrap();
+            assert_eq!(loaded.to_json(), expected.to_json(), "{} values", format);
+        }
+    }
+
+    #[test]
+    fn picks_the_format_from_the_extension() {
+        assert!(FileFormat::from_path("circuit.yaml") == FileFormat::Yaml);
+        assert!(FileFormat::from_path("circuit.YML") == FileFormat::Yaml);
+        assert!(FileFormat::from_path("dir.v2/circuit.toml") == FileFormat::Toml);
+        assert!(FileFormat::from_path("circuit.json") == FileFormat::Json);
+        assert!(FileFormat::from_path("circuit") == FileFormat::Json);
+    }
+
+    #[test]
+    fn rejects_unknown_versions() {
+        match Circuit::from_format(r#"{ "version": 3 }"#, FileFormat::Json) {
+            Err(error) => assert!(error.contains("unsupported file format version 3")),
+            Ok(_) => panic!("version 3 files should be rejected"),
+        }
+    }
+
+    #[test]
+    fn rejects_resistances_that_are_not_positive() {
+        for resistance in ["0", "\"-1k\""] {
+            let text = format!(
+                r#"{{ "version": 2, "design": {{ "supply": {{ "voltage": 10 }},
+                "circuit": [{{ "type": "resistor", "resistance": {} }}] }} }}"#,
+                resistance
+            );
+            match Circuit::from_format(&text, FileFormat::Json) {
+                Err(error) => assert!(error.contains("resistances must be finite and positive")),
+                Ok(_) => panic!("the resistance {} should be rejected", resistance),
+            }
+        }
+        let legacy = LEGACY.replace("10.0", "-10.0");
+        assert!(Circuit::from_format(&legacy, FileFormat::Json).is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_identifiers() {
+        let text = r#"{ "version": 2, "design": { "supply": { "voltage": 12 },
+            "circuit": [{ "type": "resistor", "id": "R<1>", "resistance": 10 }] } }"#;
+        match Circuit::from_format(text, FileFormat::Json) {
+            Err(error) => assert!(error.contains("Invalid identifier \"R<1>\"")),
+            Ok(_) => panic!("invalid identifiers should be rejected"),
+        }
+
+        let text = r#"{ "version": 2, "design": { "supply": { "voltage": 12 }, "circuit": [
+            { "type": "resistor", "id": "R1", "resistance": 10 },
+            { "type": "parallel", "branches": [[{ "type": "resistor", "id": "R1", "resistance": 20 }]] }
+        ] } }"#;
+        assert_eq!(
+            Circuit::from_format(text, FileFormat::Json)
+                .err()
+                .as_deref(),
+            Some("The identifier R1 is used more than once")
+        );
+        let legacy = LEGACY.replace(r#"{ "resistance""#, r#"{ "id": "R1", "resistance""#);
+        assert!(Circuit::from_format(&legacy, FileFormat::Json).is_err());
+
+        let mut circuit = sample();
+        let info = |id: &str| ComponentInfo {
+            id: Some(String::from(id)),
+            ..ComponentInfo::default()
+        };
+        assert!(circuit.set_component_info(&[], 0, info("1R")).is_err());
+        assert!(circuit.set_component_info(&[], 0, info("R 1")).is_err());
+        assert!(circuit
+            .set_component_info(&[], 0, info("R_sense.2"))
+            .is_ok());
+    }
+
+    #[test]
+    fn ships_the_current_schema() {
+        assert_eq!(
+            include_str!("../../circuit.schema.json").trim_end(),
+            json_schema().trim_end(),
+            "circuit.schema.json is out of date: run `circuit-sim schema circuit.schema.json`"
+        );
+    }
+
+    #[test]
+    fn plugin_components_keep_every_detail() {
+        let schema: serde_json::Value = serde_json::from_str(&json_schema()).unwrap();
+        let plugin = &schema["definitions"]["PluginComponent"]["properties"];
+        let info = serde_json::to_value(schemars::schema_for!(ComponentInfo)).unwrap();
+        for (key, property) in info["properties"].as_object().unwrap() {
+            assert_eq!(&plugin[key], property, "{key}");
+        }
+        assert!(plugin["type"].is_object());
+    }
+}
@@ -0,0 +1,430 @@
+use std::fmt::Display;
+
+use crate::units::format_value;
+
+use super::{Circuit, ComponentInfo, Series, SeriesElement};
+
+/// Undoable edit of a circuit.
+///
+/// `path` and `index` address an element as the index of the element in the
+/// series at `path`, see [`Circuit::get_series_at`].
+#[derive(Debug, PartialEq)]
+#[non_exhaustive]
+#[allow(missing_docs)]
+pub enum Edit {
+    /// Sets the resistance of a component.
+    SetResistance {
+        path: Vec<usize>,
+        index: usize,
+        resistance: f64,
+    },
+    /// Inserts `element` at `index`.
+    Insert {
+        path: Vec<usize>,
+        index: usize,
+        element: SeriesElement,
+    },
+    /// Removes an element.
+    Remove { path: Vec<usize>, index: usize },
+    /// Moves an element to `target` in its series.
+    Move {
+        path: Vec<usize>,
+        index: usize,
+        target: usize,
+    },
+    /// Wraps `count` elements into a parallel, see [`Circuit::wrap_in_parallel`].
+    WrapInParallel {
+        path: Vec<usize>,
+        index: usize,
+        count: usize,
+    },
+    /// Adds `branch` to a parallel.
+    AddBranch {
+        path: Vec<usize>,
+        index: usize,
+        branch: Series,
+    },
+    /// Removes the branch numbered `branch` of a parallel.
+    RemoveBranch {
+        path: Vec<usize>,
+        index: usize,
+        branch: usize,
+    },
+    /// Sets the identifier and details of a component or subcircuit instance.
+    SetInfo {
+        path: Vec<usize>,
+        index: usize,
+        info: ComponentInfo,
+    },
+    /// Sets the voltage of the power supply.
+    SetVoltage(f64),
+    /// Replaces the whole circuit.
+    ReplaceCircuit(Circuit),
+}
+
+/// Undo and redo history of the edits of a circuit.
+///
+/// Undo restores the snapshot taken before the edit, redo applies the edit again.
+#[derive(Debug, Default)]
+pub struct History {
+    done: Vec<(Edit, Circuit)>,
+    undone: Vec<Edit>,
+}
+
+fn position(path: &[usize], index: usize) -> String {
+    path.iter()
+        .chain([index].iter())
+        .map(|i| i.to_string())
+        .collect::<Vec<String>>()
+        .join(".")
+}
+
+impl Edit {
+    /// Applies the edit to `circuit`.
+    pub fn apply(&self, circuit: &mut Circuit) -> Result<(), String> {
+        match self {
+            Edit::SetResistance {
+                path,
+                index,
+                resistance,
+            } => circuit.set_resistance(path, *index, *resistance),
+            Edit::Insert {
+                path,
+                index,
+                element,
+            } => circuit.insert_element(path, *index, element.clone()),
+            Edit::Remove { path, index } => circuit.remove_element(path, *index).map(|_| ()),
+            Edit::Move {
+                path,
+                index,
+                target,
+            } => circuit.move_element(path, *index, *target),
+            Edit::WrapInParallel { path, index, count } => {
+                circuit.wrap_in_parallel(path, *index, *count)
+            }
+            Edit::AddBranch {
+                path,
+                index,
+                branch,
+            } => circuit.add_branch(path, *index, branch.clone()),
+            Edit::RemoveBranch {
+                path,
+                index,
+                branch,
+            } => circuit.remove_branch(path, *index, *branch).map(|_| ()),
+            Edit::SetInfo { path, index, info } => {
+                circuit.set_component_info(path, *index, info.clone())
+            }
+            Edit::SetVoltage(voltage) => {
+                circuit.set_voltage(*voltage);
+                Ok(())
+            }
+            Edit::ReplaceCircuit(replacement) => {
+                *circuit = replacement.clone();
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Display for Edit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Edit::SetResistance {
+                path,
+                index,
+                resistance,
+            } => write!(
+                f,
+                "Set resistance of {} to {}",
+                position(path, *index),
+                format_value(*resistance, "Ω")
+            ),
+            Edit::Insert {
+                path,
+                index,
+                element,
+            } => write!(f, "Insert {} at {}", element, position(path, *index)),
+            Edit::Remove { path, index } => write!(f, "Delete {}", position(path, *index)),
+            Edit::Move {
+                path,
+                index,
+                target,
+            } => write!(
+                f,
+                "Move {} to {}",
+                position(path, *index),
+                position(path, *target)
+            ),
+            Edit::WrapInParallel { path, index, count } => write!(
+                f,
+                "Wrap {} element(s) from {} into a parallel",
+                count,
+                position(path, *index)
+            ),
+            Edit::AddBranch { path, index, .. } => {
+                write!(f, "Add a branch to {}", position(path, *index))
+            }
+            Edit::RemoveBranch {
+                path,
+                index,
+                branch,
+            } => write!(f, "Remove branch {} of {}", branch, position(path, *index)),
+            Edit::SetInfo { path, index, info } => match &info.id {
+                Some(id) => write!(f, "Edit details of {}", id),
+                None => write!(f, "Edit details of {}", position(path, *index)),
+            },
+            Edit::SetVoltage(voltage) => write!(
+                f,
+                "Set power supply voltage to {}",
+                format_value(*voltage, "V")
+            ),
+            Edit::ReplaceCircuit(replacement) => {
+                write!(f, "Replace the circuit with {}", replacement.to_notation())
+            }
+        }
+    }
+}
+
+impl History {
+    /// Creates an empty history.
+    pub fn new() -> Self {
+        History::default()
+    }
+
+    /// Applies `edit` to `circuit` and records it, dropping the edits to redo.
+    pub fn apply(&mut self, circuit: &mut Circuit, edit: Edit) -> Result<(), String> {
+        let before = circuit.clone();
+        edit.apply(circuit)?;
+        self.done.push((edit, before));
+        self.undone.clear();
+        Ok(())
+    }
+
+    /// Restores `circuit` as it was before the last edit, and returns that edit.
+    pub fn undo(&mut self, circuit: &mut Circuit) -> Option<&Edit> {
+        let (edit, before) = self.done.pop()?;
+        *circuit = before;
+        self.undone.push(edit);
+        self.undone.last()
+    }
+
+    /// Applies the last undone edit again, and returns it; an edit that can't
+    /// be applied stays to redo.
+    pub fn redo(&mut self, circuit: &mut Circuit) -> Option<&Edit> {
+        let before = circuit.clone();
+        self.undone.last()?.apply(circuit).ok()?;
+        let edit = self.undone.pop()?;
+        self.done.push((edit, before));
+        self.done.last().map(|(edit, _)| edit)
+    }
+
+    /// Forgets every edit.
+    pub fn clear(&mut self) {
+        self.done.clear();
+        self.undone.clear();
+    }
+
+    /// Edits that can be undone, oldest first.
+    pub fn done(&self) -> impl Iterator<Item = &Edit> {
+        self.done.iter().map(|(edit, _)| edit)
+    }
+
+    /// Edits that can be redone, next to redo first.
+    pub fn undone(&self) -> impl Iterator<Item = &Edit> {
+        self.undone.iter().rev()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::{ElectronicComponent, PowerSupply};
+
+    fn resistor(resistance: f64) -> SeriesElement {
+        SeriesElement::new(ElectronicComponent::new_resistor(resistance))
+    }
+
+    #[test]
+    fn undoes_and_redoes_every_edit() {
+        let original = Circuit::from_notation("12V: R10 + (R20 || R30 + R40)").unwrap();
+        let edits = [
+            Edit::SetResistance {
+                path: vec![1, 1],
+                index: 1,
+                resistance: 50.0,
+            },
+            Edit::Insert {
+                path: vec![],
+                index: 0,
+                element: resistor(5.0),
+            },
+            Edit::Remove {
+                path: vec![2, 0],
+                index: 0,
+            },
+            Edit::Move {
+                path: vec![2, 0],
+                index: 1,
+                target: 0,
+            },
+            Edit::WrapInParallel {
+                path: vec![],
+                index: 0,
+                count: 2,
+            },
+            Edit::AddBranch {
+                path: vec![],
+                index: 0,
+                branch: vec![resistor(15.0)],
+            },
+            Edit::RemoveBranch {
+                path: vec![],
+                index: 0,
+                branch: 0,
+            },
+            Edit::SetVoltage(9.0),
+            Edit::ReplaceCircuit(Circuit::from_notation("5V: R1").unwrap()),
+        ];
+
+        let mut circuit = original.clone();
+        let mut history = History::new();
+        let mut snapshots = vec![original.to_notation()];
+        for edit in edits {
+            history.apply(&mut circuit, edit).unwrap();
+            snapshots.push(circuit.to_notation());
+        }
+        assert_eq!(
+            snapshots,
+            [
+                "12V: R10 + (R20 || R30 + R40)",
+                "12V: R10 + (R20 || R30 + R50)",
+                "12V: R5 + R10 + (R20 || R30 + R50)",
+                "12V: R5 + R10 + (R30 + R50)",
+                "12V: R5 + R10 + (R50 + R30)",
+                "12V: (R5 + R10) + (R50 + R30)",
+                "12V: (R5 + R10 || R15) + (R50 + R30)",
+                "12V: (R15) + (R50 + R30)",
+                "9V: (R15) + (R50 + R30)",
+                "5V: R1",
+            ]
+        );
+
+        for expected in snapshots.iter().rev().skip(1) {
+            assert!(history.undo(&mut circuit).is_some());
+            assert_eq!(&circuit.to_notation(), expected);
+        }
+        assert!(history.undo(&mut circuit).is_none());
+        assert_eq!(circuit, original);
+
+        for expected in snapshots.iter().skip(1) {
+            assert!(history.redo(&mut circuit).is_some());
+            assert_eq!(&circuit.to_notation(), expected);
+        }
+        assert!(history.redo(&mut circuit).is_none());
+    }
+
+    #[test]
+    fn undoes_edits_of_details() {
+        let mut circuit = Circuit::new(
+            PowerSupply::new(12.0),
+            vec![
+                resistor(10.0),
+                SeriesElement::new_parallel(vec![vec![resistor(20.0)], vec![resistor(30.0)]]),
+            ],
+        );
+        let original = circuit.to_string();
+        let mut history = History::new();
+
+        let mut info = circuit.find_component("R2").unwrap().get_info().clone();
+        info.id = Some(String::from("Rload"));
+        info.tolerance = Some(5.0);
+        let edit = Edit::SetInfo {
+            path: vec![1, 0],
+            index: 0,
+            info,
+        };
+        assert_eq!(edit.to_string(), "Edit details of Rload");
+        history.apply(&mut circuit, edit).unwrap();
+        assert!(circuit.find_component("R2").is_none());
+        assert!(circuit.find_component("Rload").is_some());
+
+        history.undo(&mut circuit);
+        assert_eq!(circuit.to_string(), original);
+        assert_eq!(
+            circuit.find_component("R2").unwrap().get_info().tolerance,
+            None
+        );
+        history.redo(&mut circuit);
+        let redone = circuit.find_component("Rload").unwrap();
+        assert_eq!(redone.get_info().tolerance, Some(5.0));
+    }
+
+    #[test]
+    fn keeps_track_of_what_to_undo_and_redo() {
+        let mut circuit = Circuit::new(PowerSupply::new(12.0), vec![resistor(10.0)]);
+        let mut history = History::new();
+        let descriptions = |edits: &mut dyn Iterator<Item = &Edit>| {
+            edits.map(ToString::to_string).collect::<Vec<_>>()
+        };
+
+        history.apply(&mut circuit, Edit::SetVoltage(9.0)).unwrap();
+        history.apply(&mut circuit, Edit::SetVoltage(6.0)).unwrap();
+        assert_eq!(
+            history
+                .undo(&mut circuit)
+                .map(ToString::to_string)
+                .as_deref(),
+            Some("Set power supply voltage to 6V")
+        );
+        assert_eq!(
+            descriptions(&mut history.undone()),
+            ["Set power supply voltage to 6V"]
+        );
+
+        // failed edits are not recorded, and keep what there is to redo
+        let remove = Edit::Remove {
+            path: vec![],
+            index: 3,
+        };
+        assert!(history.apply(&mut circuit, remove).is_err());
+        assert_eq!(history.done().count(), 1);
+        assert_eq!(history.undone().count(), 1);
+
+        // a new edit drops the edits to redo
+        history.apply(&mut circuit, Edit::SetVoltage(3.0)).unwrap();
+        assert_eq!(
+            descriptions(&mut history.done()),
+            [
+                "Set power supply voltage to 9V",
+                "Set power supply voltage to 3V"
+            ]
+        );
+        assert!(history.redo(&mut circuit).is_none());
+
+        history.clear();
+        assert!(history.undo(&mut circuit).is_none());
+        assert_eq!(circuit.get_power_supply().get_voltage(), 3.0);
+    }
+
+    #[test]
+    fn keeps_edits_that_fail_to_redo() {
+        let mut circuit =
+            Circuit::new(PowerSupply::new(12.0), vec![resistor(10.0), resistor(20.0)]);
+        let mut history = History::new();
+        let remove = Edit::Remove {
+            path: vec![],
+            index: 1,
+        };
+        history.apply(&mut circuit, remove).unwrap();
+        history.undo(&mut circuit);
+
+        // the edit no longer applies to the circuit
+        circuit.remove_element(&[], 1).unwrap();
+        let before = circuit.to_string();
+        assert!(history.redo(&mut circuit).is_none());
+        assert_eq!(circuit.to_string(), before);
+        assert_eq!(history.undone().count(), 1);
+        assert_eq!(history.done().count(), 0);
+    }
+}
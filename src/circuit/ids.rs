@@ -0,0 +1,248 @@
+use std::collections::HashSet;
+
+use super::{
+    subcircuit::INSTANCE_PREFIX, Circuit, ComponentInfo, ElectronicComponent, PowerSupply, Series,
+    SeriesElement, Subcircuit,
+};
+
+const SUPPLY_PREFIX: &str = "V";
+
+// Calls `f` on the components and subcircuit instances of `series`, without
+// entering the instances: their elements are identified within the instance
+fn for_each_identified_mut(series: &mut Series, f: &mut impl FnMut(&mut SeriesElement)) {
+    series.iter_mut().for_each(|element| match element {
+        SeriesElement::Parallel(branches) => branches
+            .iter_mut()
+            .for_each(|branch| for_each_identified_mut(branch, f)),
+        element => f(element),
+    });
+}
+
+fn element_id(element: &SeriesElement) -> Option<&str> {
+    match element {
+        SeriesElement::Component(component) => component.get_id(),
+        SeriesElement::Subcircuit(subcircuit) => subcircuit.get_id(),
+        SeriesElement::Parallel(_) => None,
+    }
+}
+
+fn set_element_id(element: &mut SeriesElement, id: Option<String>) {
+    match element {
+        SeriesElement::Component(component) => component.get_mut_info().id = id,
+        SeriesElement::Subcircuit(subcircuit) => {
+            let info = ComponentInfo {
+                id,
+                ..subcircuit.get_info().clone()
+            };
+            subcircuit.set_info(info);
+        }
+        SeriesElement::Parallel(_) => {}
+    }
+}
+
+// Forgets the identifiers of `series` already in `used`
+fn remove_duplicate_ids(series: &mut Series, used: &mut HashSet<String>) {
+    for_each_identified_mut(series, &mut |element| {
+        if let Some(id) = element_id(element) {
+            if !used.insert(String::from(id)) {
+                set_element_id(element, None);
+            }
+        }
+    });
+}
+
+fn fill_missing_ids(series: &mut Series, used: &mut HashSet<String>) {
+    for_each_identified_mut(series, &mut |element| {
+        if element_id(element).is_none() {
+            let prefix = match element {
+                SeriesElement::Component(component) => component.id_prefix(),
+                _ => INSTANCE_PREFIX,
+            };
+            let id = next_free_id(prefix, used);
+            used.insert(id.clone());
+            set_element_id(element, Some(id));
+        }
+    });
+}
+
+// Identifies the elements of a subcircuit instance, before the identifier of
+// the instance prefixes them
+pub(crate) fn assign_local_ids(series: &mut Series) {
+    let mut used = HashSet::new();
+    remove_duplicate_ids(series, &mut used);
+    fill_missing_ids(series, &mut used);
+}
+
+// Finds the component or subcircuit instance identified by `id`, entering
+// instances as their single branch
+fn find_in_series<'a>(
+    series: &'a Series,
+    id: &str,
+    path: &mut Vec<usize>,
+) -> Option<(&'a SeriesElement, usize)> {
+    for (index, element) in series.iter().enumerate() {
+        if element_id(element) == Some(id) {
+            return Some((element, index));
+        }
+        match element {
+            SeriesElement::Component(_) => {}
+            SeriesElement::Subcircuit(subcircuit) => {
+                path.extend([index, 0]);
+                if let Some(found) = find_in_series(subcircuit.get_series(), id, path) {
+                    return Some(found);
+                }
+                path.truncate(path.len() - 2);
+            }
+            SeriesElement::Parallel(branches) => {
+                for (branch, series) in branches.iter().enumerate() {
+                    path.extend([index, branch]);
+                    if let Some(found) = find_in_series(series, id, path) {
+                        return Some(found);
+                    }
+                    path.truncate(path.len() - 2);
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Whether `id` can identify a component: a letter followed by letters,
+/// digits, `_` or `.`, as in `R1`, `Rsense` or `X1.R2`.
+pub fn is_valid_id(id: &str) -> bool {
+    let mut chars = id.chars();
+    chars
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+}
+
+fn check_id(id: &str) -> Result<(), String> {
+    match is_valid_id(id) {
+        true => Ok(()),
+        false => Err(format!(
+            "Invalid identifier \"{}\": identifiers are a letter followed by letters, digits, _ or .",
+            id
+        )),
+    }
+}
+
+// Rejects the first invalid identifier of `series`, and the first one already
+// in `used`; the elements of subcircuit instances are identified within the
+// instance
+fn check_series_ids(series: &Series, used: &mut HashSet<String>) -> Result<(), String> {
+    series.iter().try_for_each(|element| {
+        if let Some(id) = element_id(element) {
+            check_id(id)?;
+            if !used.insert(String::from(id)) {
+                return Err(format!("The identifier {} is used more than once", id));
+            }
+        }
+        match element {
+            SeriesElement::Component(_) => Ok(()),
+            SeriesElement::Subcircuit(subcircuit) => {
+                check_series_ids(subcircuit.get_series(), &mut HashSet::new())
+            }
+            SeriesElement::Parallel(branches) => branches
+                .iter()
+                .try_for_each(|branch| check_series_ids(branch, used)),
+        }
+    })
+}
+
+// Rejects the identifiers of a circuit read from a file that are invalid or
+// given twice, before `Circuit::new` would rename the duplicates
+pub(crate) fn check_ids(supply: &PowerSupply, series: &Series) -> Result<(), String> {
+    let mut used = HashSet::new();
+    if let Some(id) = supply.get_id() {
+        check_id(id)?;
+        used.insert(String::from(id));
+    }
+    check_series_ids(series, &mut used)
+}
+
+fn next_free_id(prefix: &str, used: &HashSet<String>) -> String {
+    (1..)
+        .map(|n| format!("{}{}", prefix, n))
+        .find(|id| !used.contains(id))
+        .unwrap()
+}
+
+impl Circuit {
+    // Gives an identifier to every component and subcircuit instance without
+    // one; when several share an identifier, only the first one keeps it.
+    pub(crate) fn assign_ids(&mut self) {
+        let mut used = HashSet::new();
+
+        if let Some(id) = self.get_power_supply().get_id() {
+            used.insert(String::from(id));
+        }
+        remove_duplicate_ids(self.get_mut_series(), &mut used);
+
+        if self.get_power_supply().get_id().is_none() {
+            let id = next_free_id(SUPPLY_PREFIX, &used);
+            used.insert(id.clone());
+            self.get_mut_power_supply().set_id(Some(id));
+        }
+        fill_missing_ids(self.get_mut_series(), &mut used);
+    }
+
+    // Component or subcircuit instance with the given identifier
+    fn locate_element(&self, id: &str) -> Option<(&SeriesElement, Vec<usize>, usize)> {
+        let mut path = Vec::new();
+        let (element, index) = find_in_series(self.get_series(), id, &mut path)?;
+        Some((element, path, index))
+    }
+
+    /// Returns the component with the given identifier, along with the path of
+    /// its series and its index in it.
+    pub fn locate_component(&self, id: &str) -> Option<(&ElectronicComponent, Vec<usize>, usize)> {
+        match self.locate_element(id)? {
+            (SeriesElement::Component(component), path, index) => Some((component, path, index)),
+            _ => None,
+        }
+    }
+
+    /// Returns the subcircuit instance with the given identifier.
+    pub fn find_subcircuit(&self, id: &str) -> Option<&Subcircuit> {
+        match self.locate_element(id)? {
+            (SeriesElement::Subcircuit(subcircuit), _, _) => Some(subcircuit),
+            _ => None,
+        }
+    }
+
+    /// Returns the component with the given identifier.
+    pub fn find_component(&self, id: &str) -> Option<&ElectronicComponent> {
+        self.locate_component(id).map(|(component, _, _)| component)
+    }
+
+    /// Sets the identifier and details of the component or subcircuit
+    /// instance at `index` of the series at `path`, refusing identifiers
+    /// already in use or not accepted by [`is_valid_id`].
+    pub fn set_component_info(
+        &mut self,
+        path: &[usize],
+        index: usize,
+        info: ComponentInfo,
+    ) -> Result<(), String> {
+        if let Some(id) = &info.id {
+            check_id(id)?;
+            let taken = match self.locate_element(id) {
+                Some((_, other_path, other_index)) => other_path != path || other_index != index,
+                None => self.get_power_supply().get_id() == Some(id.as_str()),
+            };
+            if taken {
+                return Err(format!("The identifier {} is already used", id));
+            }
+        }
+
+        match self.series_mut(path)?.get_mut(index) {
+            Some(SeriesElement::Component(component)) => *component.get_mut_info() = info,
+            Some(SeriesElement::Subcircuit(subcircuit)) => subcircuit.set_info(info),
+            _ => return Err(String::from("The selected element is not a component")),
+        }
+        self.assign_ids();
+        self.update();
+        Ok(())
+    }
+}
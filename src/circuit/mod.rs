@@ -1,5 +1,35 @@
+//! Circuit model: power supply, components, series and parallel branches.
+
+mod builder;
+mod catalog;
+#[allow(clippy::module_inception)]
 mod circuit;
-pub mod clone_impl;
-pub mod display_impl;
-pub mod serde_impl;
-pub use circuit::*;
+mod display_impl;
+mod edit;
+mod file_format;
+mod history;
+mod ids;
+mod models;
+mod notation;
+mod plugin;
+mod serde_impl;
+mod subcircuit;
+pub use builder::{CircuitBuilder, ParallelBuilder, SeriesBuilder};
+pub use catalog::{Catalog, Part};
+pub use circuit::{
+    calculate_current, calculate_parallel_resistance, calculate_total_resistance, Circuit,
+    ComponentInfo, Distribution, ElectronicComponent, ElectronicComponentTrait, PowerSupply,
+    Resistor, Series, SeriesElement, REFERENCE_TEMPERATURE,
+};
+pub(crate) use circuit::{
+    calculate_series_voltage, check_resistance, deserialize_resistance, series_equivalent,
+};
+pub use file_format::{json_schema, FileFormat, FORMAT_VERSION};
+pub use history::{Edit, History};
+pub use ids::is_valid_id;
+pub use models::{Battery, Led};
+pub use plugin::{
+    register_component, registered_components, value_parameter, CloneModel, ComponentFactory,
+    ComponentModel, PluginComponent,
+};
+pub use subcircuit::{Subcircuit, SubcircuitDefinition};
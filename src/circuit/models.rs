@@ -0,0 +1,129 @@
+// Component models built into the simulator, registered like plugins under
+// their type tags.
+
+use serde_json::{json, Map, Value};
+
+use crate::units::format_value;
+
+use super::plugin::{value_parameter, ComponentFactory, ComponentModel};
+
+pub(crate) const BUILTIN_MODELS: [(&str, ComponentFactory); 2] = [
+    ("led", Led::from_parameters),
+    ("battery", Battery::from_parameters),
+];
+
+fn positive_parameter(parameters: &Map<String, Value>, name: &str) -> Result<f64, String> {
+    match value_parameter(parameters, name)? {
+        value if value > 0.0 => Ok(value),
+        _ => Err(format!("the {} must be positive", name)),
+    }
+}
+
+fn object(value: Value) -> Map<String, Value> {
+    value.as_object().cloned().unwrap_or_default()
+}
+
+/// Light-emitting diode, seen as its forward voltage opposing the current in
+/// series with its dynamic resistance.
+///
+/// An LED the circuit would drive current backwards through blocks it, and
+/// is solved as open.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Led {
+    /// Forward voltage, in volts.
+    pub forward_voltage: f64,
+    /// Dynamic resistance, in ohms; must be positive.
+    pub resistance: f64,
+}
+
+impl Led {
+    fn from_parameters(parameters: &Map<String, Value>) -> Result<Box<dyn ComponentModel>, String> {
+        Ok(Box::new(Led {
+            forward_voltage: value_parameter(parameters, "forward_voltage")?,
+            resistance: positive_parameter(parameters, "resistance")?,
+        }))
+    }
+}
+
+impl ComponentModel for Led {
+    fn type_tag(&self) -> &'static str {
+        "led"
+    }
+    fn name(&self) -> &'static str {
+        "LED"
+    }
+    fn id_prefix(&self) -> &'static str {
+        "D"
+    }
+    fn resistance(&self) -> f64 {
+        self.resistance
+    }
+    fn electromotive_force(&self) -> f64 {
+        -self.forward_voltage
+    }
+    fn blocks_reverse_current(&self) -> bool {
+        true
+    }
+    fn parameters(&self) -> Map<String, Value> {
+        object(json!({ "forward_voltage": self.forward_voltage, "resistance": self.resistance }))
+    }
+    fn value(&self) -> (f64, &'static str) {
+        (self.forward_voltage, "V")
+    }
+    fn describe(&self) -> String {
+        format!(
+            "Forward voltage: {}, Resistance: {}",
+            format_value(self.forward_voltage, "V"),
+            format_value(self.resistance, "Ω")
+        )
+    }
+}
+
+/// Battery, seen as its voltage in series with its internal resistance.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Battery {
+    /// Voltage without load, in volts, pushing current along the series.
+    pub voltage: f64,
+    /// Internal resistance, in ohms; must be positive.
+    pub resistance: f64,
+}
+
+impl Battery {
+    fn from_parameters(parameters: &Map<String, Value>) -> Result<Box<dyn ComponentModel>, String> {
+        Ok(Box::new(Battery {
+            voltage: value_parameter(parameters, "voltage")?,
+            resistance: positive_parameter(parameters, "resistance")?,
+        }))
+    }
+}
+
+impl ComponentModel for Battery {
+    fn type_tag(&self) -> &'static str {
+        "battery"
+    }
+    fn name(&self) -> &'static str {
+        "Battery"
+    }
+    fn id_prefix(&self) -> &'static str {
+        "B"
+    }
+    fn resistance(&self) -> f64 {
+        self.resistance
+    }
+    fn electromotive_force(&self) -> f64 {
+        self.voltage
+    }
+    fn parameters(&self) -> Map<String, Value> {
+        object(json!({ "voltage": self.voltage, "resistance": self.resistance }))
+    }
+    fn value(&self) -> (f64, &'static str) {
+        (self.voltage, "V")
+    }
+    fn describe(&self) -> String {
+        format!(
+            "Voltage: {}, Internal resistance: {}",
+            format_value(self.voltage, "V"),
+            format_value(self.resistance, "Ω")
+        )
+    }
+}
@@ -0,0 +1,342 @@
+// Compact text notation of a circuit, such as
+// `12V: R10 + R30 + R20 + (R20 + R20 || R320 + R20)`.
+//
+//   circuit  := voltage ':' [parallel]
+//   parallel := series ('||' series)*
+//   series   := term ('+' term)*
+//   term     := 'R' value | '(' parallel ')'
+//
+// Values use the engineering notation of the `units` module, e.g. R4k7.
+
+use crate::units::{format_with_digits, parse_value};
+
+use super::{check_resistance, Circuit, ElectronicComponent, PowerSupply, Series, SeriesElement};
+
+// Enough digits for the printed values to read back unchanged
+const NOTATION_DIGITS: usize = 12;
+
+struct Parser<'a> {
+    text: &'a str,
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.text[self.position..]
+    }
+
+    fn skip_spaces(&mut self) {
+        let rest = self.rest();
+        self.position += rest.len() - rest.trim_start().len();
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        self.skip_spaces();
+        if self.rest().starts_with(token) {
+            self.position += token.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn error(&self, expected: &str) -> String {
+        let column = self.text[..self.position].chars().count() + 1;
+        match self.rest().chars().next() {
+            Some(found) => format!(
+                "Expected {} at column {}, found '{}'",
+                expected, column, found
+            ),
+            None => format!("Expected {} at column {}, found the end", expected, column),
+        }
+    }
+
+    fn parallel(&mut self) -> Result<Vec<Series>, String> {
+        let mut branches = vec![self.series()?];
+        while self.eat("||") {
+            branches.push(self.series()?);
+        }
+        Ok(branches)
+    }
+
+    fn series(&mut self) -> Result<Series, String> {
+        let mut series = Series::new();
+        self.term(&mut series)?;
+        while self.eat("+") {
+            self.term(&mut series)?;
+        }
+        Ok(series)
+    }
+
+    // Parentheses holding a single branch only group their elements, so they
+    // are flattened into the enclosing series
+    fn term(&mut self, series: &mut Series) -> Result<(), String> {
+        if self.eat("(") {
+            let mut branches = self.parallel()?;
+            if !self.eat(")") {
+                return Err(self.error("')'"));
+            }
+            match branches.len() {
+                1 => series.append(&mut branches[0]),
+                _ => series.push(SeriesElement::new_parallel(branches)),
+            }
+            return Ok(());
+        }
+
+        if !self.eat("R") {
+            return Err(self.error("a resistor such as R4k7 or '('"));
+        }
+        let start = self.position;
+        let length = self
+            .rest()
+            .find(|c: char| c.is_whitespace() || "+|()".contains(c))
+            .unwrap_or(self.rest().len());
+        let value = &self.rest()[..length];
+
+        let resistance = parse_value(value).map_err(|_| {
+            self.position = start;
+            self.error("a resistance")
+        })?;
+        check_resistance(resistance)?;
+
+        self.position += length;
+        series.push(SeriesElement::new(ElectronicComponent::new_resistor(
+            resistance,
+        )));
+        Ok(())
+    }
+}
+
+fn add_loss(losses: &mut Vec<String>, loss: impl Into<String>) {
+    let loss = loss.into();
+    if !losses.contains(&loss) {
+        losses.push(loss);
+    }
+}
+
+// Details of the elements of `series` the notation drops, and the identifiers
+// of its resistors in the order the notation numbers them
+fn collect_losses(series: &Series, losses: &mut Vec<String>, ids: &mut Vec<Option<String>>) {
+    for element in series {
+        match element {
+            SeriesElement::Component(component) => {
+                let info = component.get_info();
+                for (present, loss) in [
+                    (info.label.is_some(), "labels"),
+                    (info.notes.is_some(), "notes"),
+                    (info.part.is_some(), "parts"),
+                    (
+                        info.tolerance.is_some() || info.distribution.is_some(),
+                        "tolerances",
+                    ),
+                    (
+                        info.power_rating.is_some() || info.max_voltage.is_some(),
+                        "ratings",
+                    ),
+                    (
+                        info.temperature_coefficient.is_some(),
+                        "temperature coefficients",
+                    ),
+                ] {
+                    if present {
+                        add_loss(losses, loss);
+                    }
+                }
+                match component {
+                    ElectronicComponent::Resistor(_) => ids.push(info.id.clone()),
+                    _ => add_loss(losses, format!("{} components", component.name())),
+                }
+            }
+            SeriesElement::Parallel(branches) => branches
+                .iter()
+                .for_each(|branch| collect_losses(branch, losses, ids)),
+            SeriesElement::Subcircuit(_) => add_loss(losses, "subcircuits"),
+        }
+    }
+}
+
+fn value_notation(value: f64, unit: &str) -> String {
+    format_with_digits(value, unit, NOTATION_DIGITS)
+}
+
+fn series_notation(series: &Series) -> String {
+    series
+        .iter()
+        .map(|element| match element {
+            SeriesElement::Component(ElectronicComponent::Plugin(component)) => {
+                String::from(component.get_model().type_tag())
+            }
+            SeriesElement::Component(component) => {
+                format!("R{}", value_notation(component.value().0, ""))
+            }
+            SeriesElement::Parallel(branches) => format!(
+                "({})",
+                branches
+                    .iter()
+                    .map(series_notation)
+                    .collect::<Vec<String>>()
+                    .join(" || ")
+            ),
+            SeriesElement::Subcircuit(subcircuit) => {
+                format!("({})", series_notation(subcircuit.get_series()))
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(" + ")
+}
+
+impl Circuit {
+    /// Reads a circuit written in the compact notation, such as
+    /// `12V: R10 + (R20 || R30)`.
+    pub fn from_notation(text: &str) -> Result<Circuit, String> {
+        let (voltage, elements) = text
+            .split_once(':')
+            .ok_or("Expected the power supply voltage first, such as \"12V: R10 + R20\"")?;
+        let voltage = parse_value(voltage)?;
+
+        let mut parser = Parser {
+            text,
+            position: text.len() - elements.len(),
+        };
+        parser.skip_spaces();
+
+        let series = if parser.rest().is_empty() {
+            Series::new()
+        } else {
+            let mut branches = parser.parallel()?;
+            parser.skip_spaces();
+            if !parser.rest().is_empty() {
+                return Err(parser.error("'+', '||' or the end"));
+            }
+            match branches.len() {
+                1 => branches.remove(0),
+                _ => vec![SeriesElement::new_parallel(branches)],
+            }
+        };
+
+        Ok(Circuit::new(PowerSupply::new(voltage), series))
+    }
+
+    /// Writes the circuit in the compact notation.
+    ///
+    /// Plugin components are written as their type tag, which the notation
+    /// can't read back; subcircuit instances are written as their elements.
+    pub fn to_notation(&self) -> String {
+        format!(
+            "{}: {}",
+            value_notation(self.get_power_supply().get_voltage(), "V"),
+            series_notation(self.get_series())
+        )
+        .trim_end()
+        .to_string()
+    }
+
+    /// Details of the circuit the compact notation can't hold, such as
+    /// "labels" or "LED components", lost when the circuit is replaced by
+    /// its notation; empty when [`Circuit::to_notation`] reads back the same
+    /// circuit.
+    pub fn notation_losses(&self) -> Vec<String> {
+        let mut losses = Vec::new();
+        let mut ids = Vec::new();
+        collect_losses(self.get_series(), &mut losses, &mut ids);
+        if !self.get_subcircuits().is_empty() {
+            add_loss(&mut losses, "subcircuits");
+        }
+        if self.get_ambient_temperature().is_some() {
+            add_loss(&mut losses, "ambient temperature");
+        }
+
+        // the notation numbers the resistors in order
+        if let Ok(written) = Circuit::from_notation(&self.to_notation()) {
+            let mut written_ids = Vec::new();
+            collect_losses(written.get_series(), &mut Vec::new(), &mut written_ids);
+            if ids != written_ids
+                || self.get_power_supply().get_id() != written.get_power_supply().get_id()
+            {
+                add_loss(&mut losses, "identifiers");
+            }
+        }
+        losses
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lists_the_details_the_notation_drops() {
+        let mut circuit = Circuit::from_notation("12V: R10 + (R20 || R30)").unwrap();
+        assert!(circuit.notation_losses().is_empty());
+
+        let mut info = circuit.find_component("R2").unwrap().get_info().clone();
+        info.label = Some(String::from("Load"));
+        info.tolerance = Some(1.0);
+        circuit.set_component_info(&[1, 0], 0, info).unwrap();
+        circuit.set_ambient_temperature(Some(85.0));
+        assert_eq!(
+            circuit.notation_losses(),
+            ["labels", "tolerances", "ambient temperature"]
+        );
+
+        let mut info = circuit.find_component("R1").unwrap().get_info().clone();
+        info.id = Some(String::from("Rsense"));
+        circuit.set_component_info(&[], 0, info).unwrap();
+        assert!(circuit
+            .notation_losses()
+            .contains(&String::from("identifiers")));
+    }
+
+    #[test]
+    fn parses_series_and_parallel() {
+        let circuit =
+            Circuit::from_notation("12V: R10 + R30 + R20 + (R20 + R20 || R320 + R20)").unwrap();
+
+        assert_eq!(circuit.get_series().len(), 4);
+        match &circuit.get_series()[3] {
+            SeriesElement::Parallel(branches) => {
+                assert_eq!(branches.len(), 2);
+                assert_eq!(branches[1].len(), 2);
+            }
+            _ => panic!("expected a parallel"),
+        }
+        assert!((circuit.get_intensity() - 12.0 / (60.0 + 40.0 * 340.0 / 380.0)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn prints_what_it_parses() {
+        for notation in [
+            "12V: R10 + R30 + R20 + (R20 + R20 || R320 + R20)",
+            "5V: R4.7k + ((R1M || R2.2M) + R470m || R10)",
+            "9V: (R1k || R2k || R3k)",
+            "3.3V:",
+        ] {
+            assert_eq!(
+                Circuit::from_notation(notation).unwrap().to_notation(),
+                notation
+            );
+        }
+    }
+
+    #[test]
+    fn flattens_grouping_parentheses() {
+        let circuit = Circuit::from_notation("1V: (R1 + (R2)) + R4k7").unwrap();
+        assert_eq!(circuit.to_notation(), "1V: R1 + R2 + R4.7k");
+    }
+
+    #[test]
+    fn reports_the_column_of_errors() {
+        assert_eq!(
+            Circuit::from_notation("5V: R10 + (R1 || )")
+                .err()
+                .as_deref(),
+            Some("Expected a resistor such as R4k7 or '(' at column 18, found ')'")
+        );
+        assert_eq!(
+            Circuit::from_notation("5V: R10 R2").err().as_deref(),
+            Some("Expected '+', '||' or the end at column 9, found 'R'")
+        );
+        assert!(Circuit::from_notation("R10 + R20").is_err());
+        assert!(Circuit::from_notation("5V: R0").is_err());
+    }
+}
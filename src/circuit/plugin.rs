@@ -0,0 +1,456 @@
+// Extension point for components other than resistors: a `ComponentModel`
+// describes the behaviour of a kind of component, and the registry maps the
+// type tags of circuit files to the functions creating the models.
+
+use std::{collections::BTreeMap, fmt::Debug, sync::RwLock};
+
+use serde_json::{Map, Value};
+
+use crate::units::{format_value, parse_value};
+
+use super::{models::BUILTIN_MODELS, ComponentInfo, ElectronicComponentTrait};
+
+// Type tags of the elements built into the circuit files
+pub(crate) const RESERVED_TAGS: [&str; 3] = ["resistor", "parallel", "subcircuit"];
+
+/// Creates a component model from the parameters read from a circuit file.
+pub type ComponentFactory = fn(&Map<String, Value>) -> Result<Box<dyn ComponentModel>, String>;
+
+static REGISTRY: RwLock<BTreeMap<&'static str, ComponentFactory>> = RwLock::new(BTreeMap::new());
+
+/// Behaviour of a kind of component, implemented by plugins.
+///
+/// The solver sees a component as its Thévenin equivalent: an
+/// [`electromotive_force`](ComponentModel::electromotive_force) in series with
+/// a [`resistance`](ComponentModel::resistance). Models are written to
+/// circuit files as their [`type_tag`](ComponentModel::type_tag) and
+/// [`parameters`](ComponentModel::parameters), and read back by the factory
+/// given to [`register_component`].
+///
+/// ```
+/// use circuit_sim::circuit::{
+///     register_component, value_parameter, Circuit, CircuitBuilder, ComponentModel,
+///     FileFormat, PluginComponent,
+/// };
+/// use serde_json::{json, Map, Value};
+///
+/// // Cell with an internal resistance
+/// #[derive(Debug, Clone)]
+/// struct Cell {
+///     voltage: f64,
+///     resistance: f64,
+/// }
+///
+/// impl ComponentModel for Cell {
+///     fn type_tag(&self) -> &'static str {
+///         "cell"
+///     }
+///     fn name(&self) -> &'static str {
+///         "Cell"
+///     }
+///     fn id_prefix(&self) -> &'static str {
+///         "B"
+///     }
+///     fn resistance(&self) -> f64 {
+///         self.resistance
+///     }
+///     fn electromotive_force(&self) -> f64 {
+///         self.voltage
+///     }
+///     fn parameters(&self) -> Map<String, Value> {
+///         let value = json!({ "voltage": self.voltage, "resistance": self.resistance });
+///         value.as_object().unwrap().clone()
+///     }
+/// }
+///
+/// register_component("cell", |parameters| {
+///     Ok(Box::new(Cell {
+///         voltage: value_parameter(parameters, "voltage")?,
+///         resistance: value_parameter(parameters, "resistance")?,
+///     }))
+/// })
+/// .unwrap();
+///
+/// let circuit = CircuitBuilder::new()
+///     .supply(0.0)
+///     .component(PluginComponent::new(Cell { voltage: 1.5, resistance: 0.5 }).into())
+///     .resistor(2.5)
+///     .build();
+/// assert_eq!(circuit.get_intensity(), 0.5);
+///
+/// let text = circuit.to_format(FileFormat::Json).unwrap();
+/// assert!(text.contains("\"type\": \"cell\""));
+/// assert_eq!(Circuit::from_format(&text, FileFormat::Json).unwrap(), circuit);
+/// ```
+pub trait ComponentModel: CloneModel + Debug + Send + Sync {
+    /// Type tag of the component in circuit files, such as "cell".
+    fn type_tag(&self) -> &'static str;
+
+    /// Name of the kind of component, such as "Cell".
+    fn name(&self) -> &'static str;
+
+    /// Prefix of the identifiers assigned to this kind of component.
+    fn id_prefix(&self) -> &'static str {
+        "X"
+    }
+
+    /// Resistance of the Thévenin equivalent, in ohms; must be positive.
+    fn resistance(&self) -> f64;
+
+    /// Electromotive force of the Thévenin equivalent, in volts, pushing
+    /// current along the series; zero for passive components.
+    fn electromotive_force(&self) -> f64 {
+        0.0
+    }
+
+    /// Parameters of the model, written to circuit files along with the type
+    /// tag and the identifier and details of the component.
+    fn parameters(&self) -> Map<String, Value>;
+
+    /// Main value of the component and its unit, shown on diagrams.
+    fn value(&self) -> (f64, &'static str) {
+        (self.resistance(), "Ω")
+    }
+
+    /// Short description of the parameters, such as "Resistance: 4.7kΩ".
+    fn describe(&self) -> String {
+        format!("Resistance: {}", format_value(self.resistance(), "Ω"))
+    }
+
+    /// Whether the component only conducts forwards, like a diode; the solver
+    /// then sees it as open while the circuit drives current backwards
+    /// through it.
+    fn blocks_reverse_current(&self) -> bool {
+        false
+    }
+
+    /// Sets the resistance, for models whose resistance can be edited.
+    fn set_resistance(&mut self, _resistance: f64) -> Result<(), String> {
+        Err(format!(
+            "The resistance of a {} can't be edited",
+            self.name()
+        ))
+    }
+}
+
+/// Cloning of boxed models, implemented for every `Clone` model.
+pub trait CloneModel {
+    /// Returns a boxed copy of the model.
+    fn clone_model(&self) -> Box<dyn ComponentModel>;
+}
+
+impl<T: ComponentModel + Clone + 'static> CloneModel for T {
+    fn clone_model(&self) -> Box<dyn ComponentModel> {
+        Box::new(self.clone())
+    }
+}
+
+impl Clone for Box<dyn ComponentModel> {
+    fn clone(&self) -> Self {
+        self.clone_model()
+    }
+}
+
+impl PartialEq for dyn ComponentModel {
+    fn eq(&self, other: &Self) -> bool {
+        self.type_tag() == other.type_tag() && self.parameters() == other.parameters()
+    }
+}
+
+fn builtin_factory(tag: &str) -> Option<ComponentFactory> {
+    BUILTIN_MODELS
+        .iter()
+        .find(|(builtin, _)| *builtin == tag)
+        .map(|(_, factory)| *factory)
+}
+
+/// Registers the factory of the components of type `tag`, so that circuit
+/// files holding them can be read.
+///
+/// The [`Led`](super::Led) and [`Battery`](super::Battery) models are
+/// registered from the start, as "led" and "battery".
+pub fn register_component(tag: &'static str, factory: ComponentFactory) -> Result<(), String> {
+    if RESERVED_TAGS.contains(&tag) || builtin_factory(tag).is_some() {
+        return Err(format!("The component type {} is built in", tag));
+    }
+    let mut registry = REGISTRY.write().unwrap();
+    if registry.contains_key(tag) {
+        return Err(format!("The component type {} is already registered", tag));
+    }
+    registry.insert(tag, factory);
+    Ok(())
+}
+
+/// Type tags of the registered components, built-in models included, in
+/// alphabetical order.
+pub fn registered_components() -> Vec<&'static str> {
+    let mut tags: Vec<&'static str> = REGISTRY.read().unwrap().keys().copied().collect();
+    tags.extend(BUILTIN_MODELS.iter().map(|(tag, _)| *tag));
+    tags.sort_unstable();
+    tags
+}
+
+/// Reads the parameter `name` of a component, written as a number or in
+/// engineering notation such as "4k7".
+pub fn value_parameter(parameters: &Map<String, Value>, name: &str) -> Result<f64, String> {
+    match parameters.get(name) {
+        Some(Value::Number(number)) => number.as_f64().ok_or_else(|| format!("invalid {}", name)),
+        Some(Value::String(text)) => parse_value(text),
+        Some(_) => Err(format!("invalid {}", name)),
+        None => Err(format!("missing {}", name)),
+    }
+}
+
+pub(crate) fn create_model(
+    tag: &str,
+    parameters: &Map<String, Value>,
+) -> Result<Box<dyn ComponentModel>, String> {
+    let factory = builtin_factory(tag)
+        .or_else(|| REGISTRY.read().unwrap().get(tag).copied())
+        .ok_or_else(|| format!("unknown component type {}", tag))?;
+    factory(parameters).map_err(|e| format!("invalid {}: {}", tag, e))
+}
+
+/// Component provided by a plugin, along with the voltage across it in its
+/// circuit.
+#[derive(Debug, Clone)]
+pub struct PluginComponent {
+    info: ComponentInfo,
+    model: Box<dyn ComponentModel>,
+    tension_in_circuit: f64,
+    // reverse-biased, for models blocking reverse current
+    blocking: bool,
+}
+
+impl PluginComponent {
+    /// Creates a component behaving as `model`.
+    pub fn new(model: impl ComponentModel + 'static) -> Self {
+        PluginComponent::from_model(Box::new(model))
+    }
+
+    /// Creates a component behaving as the boxed `model`.
+    pub fn from_model(model: Box<dyn ComponentModel>) -> Self {
+        PluginComponent {
+            info: ComponentInfo::default(),
+            model,
+            tension_in_circuit: 0.0,
+            blocking: false,
+        }
+    }
+
+    /// Identifier and details of the component.
+    pub fn get_info(&self) -> &ComponentInfo {
+        &self.info
+    }
+
+    pub(crate) fn get_mut_info(&mut self) -> &mut ComponentInfo {
+        &mut self.info
+    }
+
+    /// Sets the identifier and details of the component.
+    pub fn set_info(&mut self, info: ComponentInfo) {
+        self.info = info;
+    }
+
+    /// Model of the component.
+    pub fn get_model(&self) -> &dyn ComponentModel {
+        self.model.as_ref()
+    }
+
+    pub(crate) fn get_mut_model(&mut self) -> &mut dyn ComponentModel {
+        self.model.as_mut()
+    }
+
+    /// Whether the component is reverse-biased and blocks the current, for
+    /// models such as LEDs that [block reverse
+    /// current](ComponentModel::blocks_reverse_current).
+    pub fn is_blocking(&self) -> bool {
+        self.blocking
+    }
+
+    pub(crate) fn set_blocking(&mut self, blocking: bool) {
+        self.blocking = blocking;
+    }
+}
+
+impl PartialEq for PluginComponent {
+    fn eq(&self, other: &Self) -> bool {
+        self.info == other.info
+            && *self.model == *other.model
+            && self.tension_in_circuit == other.tension_in_circuit
+    }
+}
+
+impl ElectronicComponentTrait for PluginComponent {
+    fn get_resistance(&self) -> f64 {
+        if self.blocking {
+            f64::INFINITY
+        } else {
+            self.model.resistance()
+        }
+    }
+    fn get_electromotive_force(&self) -> f64 {
+        if self.blocking {
+            0.0
+        } else {
+            self.model.electromotive_force()
+        }
+    }
+    fn get_tension(&self) -> f64 {
+        self.tension_in_circuit
+    }
+    fn set_tension(&mut self, tension: f64) {
+        self.tension_in_circuit = tension;
+    }
+}
+
+impl From<PluginComponent> for super::ElectronicComponent {
+    fn from(component: PluginComponent) -> Self {
+        super::ElectronicComponent::Plugin(component)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::circuit::{Circuit, CircuitBuilder, ElectronicComponent, FileFormat, SeriesElement};
+
+    #[derive(Debug, Clone)]
+    struct Cell {
+        voltage: f64,
+        resistance: f64,
+    }
+
+    impl ComponentModel for Cell {
+        fn type_tag(&self) -> &'static str {
+            "test-cell"
+        }
+        fn name(&self) -> &'static str {
+            "Cell"
+        }
+        fn id_prefix(&self) -> &'static str {
+            "B"
+        }
+        fn resistance(&self) -> f64 {
+            self.resistance
+        }
+        fn electromotive_force(&self) -> f64 {
+            self.voltage
+        }
+        fn parameters(&self) -> Map<String, Value> {
+            let parameters = json!({ "voltage": self.voltage, "resistance": self.resistance });
+            parameters.as_object().unwrap().clone()
+        }
+    }
+
+    fn cell(voltage: f64, resistance: f64) -> ElectronicComponent {
+        // tests share the registry
+        let _ = register_component("test-cell", |parameters| {
+            Ok(Box::new(Cell {
+                voltage: value_parameter(parameters, "voltage")?,
+                resistance: value_parameter(parameters, "resistance")?,
+            }))
+        });
+        PluginComponent::new(Cell {
+            voltage,
+            resistance,
+        })
+        .into()
+    }
+
+    fn current(circuit: &Circuit, path: &[usize], index: usize) -> f64 {
+        match &circuit.get_series_at(path).unwrap()[index] {
+            SeriesElement::Component(component) => component.get_current(),
+            _ => panic!("expected a component"),
+        }
+    }
+
+    #[test]
+    fn solves_sources_in_parallel_branches() {
+        let circuit = CircuitBuilder::new()
+            .supply(0.0)
+            .resistor(1.0)
+            .parallel(|p| {
+                p.branch(|b| b.component(cell(2.0, 1.0)))
+                    .branch(|b| b.component(cell(1.0, 1.0)))
+            })
+            .build();
+
+        assert!((circuit.get_intensity() - 1.0).abs() < 1e-12);
+        assert!((current(&circuit, &[], 0) - 1.0).abs() < 1e-12);
+        assert!((current(&circuit, &[1, 0], 0) - 1.0).abs() < 1e-12);
+        assert!(current(&circuit, &[1, 1], 0).abs() < 1e-12);
+        assert_eq!(circuit.find_component("B1").unwrap().name(), "Cell");
+    }
+
+    #[test]
+    fn blocks_reverse_current_through_leds() {
+        let text = r#"{ "version": 2, "design": { "supply": { "voltage": 1 }, "circuit": [
+            { "type": "resistor", "resistance": 100 },
+            { "type": "led", "forward_voltage": "2V", "resistance": 10 }
+        ] } }"#;
+        let mut circuit = Circuit::from_format(text, FileFormat::Json).unwrap();
+        let led = |circuit: &Circuit| match &circuit.get_series()[1] {
+            SeriesElement::Component(ElectronicComponent::Plugin(led)) => led.clone(),
+            _ => panic!("expected the LED"),
+        };
+
+        // below its forward voltage, the LED is open and takes the whole supply
+        assert!(led(&circuit).is_blocking());
+        assert_eq!(circuit.get_intensity(), 0.0);
+        assert_eq!(current(&circuit, &[], 0), 0.0);
+        assert_eq!(led(&circuit).get_tension(), 1.0);
+        assert!(led(&circuit).to_string().ends_with(", blocking)"));
+
+        circuit.set_voltage(5.0);
+        assert!(!led(&circuit).is_blocking());
+        assert!((circuit.get_intensity() - 3.0 / 110.0).abs() < 1e-12);
+
+        // an open branch leaves the current to the other ones
+        let circuit = CircuitBuilder::new()
+            .supply(5.0)
+            .resistor(100.0)
+            .parallel(|p| {
+                p.branch(|b| b.resistor(100.0)).branch(|b| {
+                    b.component(
+                        PluginComponent::new(crate::circuit::Led {
+                            forward_voltage: 10.0,
+                            resistance: 1.0,
+                        })
+                        .into(),
+                    )
+                })
+            })
+            .build();
+        assert!((circuit.get_intensity() - 0.025).abs() < 1e-12);
+        assert!((current(&circuit, &[1, 0], 0) - 0.025).abs() < 1e-12);
+        assert_eq!(current(&circuit, &[1, 1], 0), 0.0);
+    }
+
+    #[test]
+    fn round_trips_plugin_components() {
+        let circuit = CircuitBuilder::new()
+            .supply(5.0)
+            .component(cell(1.5, 0.2))
+            .resistor(100.0)
+            .build();
+
+        for format in [FileFormat::Json, FileFormat::Yaml, FileFormat::Toml] {
+            let text = circuit.to_format(format).unwrap();
+            assert_eq!(Circuit::from_format(&text, format).unwrap(), circuit);
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_and_built_in_types() {
+        let text = r#"{ "version": 2, "design": { "supply": { "voltage": 5 },
+            "circuit": [{ "type": "flux-capacitor" }] } }"#;
+        match Circuit::from_format(text, FileFormat::Json) {
+            Err(error) => assert!(error.contains("unknown component type flux-capacitor")),
+            Ok(_) => panic!("unknown types should be rejected"),
+        }
+        assert!(register_component("resistor", |_| Err(String::new())).is_err());
+    }
+}
@@ -1,22 +1,95 @@
-use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
 
-use super::{ElectronicComponentTrait, Resistor};
+use schemars::{gen::SchemaGenerator, schema::Schema, schema_for, JsonSchema};
+use serde::{de::Error, ser::SerializeMap, Deserialize, Serialize};
+use serde_json::{json, Map, Value};
 
-impl Serialize for dyn ElectronicComponentTrait {
+use super::{
+    plugin::{create_model, RESERVED_TAGS},
+    ComponentInfo, PluginComponent,
+};
+
+// Keys of the identifier and details of components, read from the schema of
+// `ComponentInfo` so that they follow its fields
+fn info_keys() -> &'static [String] {
+    static INFO_KEYS: OnceLock<Vec<String>> = OnceLock::new();
+    INFO_KEYS.get_or_init(|| {
+        schema_for!(ComponentInfo)
+            .schema
+            .object
+            .map(|object| object.properties.into_keys().collect())
+            .unwrap_or_default()
+    })
+}
+
+// Plugin components are written as their type tag, identifier and details,
+// followed by the parameters of their model
+impl Serialize for PluginComponent {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
     {
-        Resistor::serialize(&self.to_resistor(), serializer)
+        let info = serde_json::to_value(self.get_info()).map_err(serde::ser::Error::custom)?;
+        let parameters = self.get_model().parameters();
+
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry("type", self.get_model().type_tag())?;
+        for (key, value) in info.as_object().into_iter().flatten() {
+            map.serialize_entry(key, value)?;
+        }
+        for (key, value) in parameters.iter() {
+            map.serialize_entry(key, value)?;
+        }
+        map.end()
     }
 }
 
-impl<'de> Deserialize<'de> for Box<dyn ElectronicComponentTrait> {
-    fn deserialize<D>(deserializer: D) -> Result<Box<dyn ElectronicComponentTrait>, D::Error>
+impl<'de> Deserialize<'de> for PluginComponent {
+    fn deserialize<D>(deserializer: D) -> Result<PluginComponent, D::Error>
     where
         D: serde::Deserializer<'de>,
     {
-        let resistor = Resistor::deserialize(deserializer)?;
-        Ok(Box::new(resistor))
+        let mut parameters = Map::deserialize(deserializer)?;
+        let tag = match parameters.remove("type") {
+            Some(Value::String(tag)) => tag,
+            _ => return Err(D::Error::missing_field("type")),
+        };
+
+        let info: Map<String, Value> = info_keys()
+            .iter()
+            .filter_map(|key| parameters.remove_entry(key))
+            .collect();
+        let info = ComponentInfo::deserialize(Value::Object(info)).map_err(D::Error::custom)?;
+
+        let mut component =
+            PluginComponent::from_model(create_model(&tag, &parameters).map_err(D::Error::custom)?);
+        component.set_info(info);
+        Ok(component)
+    }
+}
+
+impl JsonSchema for PluginComponent {
+    fn schema_name() -> String {
+        String::from("PluginComponent")
+    }
+
+    // The identifier and details of `ComponentInfo`, along with the type tag
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        let mut schema = ComponentInfo::json_schema(gen).into_object();
+        schema.metadata().description = Some(String::from(
+            "Component provided by a plugin, with the parameters of its type",
+        ));
+        let object = schema.object();
+        object.required.insert(String::from("type"));
+        object.properties.insert(
+            String::from("type"),
+            serde_json::from_value(json!({
+                "description": "Type tag of the component",
+                "type": "string",
+                "not": { "enum": RESERVED_TAGS }
+            }))
+            .unwrap(),
+        );
+        Schema::Object(schema)
     }
 }
@@ -0,0 +1,463 @@
+// Reusable blocks of a circuit. A definition holds the design elements of the
+// block, where "$name" values stand for its parameters; an instance holds the
+// elements expanded with its arguments, so the solver sees them as a series.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{
+    calculate_series_voltage, circuit::series_equivalent, Circuit, ComponentInfo, Series,
+    SeriesElement,
+};
+
+/// Prefix of the identifiers assigned to subcircuit instances.
+pub(crate) const INSTANCE_PREFIX: &str = "X";
+
+/// Named definition of a reusable block of elements.
+///
+/// The topology of the circuits makes every block a two-terminal network,
+/// connected in series through its [`terminals`](SubcircuitDefinition::terminals).
+///
+/// ```
+/// use circuit_sim::circuit::{Circuit, SeriesElement, SubcircuitDefinition};
+///
+/// let divider = Circuit::from_notation("0V: R10k + R10k").unwrap();
+/// let definition = SubcircuitDefinition::new("divider", divider.get_series())
+///     .with_parameter("top", 10e3)
+///     .bind("R1", "resistance", "top")
+///     .unwrap();
+///
+/// let mut circuit = Circuit::from_notation("5V: R1k").unwrap();
+/// circuit.define_subcircuit(definition);
+/// let instance = circuit.instantiate("divider", &[("top", 4.7e3)]).unwrap();
+/// circuit.insert_element(&[], 1, instance).unwrap();
+///
+/// assert_eq!(circuit.find_component("X1.R1").unwrap().value().0, 4.7e3);
+/// assert_eq!(circuit.get_intensity(), 5.0 / 15.7e3);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubcircuitDefinition {
+    pub(crate) name: String,
+    pub(crate) parameters: BTreeMap<String, f64>,
+    pub(crate) terminals: [String; 2],
+    // design elements, as written in circuit files
+    pub(crate) circuit: Vec<Value>,
+    // library file the definition was read from
+    pub(crate) library: Option<String>,
+}
+
+/// Instance of a [`SubcircuitDefinition`], holding its expanded elements.
+///
+/// The identifiers of the elements are prefixed by the identifier of the
+/// instance, such as `X1.R2`. Instances are edited through their definition,
+/// their elements can't be edited one by one.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Subcircuit {
+    #[serde(flatten)]
+    info: ComponentInfo,
+    definition: String,
+    arguments: BTreeMap<String, f64>,
+    terminals: [String; 2],
+    series: Series,
+}
+
+pub(crate) fn default_terminals() -> [String; 2] {
+    [String::from("in"), String::from("out")]
+}
+
+// Replaces the "$name" strings by the values of the parameters
+fn substitute(value: &mut Value, parameters: &BTreeMap<String, f64>) -> Result<(), String> {
+    match value {
+        Value::String(text) => {
+            if let Some(name) = text.strip_prefix('$') {
+                let parameter = parameters
+                    .get(name)
+                    .ok_or_else(|| format!("unknown parameter ${}", name))?;
+                *value = Value::from(*parameter);
+            }
+        }
+        Value::Array(values) => values
+            .iter_mut()
+            .try_for_each(|value| substitute(value, parameters))?,
+        Value::Object(map) => map
+            .values_mut()
+            .try_for_each(|value| substitute(value, parameters))?,
+        _ => {}
+    }
+    Ok(())
+}
+
+fn bind_value(value: &mut Value, id: &str, field: &str, placeholder: &str) -> bool {
+    match value {
+        Value::Object(map) if map.get("id").and_then(Value::as_str) == Some(id) => {
+            map.insert(String::from(field), Value::from(placeholder));
+            true
+        }
+        Value::Object(map) => map
+            .values_mut()
+            .any(|value| bind_value(value, id, field, placeholder)),
+        Value::Array(values) => values
+            .iter_mut()
+            .any(|value| bind_value(value, id, field, placeholder)),
+        _ => false,
+    }
+}
+
+// Prefixes the identifiers of the elements of `series` with `prefix`
+fn prefix_ids(series: &mut Series, prefix: &str) {
+    for element in series.iter_mut() {
+        match element {
+            SeriesElement::Component(component) => {
+                let info = component.get_mut_info();
+                info.id = info.id.take().map(|id| format!("{}{}", prefix, id));
+            }
+            SeriesElement::Parallel(branches) => branches
+                .iter_mut()
+                .for_each(|branch| prefix_ids(branch, prefix)),
+            SeriesElement::Subcircuit(subcircuit) => {
+                let info = &mut subcircuit.info;
+                info.id = info.id.take().map(|id| format!("{}{}", prefix, id));
+                prefix_ids(&mut subcircuit.series, prefix);
+            }
+        }
+    }
+}
+
+impl SubcircuitDefinition {
+    /// Creates a definition holding a copy of `series`, without parameters.
+    pub fn new(name: &str, series: &Series) -> Self {
+        SubcircuitDefinition {
+            name: String::from(name),
+            parameters: BTreeMap::new(),
+            terminals: default_terminals(),
+            circuit: super::file_format::design_values(series),
+            library: None,
+        }
+    }
+
+    /// Adds a parameter, used by instances that don't give it a value.
+    pub fn with_parameter(mut self, name: &str, default: f64) -> Self {
+        self.parameters.insert(String::from(name), default);
+        self
+    }
+
+    /// Names the two terminals of the block, "in" and "out" by default.
+    pub fn with_terminals(mut self, input: &str, output: &str) -> Self {
+        self.terminals = [String::from(input), String::from(output)];
+        self
+    }
+
+    /// Makes the `field` of the element identified by `id`, such as its
+    /// "resistance", take the value of `parameter`.
+    pub fn bind(mut self, id: &str, field: &str, parameter: &str) -> Result<Self, String> {
+        if !self.parameters.contains_key(parameter) {
+            return Err(format!("unknown parameter {}", parameter));
+        }
+        let placeholder = format!("${}", parameter);
+        if !self
+            .circuit
+            .iter_mut()
+            .any(|value| bind_value(value, id, field, &placeholder))
+        {
+            return Err(format!("no element {} in the subcircuit {}", id, self.name));
+        }
+        Ok(self)
+    }
+
+    /// Name of the definition.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Parameters of the definition, with their default values.
+    pub fn parameters(&self) -> &BTreeMap<String, f64> {
+        &self.parameters
+    }
+
+    /// Names of the input and output terminals.
+    pub fn terminals(&self) -> &[String; 2] {
+        &self.terminals
+    }
+
+    /// Library file the definition was read from, if it isn't stored in the
+    /// circuit file.
+    pub fn library(&self) -> Option<&str> {
+        self.library.as_deref()
+    }
+
+    // Design elements with the parameters replaced by `arguments` or their
+    // default values
+    pub(crate) fn expand(&self, arguments: &BTreeMap<String, f64>) -> Result<Vec<Value>, String> {
+        if let Some(unknown) = arguments
+            .keys()
+            .find(|name| !self.parameters.contains_key(*name))
+        {
+            return Err(format!(
+                "the subcircuit {} has no parameter {}",
+                self.name, unknown
+            ));
+        }
+
+        let mut values = self.parameters.clone();
+        values.extend(arguments.iter().map(|(name, value)| (name.clone(), *value)));
+        let mut circuit = self.circuit.clone();
+        circuit
+            .iter_mut()
+            .try_for_each(|value| substitute(value, &values))?;
+        Ok(circuit)
+    }
+}
+
+impl Subcircuit {
+    // `series` holds the expanded elements with identifiers local to the
+    // instance
+    pub(crate) fn new(
+        definition: &SubcircuitDefinition,
+        arguments: BTreeMap<String, f64>,
+        info: ComponentInfo,
+        series: Series,
+    ) -> Self {
+        let mut subcircuit = Subcircuit {
+            info: ComponentInfo::default(),
+            definition: definition.name.clone(),
+            arguments,
+            terminals: definition.terminals.clone(),
+            series,
+        };
+        subcircuit.set_info(info);
+        subcircuit
+    }
+
+    /// Identifier and details of the instance.
+    pub fn get_info(&self) -> &ComponentInfo {
+        &self.info
+    }
+
+    /// Identifier of the instance, such as `X1`.
+    pub fn get_id(&self) -> Option<&str> {
+        self.info.id.as_deref()
+    }
+
+    // Sets the identifier and details, renaming the elements of the instance
+    pub(crate) fn set_info(&mut self, info: ComponentInfo) {
+        if let Some(old) = &self.info.id {
+            strip_ids(&mut self.series, &format!("{}.", old));
+        }
+        if let Some(new) = &info.id {
+            prefix_ids(&mut self.series, &format!("{}.", new));
+        }
+        self.info = info;
+    }
+
+    /// Name of the definition of the instance.
+    pub fn get_definition(&self) -> &str {
+        &self.definition
+    }
+
+    /// Values given to the parameters of the definition.
+    pub fn get_arguments(&self) -> &BTreeMap<String, f64> {
+        &self.arguments
+    }
+
+    /// Names of the input and output terminals.
+    pub fn get_terminals(&self) -> &[String; 2] {
+        &self.terminals
+    }
+
+    /// Voltage between the terminals, in volts.
+    pub fn get_voltage(&self) -> f64 {
+        calculate_series_voltage(&self.series)
+    }
+
+    /// Current flowing through the instance, in amperes.
+    pub fn get_current(&self) -> f64 {
+        let (resistance, force) = series_equivalent(&self.series);
+        (self.get_voltage() + force) / resistance
+    }
+
+    /// Expanded elements of the instance.
+    pub fn get_series(&self) -> &Series {
+        &self.series
+    }
+
+    pub(crate) fn get_mut_series(&mut self) -> &mut Series {
+        &mut self.series
+    }
+}
+
+fn strip_ids(series: &mut Series, prefix: &str) {
+    for element in series.iter_mut() {
+        let info = match element {
+            SeriesElement::Component(component) => component.get_mut_info(),
+            SeriesElement::Parallel(branches) => {
+                branches
+                    .iter_mut()
+                    .for_each(|branch| strip_ids(branch, prefix));
+                continue;
+            }
+            SeriesElement::Subcircuit(subcircuit) => {
+                strip_ids(&mut subcircuit.series, prefix);
+                &mut subcircuit.info
+            }
+        };
+        if let Some(id) = &info.id {
+            info.id = Some(String::from(id.strip_prefix(prefix).unwrap_or(id)));
+        }
+    }
+}
+
+impl Circuit {
+    /// Adds a subcircuit definition, replacing the one with the same name.
+    pub fn define_subcircuit(&mut self, definition: SubcircuitDefinition) {
+        self.get_mut_subcircuits()
+            .insert(definition.name.clone(), definition);
+    }
+
+    /// Creates an instance of the subcircuit `name`, to be inserted in the
+    /// circuit; parameters left out take their default value.
+    pub fn instantiate(
+        &self,
+        name: &str,
+        arguments: &[(&str, f64)],
+    ) -> Result<SeriesElement, String> {
+        let arguments = arguments
+            .iter()
+            .map(|(name, value)| (String::from(*name), *value))
+            .collect();
+        super::file_format::instantiate(
+            self.get_subcircuits(),
+            name,
+            arguments,
+            ComponentInfo::default(),
+            &mut Vec::new(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::{
+        analysis::solve,
+        circuit::{CircuitBuilder, FileFormat},
+    };
+
+    const FILE: &str = r#"{ "version": 2, "design": {
+        "subcircuits": {
+            "divider": {
+                "parameters": { "top": "10k" },
+                "circuit": [
+                    { "type": "resistor", "id": "RT", "resistance": "$top" },
+                    { "type": "resistor", "id": "RB", "resistance": "10k" }
+                ]
+            },
+            "pair": {
+                "circuit": [{ "type": "parallel", "branches": [
+                    [{ "type": "subcircuit", "definition": "divider" }],
+                    [{ "type": "subcircuit", "definition": "divider", "arguments": { "top": 30000 } }]
+                ] }]
+            }
+        },
+        "supply": { "voltage": 12 },
+        "circuit": [
+            { "type": "resistor", "resistance": 1000 },
+            { "type": "subcircuit", "definition": "pair" }
+        ]
+    } }"#;
+
+    #[test]
+    fn expands_nested_instances_with_their_arguments() {
+        let circuit = Circuit::from_format(FILE, FileFormat::Json).unwrap();
+
+        assert_eq!(circuit.find_component("X1.X1.RT").unwrap().value().0, 10e3);
+        assert_eq!(circuit.find_component("X1.X2.RT").unwrap().value().0, 30e3);
+        // 1k in series with 20k || 40k
+        assert!((circuit.get_intensity() - 12.0 / (1e3 + 40e3 / 3.0)).abs() < 1e-12);
+
+        let solution = solve(&circuit);
+        let ids: Vec<_> = solution.subcircuits.iter().map(|s| s.id.clone()).collect();
+        assert_eq!(
+            ids,
+            [Some("X1.X1"), Some("X1.X2"), Some("X1")].map(|id| id.map(String::from))
+        );
+        let pair = &solution.subcircuits[2];
+        assert!((pair.current - circuit.get_intensity()).abs() < 1e-12);
+        assert!((pair.power - pair.voltage * pair.current).abs() < 1e-12);
+    }
+
+    #[test]
+    fn round_trips_definitions_and_instances() {
+        let circuit = Circuit::from_format(FILE, FileFormat::Json).unwrap();
+
+        for format in [FileFormat::Json, FileFormat::Yaml, FileFormat::Toml] {
+            let text = circuit.to_format(format).unwrap();
+            assert_eq!(Circuit::from_format(&text, format).unwrap(), circuit);
+        }
+    }
+
+    #[test]
+    fn rejects_recursive_definitions_and_unknown_parameters() {
+        let text = r#"{ "version": 2, "design": {
+            "subcircuits": { "loop": { "circuit": [{ "type": "subcircuit", "definition": "loop" }] } },
+            "supply": { "voltage": 5 },
+            "circuit": [{ "type": "subcircuit", "definition": "loop" }] } }"#;
+        match Circuit::from_format(text, FileFormat::Json) {
+            Err(error) => assert!(error.contains("the subcircuit loop contains itself")),
+            Ok(_) => panic!("recursive subcircuits should be rejected"),
+        }
+
+        let circuit = Circuit::from_format(FILE, FileFormat::Json).unwrap();
+        assert!(circuit.instantiate("divider", &[("bottom", 1.0)]).is_err());
+        assert!(circuit.instantiate("filter", &[]).is_err());
+    }
+
+    #[test]
+    fn keeps_instances_read_only() {
+        let mut circuit = CircuitBuilder::new().supply(5.0).resistor(100.0).build();
+        circuit.define_subcircuit(SubcircuitDefinition::new(
+            "load",
+            CircuitBuilder::new().resistor(50.0).build().get_series(),
+        ));
+        let instance = circuit.instantiate("load", &[]).unwrap();
+        circuit.insert_element(&[], 1, instance).unwrap();
+
+        assert_eq!(circuit.get_series_at(&[1, 0]).unwrap().len(), 1);
+        assert!(circuit.set_resistance(&[1, 0], 0, 10.0).is_err());
+        assert!(circuit.remove_element(&[1, 0], 0).is_err());
+        assert_eq!(circuit.get_intensity(), 5.0 / 150.0);
+    }
+
+    #[test]
+    fn reads_definitions_from_libraries() {
+        let directory = std::env::temp_dir().join(format!("subcircuits-{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(
+            directory.join("parts.yaml"),
+            "version: 2\nsubcircuits:\n  load:\n    parameters:\n      r: 1k\n    circuit:\n    - type: resistor\n      resistance: $r\n",
+        )
+        .unwrap();
+        let path = directory.join("circuit.json");
+        fs::write(
+            &path,
+            r#"{ "version": 2, "design": { "libraries": ["parts.yaml"], "supply": { "voltage": 3 },
+                "circuit": [{ "type": "subcircuit", "definition": "load", "arguments": { "r": "3k" } }] } }"#,
+        )
+        .unwrap();
+
+        let circuit = Circuit::from_file(path.to_str().unwrap()).unwrap();
+        fs::remove_dir_all(&directory).unwrap();
+
+        assert_eq!(circuit.get_intensity(), 1e-3);
+        assert_eq!(
+            circuit.get_subcircuits()["load"].library(),
+            Some("parts.yaml")
+        );
+        // library definitions stay in their file
+        let text = circuit.to_json();
+        assert!(text.contains("\"libraries\"") && !text.contains("\"subcircuits\": {"));
+    }
+}
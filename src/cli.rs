@@ -0,0 +1,516 @@
+use std::{
+    fs,
+    path::Path,
+    sync::OnceLock,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use circuit_sim::{
+    analysis::{
+        check_ratings, diff, explain, monte_carlo, sensitivity, snap_resistances, solve,
+        synthesize, temperature_sweep, ESeries, Probe, Target, DEFAULT_MARGIN,
+    },
+    circuit::{
+        calculate_total_resistance, json_schema, Catalog, Circuit, ElectronicComponentTrait,
+        FileFormat, FORMAT_VERSION,
+    },
+    export::{circuitikz::to_circuitikz, dot::to_dot, svg::to_svg},
+    units::{format_value, set_significant_digits},
+};
+
+const USAGE: &str = "Usage:
+  circuit-sim                                 start the interactive simulator
+  circuit-sim show <circuit.json> [ID]        print the solved circuit, or one component by ID
+  circuit-sim calc <notation>                 solve a circuit written like '12V: R10 + (R4k7 || R2k2)'
+  circuit-sim explain <circuit.json> [output.md]
+                                              explain step by step how the circuit is reduced and solved, or write
+                                              the steps to a Markdown file
+  circuit-sim diff <before.json> <after.json> compare two revisions of a circuit, matching components by ID
+  circuit-sim check <circuit.json> [--margin <PERCENT>]
+                                              check the components against their power and voltage ratings,
+                                              warning above PERCENT of a rating (default 80); fails on overloads
+  circuit-sim montecarlo <circuit.json> [PROBE...] [--runs <N>] [--seed <SEED>]
+                                              solve the circuit N times (default 1000), drawing every resistor within
+                                              its tolerance, and print statistics of the probes, such as 'V(R1)',
+                                              'I(X1)' or 'P(R2)' (default: the voltage of every component)
+  circuit-sim sweep <circuit.json> [PROBE...] [--from <°C>] [--to <°C>] [--step <°C>]
+                                              solve the circuit at ambient temperatures from -40°C to 125°C by steps
+                                              of 15°C unless told otherwise, and plot the drift of the probes
+  circuit-sim sensitivity <circuit.json> <PROBE>
+                                              rank the resistors by their effect on the probe, such as 'V(R2)', and
+                                              find its worst cases with the resistors at the ends of their tolerances
+  circuit-sim snap <circuit.json> <E6|E12|E24|E48|E96> [--pairs] [--id <ID>] [output.json]
+                                              replace every resistance, or the one of the resistor ID, by the nearest
+                                              standard value, or pair of values with --pairs, printing the change of
+                                              the voltages; the snapped circuit is written to the output file
+  circuit-sim synthesize <circuit.json> <TARGET...> --vary <ID,...> [--snap <E6|...|E96>] [--pairs]
+                     [--output <output.json>]
+                                              solve for the resistances of the resistors ID so that the circuit
+                                              meets the targets, such as 'V(R3)=3.3V' or 'I(V1)<=20mA', optionally
+                                              snapped to standard values; fails when the targets can't be met
+  circuit-sim convert <circuit.json> [output.json]
+                                              upgrade a circuit file to the current format, in place by default,
+                                              or convert it to the format of the output file extension
+  circuit-sim schema [output.json]            print the JSON Schema of circuit files
+  circuit-sim svg <circuit.json> [output.svg] export the circuit schematic as SVG
+  circuit-sim dot <circuit.json> [output.dot] export the circuit topology as Graphviz DOT
+  circuit-sim circuitikz <circuit.json> [output.tex] [--annotate]
+                                              export the circuit as a LaTeX circuitikz block
+  circuit-sim parts [QUERY]                   list the parts of the parts library matching every word of QUERY
+
+Options:
+  --digits <N>                                significant digits of the displayed values (default 3)
+  --parts <parts.toml>                        parts library offered when adding components (default parts.toml,
+                                              when it exists)
+
+Circuit files can be written in JSON, YAML (.yaml, .yml) or TOML (.toml).
+Values can be written in engineering notation, such as 4k7, 2.2M or 470m.";
+
+const DEFAULT_CATALOG: &str = "parts.toml";
+
+const DEFAULT_RUNS: usize = 1000;
+
+// Default temperature sweep, in °C
+const DEFAULT_SWEEP: (f64, f64, f64) = (-40.0, 125.0, 15.0);
+
+static CATALOG_PATH: OnceLock<String> = OnceLock::new();
+
+static CATALOG: OnceLock<Result<Catalog, String>> = OnceLock::new();
+
+/// Parts library given by `--parts`, or `parts.toml` when it exists, loaded
+/// on first use; empty when there is none.
+pub fn parts_catalog() -> Result<&'static Catalog, String> {
+    CATALOG
+        .get_or_init(|| {
+            let path = match CATALOG_PATH.get() {
+                Some(path) => path.as_str(),
+                None if Path::new(DEFAULT_CATALOG).is_file() => DEFAULT_CATALOG,
+                None => return Ok(Catalog::default()),
+            };
+            Catalog::load(path)
+                .map_err(|e| format!("Couldn't load the parts library {}: {}", path, e))
+        })
+        .as_ref()
+        .map_err(String::clone)
+}
+
+// Removes the options shared by every command, including the interactive
+// simulator, from `args`
+pub fn apply_global_options(args: &mut Vec<String>) -> Result<(), String> {
+    if let Some(position) = args.iter().position(|arg| arg == "--digits") {
+        let digits = args
+            .get(position + 1)
+            .and_then(|digits| digits.parse::<usize>().ok())
+            .filter(|digits| *digits > 0)
+            .ok_or("--digits expects a positive number")?;
+        set_significant_digits(digits);
+        args.drain(position..position + 2);
+    }
+
+    // the library itself is only loaded by the commands adding parts
+    if let Some(position) = args.iter().position(|arg| arg == "--parts") {
+        let path = args
+            .get(position + 1)
+            .cloned()
+            .ok_or("--parts expects a parts library file")?;
+        args.drain(position..position + 2);
+        CATALOG_PATH.get_or_init(|| path);
+    }
+    Ok(())
+}
+
+fn load_circuit(path: &str) -> Result<Circuit, String> {
+    fs::metadata(path).map_err(|e| format!("Couldn't read {}: {}", path, e))?;
+    Circuit::from_file(path).map_err(|e| format!("Couldn't parse {}: {}", path, e))
+}
+
+fn write_output(output: Option<&String>, content: &str) -> Result<(), String> {
+    match output {
+        Some(path) => {
+            fs::write(path, content).map_err(|e| format!("Couldn't write {}: {}", path, e))
+        }
+        None => {
+            print!("{}", content);
+            Ok(())
+        }
+    }
+}
+
+fn show_command(args: &[String]) -> Result<(), String> {
+    let input = args.first().ok_or(USAGE)?;
+    let circuit = load_circuit(input)?;
+
+    let id = match args.get(1) {
+        Some(id) => id,
+        None => {
+            print!("{}", circuit);
+            return Ok(());
+        }
+    };
+
+    if circuit.get_power_supply().get_id() == Some(id.as_str()) {
+        println!("{}", circuit.get_power_supply());
+        println!("Current: {}", format_value(circuit.get_intensity(), "A"));
+        return Ok(());
+    }
+
+    let component = circuit
+        .find_component(id)
+        .ok_or_else(|| format!("No component with the identifier {}", id))?;
+    let info = component.get_info();
+
+    println!("{}", component);
+    if let Some(notes) = &info.notes {
+        println!("Notes: {}", notes);
+    }
+    if let Some(part) = &info.part {
+        println!("Part: {}", part);
+    }
+    if let Some(tolerance) = info.tolerance {
+        println!(
+            "Tolerance: ±{}% ({})",
+            tolerance,
+            info.distribution.unwrap_or_default()
+        );
+    }
+    if let Some(coefficient) = info.temperature_coefficient {
+        println!("Temperature coefficient: {}ppm/°C", coefficient);
+    }
+    if let Some(power_rating) = info.power_rating {
+        println!("Power rating: {}", format_value(power_rating, "W"));
+    }
+    if let Some(max_voltage) = info.max_voltage {
+        println!("Maximum voltage: {}", format_value(max_voltage, "V"));
+    }
+    println!("Current: {}", format_value(component.get_current(), "A"));
+    println!(
+        "Power: {}",
+        format_value(component.get_current() * component.get_tension(), "W")
+    );
+
+    Ok(())
+}
+
+fn calc_command(args: &[String]) -> Result<(), String> {
+    if args.is_empty() {
+        return Err(String::from(USAGE));
+    }
+    let circuit = Circuit::from_notation(&args.join(" "))?;
+
+    print!("{}", circuit);
+    println!(
+        "Total resistance: {}",
+        format_value(calculate_total_resistance(circuit.get_series()), "Ω")
+    );
+    println!("Current: {}", format_value(circuit.get_intensity(), "A"));
+    println!(
+        "Power: {}",
+        format_value(
+            circuit.get_intensity() * circuit.get_power_supply().get_voltage(),
+            "W"
+        )
+    );
+
+    Ok(())
+}
+
+fn explain_command(args: &[String]) -> Result<(), String> {
+    let (input, output) = match args {
+        [input] => (input, None),
+        [input, output] => (input, Some(output)),
+        _ => return Err(String::from(USAGE)),
+    };
+
+    let explanation = explain(&load_circuit(input)?);
+    match output {
+        Some(output) => fs::write(output, explanation.to_markdown())
+            .map_err(|e| format!("Couldn't write {}: {}", output, e)),
+        None => {
+            print!("{}", explanation);
+            Ok(())
+        }
+    }
+}
+
+fn diff_command(args: &[String]) -> Result<(), String> {
+    let (before, after) = match args {
+        [before, after] => (load_circuit(before)?, load_circuit(after)?),
+        _ => return Err(String::from(USAGE)),
+    };
+
+    print!("{}", diff(&before, &after));
+    Ok(())
+}
+
+fn check_command(args: &[String]) -> Result<(), String> {
+    let (input, margin) = match args {
+        [input] => (input, DEFAULT_MARGIN),
+        [input, option, margin] if option == "--margin" => {
+            let margin = margin
+                .trim_end_matches('%')
+                .parse::<f64>()
+                .ok()
+                .filter(|margin| *margin > 0.0)
+                .ok_or("--margin expects a positive percentage")?;
+            (input, margin / 100.0)
+        }
+        _ => return Err(String::from(USAGE)),
+    };
+
+    let checks = check_ratings(&load_circuit(input)?, margin);
+    print!("{}", checks);
+    if checks.has_overloads() {
+        return Err(String::from("Some components exceed their ratings"));
+    }
+    Ok(())
+}
+
+// Removes `option` and its value from `args`, parsing the value
+fn take_option<T: std::str::FromStr>(
+    args: &mut Vec<String>,
+    option: &str,
+) -> Result<Option<T>, String> {
+    let position = match args.iter().position(|arg| arg == option) {
+        Some(position) => position,
+        None => return Ok(None),
+    };
+    let value = args
+        .get(position + 1)
+        .and_then(|value| value.parse().ok())
+        .ok_or_else(|| format!("{} expects a number", option))?;
+    args.drain(position..position + 2);
+    Ok(Some(value))
+}
+
+// Removes `option` and its value from `args`, for values that aren't numbers
+fn take_text(
+    args: &mut Vec<String>,
+    option: &str,
+    expected: &str,
+) -> Result<Option<String>, String> {
+    match args.iter().position(|arg| arg == option) {
+        Some(position) if position + 1 < args.len() => {
+            let value = args.remove(position + 1);
+            args.remove(position);
+            Ok(Some(value))
+        }
+        Some(_) => Err(format!("{} expects {}", option, expected)),
+        None => Ok(None),
+    }
+}
+
+// Probes written like V(R1), or the voltage of every component when there is
+// none
+fn parse_probes(circuit: &Circuit, probes: &[String]) -> Result<Vec<Probe>, String> {
+    if probes.is_empty() {
+        return Ok(solve(circuit)
+            .components
+            .iter()
+            .filter_map(|component| component.id.as_deref().map(Probe::voltage))
+            .collect());
+    }
+    probes.iter().map(|probe| probe.parse()).collect()
+}
+
+fn montecarlo_command(args: &[String]) -> Result<(), String> {
+    let mut args = args.to_vec();
+    let runs = take_option(&mut args, "--runs")?.unwrap_or(DEFAULT_RUNS);
+    // without a seed, a new one is drawn and printed so the run can be repeated
+    let seed = match take_option(&mut args, "--seed")? {
+        Some(seed) => seed,
+        None => SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.as_nanos() as u64),
+    };
+    let (input, probes) = args.split_first().ok_or(USAGE)?;
+    let circuit = load_circuit(input)?;
+    let probes = parse_probes(&circuit, probes)?;
+
+    print!("{}", monte_carlo(&circuit, &probes, runs, seed)?);
+    Ok(())
+}
+
+fn sweep_command(args: &[String]) -> Result<(), String> {
+    let mut args = args.to_vec();
+    let (from, to, step) = DEFAULT_SWEEP;
+    let from = take_option(&mut args, "--from")?.unwrap_or(from);
+    let to = take_option(&mut args, "--to")?.unwrap_or(to);
+    let step = take_option(&mut args, "--step")?.unwrap_or(step);
+    let (input, probes) = args.split_first().ok_or(USAGE)?;
+    let circuit = load_circuit(input)?;
+    let probes = parse_probes(&circuit, probes)?;
+
+    print!("{}", temperature_sweep(&circuit, &probes, from, to, step)?);
+    Ok(())
+}
+
+fn sensitivity_command(args: &[String]) -> Result<(), String> {
+    let (input, output) = match args {
+        [input, output] => (input, output.parse::<Probe>()?),
+        _ => return Err(String::from(USAGE)),
+    };
+
+    print!("{}", sensitivity(&load_circuit(input)?, &output)?);
+    Ok(())
+}
+
+fn snap_command(args: &[String]) -> Result<(), String> {
+    let mut args = args.to_vec();
+    let pairs = match args.iter().position(|arg| arg == "--pairs") {
+        Some(position) => {
+            args.remove(position);
+            true
+        }
+        None => false,
+    };
+    let id = take_text(&mut args, "--id", "a resistor identifier")?;
+    let (input, series, output) = match args.as_slice() {
+        [input, series] => (input, series, None),
+        [input, series, output] => (input, series, Some(output)),
+        _ => return Err(String::from(USAGE)),
+    };
+
+    let circuit = load_circuit(input)?;
+    let report = snap_resistances(&circuit, series.parse::<ESeries>()?, pairs, id.as_deref())?;
+    print!("{}", report);
+    if let Some(output) = output {
+        fs::write(
+            output,
+            report.circuit.to_format(FileFormat::from_path(output))?,
+        )
+        .map_err(|e| format!("Couldn't write {}: {}", output, e))?;
+    }
+    Ok(())
+}
+
+fn synthesize_command(args: &[String]) -> Result<(), String> {
+    let mut args = args.to_vec();
+    let unknowns: Vec<String> = take_text(&mut args, "--vary", "resistor identifiers")?
+        .ok_or("--vary expects resistor identifiers")?
+        .split(',')
+        .map(|id| String::from(id.trim()))
+        .filter(|id| !id.is_empty())
+        .collect();
+    let series = take_text(&mut args, "--snap", "a series of standard values")?
+        .map(|series| series.parse::<ESeries>())
+        .transpose()?;
+    let pairs = match args.iter().position(|arg| arg == "--pairs") {
+        Some(position) => {
+            args.remove(position);
+            true
+        }
+        None => false,
+    };
+    let output = take_text(&mut args, "--output", "an output file")?;
+    let (input, targets) = args.split_first().ok_or(USAGE)?;
+    let targets = targets
+        .iter()
+        .map(|target| target.parse::<Target>())
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let circuit = load_circuit(input)?;
+    let report = synthesize(
+        &circuit,
+        &unknowns,
+        &targets,
+        series.map(|series| (series, pairs)),
+    )?;
+    print!("{}", report);
+    if let Some(output) = output {
+        fs::write(
+            &output,
+            report.circuit.to_format(FileFormat::from_path(&output))?,
+        )
+        .map_err(|e| format!("Couldn't write {}: {}", output, e))?;
+    }
+    if !report.is_met() {
+        return Err(String::from("Some targets can't be met"));
+    }
+    Ok(())
+}
+
+fn convert_command(args: &[String]) -> Result<(), String> {
+    let input = args.first().ok_or(USAGE)?;
+    let output = args.get(1).unwrap_or(input);
+    let text = fs::read_to_string(input).map_err(|e| format!("Couldn't read {}: {}", input, e))?;
+    let format = FileFormat::from_path(output);
+
+    if !Circuit::is_legacy(&text, FileFormat::from_path(input)) && output == input {
+        println!("{} already uses the current format", input);
+        return Ok(());
+    }
+
+    let circuit = load_circuit(input)?;
+    fs::write(output, circuit.to_format(format)?)
+        .map_err(|e| format!("Couldn't write {}: {}", output, e))?;
+    println!(
+        "Converted {} to {} format version {}",
+        input, format, FORMAT_VERSION
+    );
+
+    Ok(())
+}
+
+fn schema_command(args: &[String]) -> Result<(), String> {
+    write_output(args.first(), &format!("{}\n", json_schema()))
+}
+
+fn svg_command(args: &[String]) -> Result<(), String> {
+    let input = args.first().ok_or(USAGE)?;
+    let circuit = load_circuit(input)?;
+
+    write_output(args.get(1), &to_svg(&circuit))
+}
+
+fn dot_command(args: &[String]) -> Result<(), String> {
+    let input = args.first().ok_or(USAGE)?;
+    let circuit = load_circuit(input)?;
+
+    write_output(args.get(1), &to_dot(&circuit))
+}
+
+fn circuitikz_command(args: &[String]) -> Result<(), String> {
+    let annotate = args.iter().any(|arg| arg == "--annotate");
+    let paths: Vec<&String> = args.iter().filter(|arg| *arg != "--annotate").collect();
+    let input = paths.first().ok_or(USAGE)?;
+    let circuit = load_circuit(input)?;
+
+    write_output(paths.get(1).copied(), &to_circuitikz(&circuit, annotate))
+}
+
+fn parts_command(args: &[String]) -> Result<(), String> {
+    let parts = parts_catalog()?.search(&args.join(" "));
+    if parts.is_empty() {
+        return Err(String::from("No matching part in the parts library"));
+    }
+    for part in parts {
+        println!("{}", part);
+    }
+    Ok(())
+}
+
+pub fn run(args: &[String]) -> Result<(), String> {
+    match args.first().map(String::as_str) {
+        Some("show") => show_command(&args[1..]),
+        Some("calc") => calc_command(&args[1..]),
+        Some("explain") => explain_command(&args[1..]),
+        Some("diff") => diff_command(&args[1..]),
+        Some("check") => check_command(&args[1..]),
+        Some("montecarlo") => montecarlo_command(&args[1..]),
+        Some("sensitivity") => sensitivity_command(&args[1..]),
+        Some("sweep") => sweep_command(&args[1..]),
+        Some("snap") => snap_command(&args[1..]),
+        Some("synthesize") => synthesize_command(&args[1..]),
+        Some("convert") => convert_command(&args[1..]),
+        Some("schema") => schema_command(&args[1..]),
+        Some("svg") => svg_command(&args[1..]),
+        Some("dot") => dot_command(&args[1..]),
+        Some("circuitikz") => circuitikz_command(&args[1..]),
+        Some("parts") => parts_command(&args[1..]),
+        Some("help") | Some("--help") | Some("-h") => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        _ => Err(String::from(USAGE)),
+    }
+}
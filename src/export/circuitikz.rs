@@ -0,0 +1,185 @@
+//! CircuiTikZ export, for LaTeX documents.
+
+use std::fmt::Write;
+
+use crate::{
+    circuit::Circuit,
+    export::{
+        id_prefix,
+        layout::{layout_circuit, Point, Shape},
+    },
+    units::{engineering, significant_digits},
+};
+
+fn coordinate(point: Point) -> String {
+    // tikz y axis grows upwards
+    format!("({},{})", point.x, -point.y)
+}
+
+// Text outside math mode, with the LaTeX special characters escaped
+fn escape(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '\\' => String::from("\\textbackslash{}"),
+            '~' => String::from("\\textasciitilde{}"),
+            '^' => String::from("\\textasciicircum{}"),
+            '<' => String::from("\\textless{}"),
+            '>' => String::from("\\textgreater{}"),
+            '_' | '%' | '&' | '#' | '$' | '{' | '}' => format!("\\{}", c),
+            c => String::from(c),
+        })
+        .collect()
+}
+
+fn quantity(value: f64, unit: &str) -> String {
+    let (mantissa, prefix) = engineering(value, significant_digits());
+    let prefix = match prefix {
+        "µ" => "\\mu ",
+        prefix => prefix,
+    };
+    format!("${}\\,\\mathrm{{{}{}}}$", mantissa, prefix, unit)
+}
+
+/// CircuiTikZ drawing of the circuit, annotated with the solved values when
+/// `annotate` is set.
+pub fn to_circuitikz(circuit: &Circuit, annotate: bool) -> String {
+    let layout = layout_circuit(circuit);
+    let mut tex = String::new();
+
+    writeln!(tex, "\\begin{{circuitikz}}").unwrap();
+
+    for shape in &layout.shapes {
+        match shape {
+            Shape::Wire(from, to) => writeln!(
+                tex,
+                "  \\draw {} -- {};",
+                coordinate(*from),
+                coordinate(*to)
+            )
+            .unwrap(),
+            Shape::Junction(at) => {
+                writeln!(tex, "  \\node[circ] at {} {{}};", coordinate(*at)).unwrap()
+            }
+            Shape::Component {
+                from,
+                to,
+                id,
+                label,
+                name,
+                value,
+                unit,
+                voltage,
+                current,
+            } => {
+                let unit = match *unit {
+                    "Ω" => "\\Omega",
+                    unit => unit,
+                };
+                // other components are drawn as generic bipoles
+                let mut options = format!(
+                    "{}, l={{{}{}{}}}",
+                    if name.is_some() { "generic" } else { "R" },
+                    escape(&id_prefix(id.as_deref(), label.as_deref())),
+                    name.map_or(String::new(), |name| format!("{} ", escape(name))),
+                    quantity(*value, unit)
+                );
+                if annotate {
+                    write!(
+                        options,
+                        ", v={}, i={}",
+                        quantity(*voltage, "V"),
+                        quantity(*current, "A")
+                    )
+                    .unwrap();
+                }
+                writeln!(
+                    tex,
+                    "  \\draw {} to[{}] {};",
+                    coordinate(*from),
+                    options,
+                    coordinate(*to)
+                )
+                .unwrap();
+            }
+            Shape::Source {
+                from,
+                to,
+                id,
+                voltage,
+                current,
+            } => {
+                let mut options = format!(
+                    "V, l={{{}{}}}",
+                    escape(&id_prefix(id.as_deref(), None)),
+                    quantity(*voltage, "V")
+                );
+                if annotate {
+                    write!(options, ", i={}", quantity(*current, "A")).unwrap();
+                }
+                writeln!(
+                    tex,
+                    "  \\draw {} to[{}] {};",
+                    coordinate(*from),
+                    options,
+                    coordinate(*to)
+                )
+                .unwrap();
+            }
+        }
+    }
+
+    writeln!(tex, "\\end{{circuitikz}}").unwrap();
+
+    tex
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::{labelled_circuit, sample_circuit};
+
+    #[test]
+    fn draws_series_and_parallel_components() {
+        assert_eq!(
+            to_circuitikz(&sample_circuit(), false),
+            r"\begin{circuitikz}
+  \draw (3,-5.5) to[V, l={V1 $12\,\mathrm{V}$}] (3,-2);
+  \draw (3,-2) -- (4,-2);
+  \draw (4,-2) to[R, l={R1 $10\,\mathrm{\Omega}$}] (7,-2);
+  \draw (7,-2) -- (7.5,-2);
+  \draw (10.5,-2) -- (11,-2);
+  \draw (7.5,-2) to[R, l={R2 $20\,\mathrm{\Omega}$}] (10.5,-2);
+  \node[circ] at (7.5,-2) {};
+  \node[circ] at (10.5,-2) {};
+  \draw (7.5,-4) to[R, l={R3 $30\,\mathrm{\Omega}$}] (10.5,-4);
+  \node[circ] at (7.5,-4) {};
+  \node[circ] at (10.5,-4) {};
+  \draw (7.5,-2) -- (7.5,-4);
+  \draw (10.5,-2) -- (10.5,-4);
+  \draw (11,-2) -- (12,-2);
+  \draw (12,-2) -- (12,-5.5);
+  \draw (12,-5.5) -- (3,-5.5);
+\end{circuitikz}
+"
+        );
+    }
+
+    #[test]
+    fn annotates_the_solved_values() {
+        let tex = to_circuitikz(&sample_circuit(), true);
+
+        assert!(tex.contains(
+            r"\draw (3,-5.5) to[V, l={V1 $12\,\mathrm{V}$}, i=$545\,\mathrm{mA}$] (3,-2);"
+        ));
+        assert!(tex.contains(
+            r"\draw (7.5,-4) to[R, l={R3 $30\,\mathrm{\Omega}$}, v=$6.55\,\mathrm{V}$, i=$218\,\mathrm{mA}$] (10.5,-4);"
+        ));
+    }
+
+    #[test]
+    fn escapes_labels() {
+        assert!(to_circuitikz(&labelled_circuit(), false).contains(
+            r#"to[R, l={R1 (\textless{}In\textgreater{} \& "out" \{x\} \textbackslash{}y) $10\,\mathrm{\Omega}$}]"#
+        ));
+    }
+}
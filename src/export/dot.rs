@@ -0,0 +1,141 @@
+//! Graphviz DOT export.
+
+use std::fmt::Write;
+
+use crate::{
+    circuit::{Circuit, ElectronicComponent, ElectronicComponentTrait, Series, SeriesElement},
+    export::id_prefix,
+    units::format_value,
+};
+
+// Text inside a quoted DOT string
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+struct DotWriter {
+    dot: String,
+    nodes: usize,
+}
+
+impl DotWriter {
+    fn new_node(&mut self) -> usize {
+        let node = self.nodes;
+        writeln!(self.dot, "  n{} [label=\"N{}\"];", node, node).unwrap();
+        self.nodes += 1;
+        node
+    }
+
+    fn edge(&mut self, from: usize, to: usize, attributes: &str) {
+        writeln!(self.dot, "  n{} -- n{} [{}];", from, to, attributes).unwrap();
+    }
+
+    fn series(&mut self, series: &Series, from: usize, to: usize) {
+        if series.is_empty() {
+            self.edge(from, to, "style=dashed");
+            return;
+        }
+
+        let mut current = from;
+        for (i, element) in series.iter().enumerate() {
+            let next = if i + 1 == series.len() {
+                to
+            } else {
+                self.new_node()
+            };
+            self.element(element, current, next);
+            current = next;
+        }
+    }
+
+    fn element(&mut self, element: &SeriesElement, from: usize, to: usize) {
+        match element {
+            SeriesElement::Component(component) => {
+                let (value, unit) = component.value();
+                let name = match component {
+                    ElectronicComponent::Resistor(_) => String::new(),
+                    _ => format!("{} ", component.name()),
+                };
+                let label = format!(
+                    "label=\"{}{}\\n{}, {}\"",
+                    escape(&format!(
+                        "{}{}",
+                        id_prefix(component.get_id(), component.get_info().label.as_deref()),
+                        name
+                    )),
+                    format_value(value, unit),
+                    format_value(component.get_tension(), "V"),
+                    format_value(component.get_current(), "A")
+                );
+                self.edge(from, to, &label);
+            }
+            SeriesElement::Parallel(branches) => {
+                for branch in branches {
+                    self.series(branch, from, to);
+                }
+            }
+            SeriesElement::Subcircuit(subcircuit) => self.series(subcircuit.get_series(), from, to),
+        }
+    }
+}
+
+/// Graphviz DOT graph of the circuit.
+pub fn to_dot(circuit: &Circuit) -> String {
+    let mut writer = DotWriter {
+        dot: String::new(),
+        nodes: 0,
+    };
+
+    writeln!(writer.dot, "graph circuit {{").unwrap();
+    writeln!(writer.dot, "  rankdir=LR;").unwrap();
+    writeln!(writer.dot, "  node [shape=circle, fontsize=10];").unwrap();
+    writeln!(writer.dot, "  edge [fontsize=10];").unwrap();
+
+    let positive = writer.new_node();
+    let ground = writer.new_node();
+
+    let supply = format!(
+        "label=\"{}{}\\n{}\", color=red",
+        escape(&id_prefix(circuit.get_power_supply().get_id(), None)),
+        format_value(circuit.get_power_supply().get_voltage(), "V"),
+        format_value(circuit.get_intensity(), "A")
+    );
+    writer.edge(ground, positive, &supply);
+    writer.series(circuit.get_series(), positive, ground);
+
+    writeln!(writer.dot, "}}").unwrap();
+
+    writer.dot
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::{labelled_circuit, sample_circuit};
+
+    #[test]
+    fn draws_series_and_parallel_components() {
+        assert_eq!(
+            to_dot(&sample_circuit()),
+            r#"graph circuit {
+  rankdir=LR;
+  node [shape=circle, fontsize=10];
+  edge [fontsize=10];
+  n0 [label="N0"];
+  n1 [label="N1"];
+  n1 -- n0 [label="V1 12V\n545mA", color=red];
+  n2 [label="N2"];
+  n0 -- n2 [label="R1 10Ω\n5.45V, 545mA"];
+  n2 -- n1 [label="R2 20Ω\n6.55V, 327mA"];
+  n2 -- n1 [label="R3 30Ω\n6.55V, 218mA"];
+}
+"#
+        );
+    }
+
+    #[test]
+    fn escapes_labels() {
+        assert!(to_dot(&labelled_circuit())
+            .contains(r#"n0 -- n2 [label="R1 (<In> & \"out\" {x} \\y) 10Ω\n5.45V, 545mA"];"#));
+    }
+}
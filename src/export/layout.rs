@@ -0,0 +1,283 @@
+use crate::circuit::{
+    Circuit, ElectronicComponent, ElectronicComponentTrait, Series, SeriesElement,
+};
+
+const COMPONENT_WIDTH: f64 = 3.0;
+const COMPONENT_HEIGHT: f64 = 2.0;
+const WIRE_WIDTH: f64 = 1.0;
+const BUS_PADDING: f64 = 0.5;
+const MARGIN_LEFT: f64 = 3.0;
+const MARGIN: f64 = 1.0;
+
+#[derive(Clone, Copy)]
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
+}
+
+pub enum Shape {
+    Wire(Point, Point),
+    Junction(Point),
+    // `name` is None for resistors, whose value is their resistance
+    Component {
+        from: Point,
+        to: Point,
+        id: Option<String>,
+        label: Option<String>,
+        name: Option<&'static str>,
+        value: f64,
+        unit: &'static str,
+        voltage: f64,
+        current: f64,
+    },
+    // `from` is the negative terminal, `to` the positive one
+    Source {
+        from: Point,
+        to: Point,
+        id: Option<String>,
+        voltage: f64,
+        current: f64,
+    },
+}
+
+// Coordinates are grid units, y grows downwards
+pub struct Layout {
+    pub width: f64,
+    pub height: f64,
+    pub shapes: Vec<Shape>,
+}
+
+struct Size {
+    width: f64,
+    height: f64,
+    // distance between the top of the block and its connection wire
+    anchor: f64,
+}
+
+impl Point {
+    pub fn new(x: f64, y: f64) -> Self {
+        Point { x, y }
+    }
+}
+
+fn measure_series(series: &Series) -> Size {
+    if series.is_empty() {
+        return Size {
+            width: WIRE_WIDTH,
+            height: COMPONENT_HEIGHT,
+            anchor: COMPONENT_HEIGHT / 2.0,
+        };
+    }
+
+    let sizes: Vec<Size> = series.iter().map(measure_element).collect();
+    let anchor = sizes.iter().map(|s| s.anchor).fold(0.0, f64::max);
+    let below = sizes
+        .iter()
+        .map(|s| s.height - s.anchor)
+        .fold(0.0, f64::max);
+
+    Size {
+        width: sizes.iter().map(|s| s.width).sum(),
+        height: anchor + below,
+        anchor,
+    }
+}
+
+fn measure_parallel(branches: &[Series]) -> Size {
+    let sizes: Vec<Size> = branches.iter().map(measure_series).collect();
+
+    Size {
+        width: sizes.iter().map(|s| s.width).fold(0.0, f64::max) + 2.0 * BUS_PADDING,
+        height: sizes.iter().map(|s| s.height).sum(),
+        anchor: sizes.first().map_or(COMPONENT_HEIGHT / 2.0, |s| s.anchor),
+    }
+}
+
+fn measure_element(element: &SeriesElement) -> Size {
+    match element {
+        SeriesElement::Component(_) => Size {
+            width: COMPONENT_WIDTH,
+            height: COMPONENT_HEIGHT,
+            anchor: COMPONENT_HEIGHT / 2.0,
+        },
+        SeriesElement::Parallel(branches) => measure_parallel(branches),
+        SeriesElement::Subcircuit(subcircuit) => measure_series(subcircuit.get_series()),
+    }
+}
+
+// Places `series` with its connection wire at height `y`, starting at `x`.
+// Returns the x coordinate where the series ends.
+fn place_series(series: &Series, x: f64, y: f64, shapes: &mut Vec<Shape>) -> f64 {
+    if series.is_empty() {
+        shapes.push(Shape::Wire(Point::new(x, y), Point::new(x + WIRE_WIDTH, y)));
+        return x + WIRE_WIDTH;
+    }
+
+    series
+        .iter()
+        .fold(x, |x, element| place_element(element, x, y, shapes))
+}
+
+fn place_element(element: &SeriesElement, x: f64, y: f64, shapes: &mut Vec<Shape>) -> f64 {
+    match element {
+        SeriesElement::Component(component) => {
+            let name = match component {
+                ElectronicComponent::Resistor(_) => None,
+                _ => Some(component.name()),
+            };
+            let (value, unit) = component.value();
+            shapes.push(Shape::Component {
+                from: Point::new(x, y),
+                to: Point::new(x + COMPONENT_WIDTH, y),
+                id: component.get_id().map(String::from),
+                label: component.get_info().label.clone(),
+                name,
+                value,
+                unit,
+                voltage: component.get_tension(),
+                current: component.get_current(),
+            });
+            x + COMPONENT_WIDTH
+        }
+        SeriesElement::Parallel(branches) => place_parallel(branches, x, y, shapes),
+        SeriesElement::Subcircuit(subcircuit) => {
+            place_series(subcircuit.get_series(), x, y, shapes)
+        }
+    }
+}
+
+fn place_parallel(branches: &[Series], x: f64, y: f64, shapes: &mut Vec<Shape>) -> f64 {
+    let size = measure_parallel(branches);
+    let left_bus = x + BUS_PADDING;
+    let right_bus = x + size.width - BUS_PADDING;
+
+    shapes.push(Shape::Wire(Point::new(x, y), Point::new(left_bus, y)));
+    shapes.push(Shape::Wire(
+        Point::new(right_bus, y),
+        Point::new(x + size.width, y),
+    ));
+
+    let top = y - size.anchor;
+    let mut offset = 0.0;
+    let mut last_y = y;
+
+    for branch in branches {
+        let branch_size = measure_series(branch);
+        let branch_y = top + offset + branch_size.anchor;
+        let end = place_series(branch, left_bus, branch_y, shapes);
+
+        if end < right_bus {
+            shapes.push(Shape::Wire(
+                Point::new(end, branch_y),
+                Point::new(right_bus, branch_y),
+            ));
+        }
+        if branches.len() > 1 {
+            shapes.push(Shape::Junction(Point::new(left_bus, branch_y)));
+            shapes.push(Shape::Junction(Point::new(right_bus, branch_y)));
+        }
+
+        last_y = branch_y;
+        offset += branch_size.height;
+    }
+
+    shapes.push(Shape::Wire(
+        Point::new(left_bus, y),
+        Point::new(left_bus, last_y),
+    ));
+    shapes.push(Shape::Wire(
+        Point::new(right_bus, y),
+        Point::new(right_bus, last_y),
+    ));
+
+    x + size.width
+}
+
+pub fn layout_circuit(circuit: &Circuit) -> Layout {
+    let series = circuit.get_series();
+    let size = measure_series(series);
+    let mut shapes = Vec::new();
+
+    let rail = MARGIN + size.anchor;
+    let ground = (MARGIN + size.height + MARGIN / 2.0).max(rail + COMPONENT_HEIGHT);
+    let source_x = MARGIN_LEFT;
+    let start = source_x + WIRE_WIDTH;
+
+    shapes.push(Shape::Source {
+        from: Point::new(source_x, ground),
+        to: Point::new(source_x, rail),
+        id: circuit.get_power_supply().get_id().map(String::from),
+        voltage: circuit.get_power_supply().get_voltage(),
+        current: circuit.get_intensity(),
+    });
+    shapes.push(Shape::Wire(
+        Point::new(source_x, rail),
+        Point::new(start, rail),
+    ));
+
+    let end = place_series(series, start, rail, &mut shapes);
+    let corner = end + WIRE_WIDTH;
+
+    shapes.push(Shape::Wire(Point::new(end, rail), Point::new(corner, rail)));
+    shapes.push(Shape::Wire(
+        Point::new(corner, rail),
+        Point::new(corner, ground),
+    ));
+    shapes.push(Shape::Wire(
+        Point::new(corner, ground),
+        Point::new(source_x, ground),
+    ));
+
+    Layout {
+        width: corner + MARGIN,
+        height: ground + MARGIN,
+        shapes,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::sample_circuit;
+
+    // (x, y) of the points, to compare them
+    fn ends(from: &Point, to: &Point) -> ((f64, f64), (f64, f64)) {
+        ((from.x, from.y), (to.x, to.y))
+    }
+
+    #[test]
+    fn places_parallel_branches_below_each_other() {
+        let layout = layout_circuit(&sample_circuit());
+        assert_eq!((layout.width, layout.height), (13.0, 6.5));
+
+        let components: Vec<_> = layout
+            .shapes
+            .iter()
+            .filter_map(|shape| match shape {
+                Shape::Component { from, to, .. } => Some(ends(from, to)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            components,
+            [
+                ((4.0, 2.0), (7.0, 2.0)),
+                ((7.5, 2.0), (10.5, 2.0)),
+                ((7.5, 4.0), (10.5, 4.0)),
+            ]
+        );
+
+        match &layout.shapes[0] {
+            Shape::Source { from, to, .. } => {
+                assert_eq!(ends(from, to), ((3.0, 5.5), (3.0, 2.0)))
+            }
+            _ => panic!("the power supply is placed first"),
+        }
+        let junctions = layout
+            .shapes
+            .iter()
+            .filter(|shape| matches!(shape, Shape::Junction(_)))
+            .count();
+        assert_eq!(junctions, 4);
+    }
+}
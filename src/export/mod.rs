@@ -0,0 +1,48 @@
+//! Exports of circuit diagrams.
+
+pub mod circuitikz;
+pub mod dot;
+mod layout;
+pub mod svg;
+
+// "R1 (Pull-up) " prefix of the annotations of identified or labelled
+// components
+fn id_prefix(id: Option<&str>, label: Option<&str>) -> String {
+    match (id, label) {
+        (Some(id), Some(label)) => format!("{} ({}) ", id, label),
+        (Some(id), None) => format!("{} ", id),
+        (None, Some(label)) => format!("({}) ", label),
+        (None, None) => String::new(),
+    }
+}
+
+// R1 in series with R2 || R3, the circuit drawn by the tests of the exports
+#[cfg(test)]
+fn sample_circuit() -> crate::circuit::Circuit {
+    use crate::circuit::{Circuit, ElectronicComponent, PowerSupply, SeriesElement};
+
+    let resistor = |resistance| SeriesElement::new(ElectronicComponent::new_resistor(resistance));
+    Circuit::new(
+        PowerSupply::new(12.0),
+        vec![
+            resistor(10.0),
+            SeriesElement::new_parallel(vec![vec![resistor(20.0)], vec![resistor(30.0)]]),
+        ],
+    )
+}
+
+// The sample circuit with a label on R1 made of characters every format
+// escapes
+#[cfg(test)]
+fn labelled_circuit() -> crate::circuit::Circuit {
+    use crate::circuit::ComponentInfo;
+
+    let mut circuit = sample_circuit();
+    let info = ComponentInfo {
+        id: Some(String::from("R1")),
+        label: Some(String::from(r#"<In> & "out" {x} \y"#)),
+        ..ComponentInfo::default()
+    };
+    circuit.set_component_info(&[], 0, info).unwrap();
+    circuit
+}
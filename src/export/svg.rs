@@ -0,0 +1,249 @@
+//! SVG export.
+
+use std::fmt::Write;
+
+use crate::{
+    circuit::Circuit,
+    export::{
+        id_prefix,
+        layout::{layout_circuit, Point, Shape},
+    },
+    units::format_value,
+};
+
+const SCALE: f64 = 40.0;
+const STROKE: &str = "stroke=\"black\" stroke-width=\"2\"";
+const FONT: &str = "font-family=\"sans-serif\" font-size=\"12\"";
+
+fn line(svg: &mut String, from: Point, to: Point) {
+    writeln!(
+        svg,
+        "  <line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" {} />",
+        from.x * SCALE,
+        from.y * SCALE,
+        to.x * SCALE,
+        to.y * SCALE,
+        STROKE
+    )
+    .unwrap();
+}
+
+fn text(svg: &mut String, at: Point, anchor: &str, content: &str) {
+    let content = content
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;");
+    writeln!(
+        svg,
+        "  <text x=\"{}\" y=\"{}\" text-anchor=\"{}\" {}>{}</text>",
+        at.x * SCALE,
+        at.y * SCALE,
+        anchor,
+        FONT,
+        content
+    )
+    .unwrap();
+}
+
+// Components are drawn as resistors, labelled with their kind unless they are
+// resistors
+fn draw_component(
+    svg: &mut String,
+    from: Point,
+    to: Point,
+    label: &str,
+    voltage: f64,
+    current: f64,
+) {
+    let middle = (from.x + to.x) / 2.0;
+    let body_start = middle - 0.6;
+    let body_end = middle + 0.6;
+
+    line(svg, from, Point::new(body_start, from.y));
+    line(svg, Point::new(body_end, to.y), to);
+    writeln!(
+        svg,
+        "  <rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"white\" {} />",
+        body_start * SCALE,
+        (from.y - 0.2) * SCALE,
+        1.2 * SCALE,
+        0.4 * SCALE,
+        STROKE
+    )
+    .unwrap();
+
+    text(svg, Point::new(middle, from.y - 0.4), "middle", label);
+    text(
+        svg,
+        Point::new(middle, from.y + 0.65),
+        "middle",
+        &format!(
+            "{}, {}",
+            format_value(voltage, "V"),
+            format_value(current, "A")
+        ),
+    );
+}
+
+fn draw_source(
+    svg: &mut String,
+    from: Point,
+    to: Point,
+    id: &Option<String>,
+    voltage: f64,
+    current: f64,
+) {
+    let middle = (from.y + to.y) / 2.0;
+    let positive = middle - 0.1;
+    let negative = middle + 0.1;
+
+    line(svg, to, Point::new(to.x, positive));
+    line(svg, Point::new(from.x, negative), from);
+    line(
+        svg,
+        Point::new(to.x - 0.4, positive),
+        Point::new(to.x + 0.4, positive),
+    );
+    line(
+        svg,
+        Point::new(to.x - 0.2, negative),
+        Point::new(to.x + 0.2, negative),
+    );
+
+    text(svg, Point::new(to.x + 0.5, positive - 0.1), "start", "+");
+    text(
+        svg,
+        Point::new(to.x - 0.6, middle),
+        "end",
+        &format!(
+            "{}{}",
+            id_prefix(id.as_deref(), None),
+            format_value(voltage, "V")
+        ),
+    );
+    text(
+        svg,
+        Point::new(to.x - 0.6, middle + 0.4),
+        "end",
+        &format_value(current, "A"),
+    );
+}
+
+/// SVG drawing of the circuit.
+pub fn to_svg(circuit: &Circuit) -> String {
+    let layout = layout_circuit(circuit);
+    let mut svg = String::new();
+
+    writeln!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" viewBox=\"0 0 {0} {1}\">",
+        layout.width * SCALE,
+        layout.height * SCALE
+    )
+    .unwrap();
+    writeln!(
+        svg,
+        "  <rect width=\"100%\" height=\"100%\" fill=\"white\" />"
+    )
+    .unwrap();
+
+    for shape in &layout.shapes {
+        match shape {
+            Shape::Wire(from, to) => line(&mut svg, *from, *to),
+            Shape::Junction(at) => writeln!(
+                svg,
+                "  <circle cx=\"{}\" cy=\"{}\" r=\"3\" fill=\"black\" />",
+                at.x * SCALE,
+                at.y * SCALE
+            )
+            .unwrap(),
+            Shape::Component {
+                from,
+                to,
+                id,
+                label,
+                name,
+                value,
+                unit,
+                voltage,
+                current,
+            } => {
+                let label = format!(
+                    "{}{}{}",
+                    id_prefix(id.as_deref(), label.as_deref()),
+                    name.map_or(String::new(), |name| format!("{} ", name)),
+                    format_value(*value, unit)
+                );
+                draw_component(&mut svg, *from, *to, &label, *voltage, *current)
+            }
+            Shape::Source {
+                from,
+                to,
+                id,
+                voltage,
+                current,
+            } => draw_source(&mut svg, *from, *to, id, *voltage, *current),
+        }
+    }
+
+    writeln!(svg, "</svg>").unwrap();
+
+    svg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::{labelled_circuit, sample_circuit};
+
+    #[test]
+    fn draws_series_and_parallel_components() {
+        assert_eq!(
+            to_svg(&sample_circuit()),
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="520" height="260" viewBox="0 0 520 260">
+  <rect width="100%" height="100%" fill="white" />
+  <line x1="120" y1="80" x2="120" y2="146" stroke="black" stroke-width="2" />
+  <line x1="120" y1="154" x2="120" y2="220" stroke="black" stroke-width="2" />
+  <line x1="104" y1="146" x2="136" y2="146" stroke="black" stroke-width="2" />
+  <line x1="112" y1="154" x2="128" y2="154" stroke="black" stroke-width="2" />
+  <text x="140" y="142" text-anchor="start" font-family="sans-serif" font-size="12">+</text>
+  <text x="96" y="150" text-anchor="end" font-family="sans-serif" font-size="12">V1 12V</text>
+  <text x="96" y="166" text-anchor="end" font-family="sans-serif" font-size="12">545mA</text>
+  <line x1="120" y1="80" x2="160" y2="80" stroke="black" stroke-width="2" />
+  <line x1="160" y1="80" x2="196" y2="80" stroke="black" stroke-width="2" />
+  <line x1="244" y1="80" x2="280" y2="80" stroke="black" stroke-width="2" />
+  <rect x="196" y="72" width="48" height="16" fill="white" stroke="black" stroke-width="2" />
+  <text x="220" y="64" text-anchor="middle" font-family="sans-serif" font-size="12">R1 10Ω</text>
+  <text x="220" y="106" text-anchor="middle" font-family="sans-serif" font-size="12">5.45V, 545mA</text>
+  <line x1="280" y1="80" x2="300" y2="80" stroke="black" stroke-width="2" />
+  <line x1="420" y1="80" x2="440" y2="80" stroke="black" stroke-width="2" />
+  <line x1="300" y1="80" x2="336" y2="80" stroke="black" stroke-width="2" />
+  <line x1="384" y1="80" x2="420" y2="80" stroke="black" stroke-width="2" />
+  <rect x="336" y="72" width="48" height="16" fill="white" stroke="black" stroke-width="2" />
+  <text x="360" y="64" text-anchor="middle" font-family="sans-serif" font-size="12">R2 20Ω</text>
+  <text x="360" y="106" text-anchor="middle" font-family="sans-serif" font-size="12">6.55V, 327mA</text>
+  <circle cx="300" cy="80" r="3" fill="black" />
+  <circle cx="420" cy="80" r="3" fill="black" />
+  <line x1="300" y1="160" x2="336" y2="160" stroke="black" stroke-width="2" />
+  <line x1="384" y1="160" x2="420" y2="160" stroke="black" stroke-width="2" />
+  <rect x="336" y="152" width="48" height="16" fill="white" stroke="black" stroke-width="2" />
+  <text x="360" y="144" text-anchor="middle" font-family="sans-serif" font-size="12">R3 30Ω</text>
+  <text x="360" y="186" text-anchor="middle" font-family="sans-serif" font-size="12">6.55V, 218mA</text>
+  <circle cx="300" cy="160" r="3" fill="black" />
+  <circle cx="420" cy="160" r="3" fill="black" />
+  <line x1="300" y1="80" x2="300" y2="160" stroke="black" stroke-width="2" />
+  <line x1="420" y1="80" x2="420" y2="160" stroke="black" stroke-width="2" />
+  <line x1="440" y1="80" x2="480" y2="80" stroke="black" stroke-width="2" />
+  <line x1="480" y1="80" x2="480" y2="220" stroke="black" stroke-width="2" />
+  <line x1="480" y1="220" x2="120" y2="220" stroke="black" stroke-width="2" />
+</svg>
+"#
+        );
+    }
+
+    #[test]
+    fn escapes_labels() {
+        assert!(to_svg(&labelled_circuit())
+            .contains(r#"font-size="12">R1 (&lt;In&gt; &amp; "out" {x} \y) 10Ω</text>"#));
+    }
+}
@@ -0,0 +1,49 @@
+//! Electronic circuit simulator.
+//!
+//! Circuits are made of a [`PowerSupply`](circuit::PowerSupply) feeding a
+//! [`Series`](circuit::Series) of resistors and parallel groups of branches.
+//! They are solved every time they change, and [`analysis::solve`] gathers
+//! the resulting voltages, currents and powers.
+//!
+//! ```
+//! use circuit_sim::{
+//!     analysis::solve,
+//!     circuit::{Circuit, ElectronicComponent, PowerSupply, SeriesElement},
+//! };
+//!
+//! let circuit = Circuit::new(
+//!     PowerSupply::new(9.0),
+//!     vec![
+//!         SeriesElement::new(ElectronicComponent::new_resistor(1000.0)),
+//!         SeriesElement::new(ElectronicComponent::new_resistor(2000.0)),
+//!     ],
+//! );
+//!
+//! assert_eq!(solve(&circuit).component("R2").unwrap().voltage, 6.0);
+//! ```
+//!
+//! Items that are not documented here are not part of the public API and may
+//! change in any release.
+#![warn(missing_docs)]
+
+pub mod analysis;
+pub mod circuit;
+pub mod export;
+pub mod units;
+
+/// Internals kept out of the public API, checked by the examples below.
+///
+/// The solver helpers are private:
+///
+/// ```compile_fail
+/// use circuit_sim::circuit::calculate_series_voltage;
+/// ```
+///
+/// Circuits are read and written with [`circuit::FileFormat`] only, the
+/// unversioned format being read for migration alone:
+///
+/// ```compile_fail
+/// let circuit: circuit_sim::circuit::Circuit = serde_json::from_str("{}").unwrap();
+/// ```
+#[cfg(doctest)]
+pub struct PrivateApi;
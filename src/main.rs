@@ -1,12 +1,28 @@
-use circuit::{Circuit, PowerSupply, Series};
+use circuit_sim::circuit::{Circuit, History, PowerSupply, Series};
 
 use dialoguer::{console::Term, theme::ColorfulTheme};
 
-mod circuit;
+mod cli;
+mod tui;
 mod utils;
 mod views;
 
 fn main() {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+
+    if let Err(message) = cli::apply_global_options(&mut args) {
+        eprintln!("{}", message);
+        std::process::exit(1);
+    }
+
+    if !args.is_empty() {
+        if let Err(message) = cli::run(&args) {
+            eprintln!("{}", message);
+            std::process::exit(1);
+        }
+        return;
+    }
+
     let term = Term::stdout();
 
     let theme = &ColorfulTheme::default();
@@ -15,29 +31,12 @@ fn main() {
 
     let series: Series = vec![];
 
-    let mut circuit = Circuit::new(power_supply, series);
-
-    let mut current_message: Option<String> = Some(String::from(""));
+    let circuit = Circuit::new(power_supply, series);
 
-    loop {
-        match current_message.to_owned() {
-            Some(data) => {
-                if data.is_empty() {
-                    current_message = None;
-                }
-            }
-            None => {}
-        }
+    let history = History::new();
 
-        let response = views::home(&mut circuit, &term, theme, current_message.clone());
-
-        match response {
-            views::HomeReturn::Exit => break,
-            views::HomeReturn::Continue => continue,
-            views::HomeReturn::ContinueWithMessage(message) => {
-                current_message = Some(message);
-                continue;
-            }
-        }
+    if let Err(error) = tui::run(circuit, history, &term, theme) {
+        eprintln!("{}", error);
+        std::process::exit(1);
     }
 }
@@ -0,0 +1,663 @@
+use std::{collections::HashSet, fs};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use circuit_sim::{
+    analysis::{explain, snap_resistances, ESeries},
+    circuit::{Circuit, Edit, ElectronicComponent, History, SeriesElement},
+    units::{format_value, parse_value},
+};
+
+use crate::cli::parts_catalog;
+
+// Lines scrolled by PgUp and PgDn in the explanation
+const EXPLANATION_PAGE: usize = 10;
+
+const DEFAULT_EXPLANATION: &str = "explanation.md";
+
+pub enum Row {
+    Supply,
+    Element {
+        path: Vec<usize>,
+        index: usize,
+    },
+    Branch {
+        path: Vec<usize>,
+        index: usize,
+        branch: usize,
+    },
+}
+
+pub struct TreeLine {
+    pub depth: usize,
+    pub row: Row,
+}
+
+pub enum Flow {
+    Continue,
+    Prompt(Prompt),
+    Menu,
+    Quit,
+}
+
+pub enum Prompt {
+    Resistance(Row, Placement),
+    Voltage,
+    AddBranch(Vec<usize>, usize),
+    Label(Vec<usize>, usize),
+    Notes(Vec<usize>, usize),
+    // warning shown when the notation can't hold the whole circuit, which is
+    // then replaced rather than edited
+    Notation(Option<String>),
+    // identifier of the resistor to snap, every resistor when missing
+    Snap(Option<String>),
+    Explanation,
+    // confirmation of quitting with unsaved edits
+    Quit,
+}
+
+pub enum Placement {
+    Replace,
+    Before,
+    After,
+}
+
+pub struct App {
+    pub circuit: Circuit,
+    pub history: History,
+    // circuit as last loaded or saved, to warn about unsaved edits
+    pub saved: Circuit,
+    pub collapsed: HashSet<Vec<usize>>,
+    pub cursor: usize,
+    pub status: String,
+    // first line of the explanation shown instead of the results, if any
+    pub explanation: Option<usize>,
+}
+
+impl Row {
+    // Key identifying a collapsible node of the tree
+    fn key(&self) -> Option<Vec<usize>> {
+        match self {
+            Row::Supply => None,
+            Row::Element { path, index } => Some([path.as_slice(), &[*index]].concat()),
+            Row::Branch {
+                path,
+                index,
+                branch,
+            } => Some([path.as_slice(), &[*index, *branch]].concat()),
+        }
+    }
+}
+
+impl Prompt {
+    pub fn label(&self) -> &str {
+        match self {
+            Prompt::Resistance(_, Placement::Replace) => "New resistance (Ω)",
+            Prompt::Resistance(..) | Prompt::AddBranch(..) => {
+                "Resistance of the new component (Ω), or part to search"
+            }
+            Prompt::Voltage => "Power supply voltage (V)",
+            Prompt::Label(..) => "Label (empty to remove)",
+            Prompt::Notes(..) => "Notes (empty to remove)",
+            Prompt::Notation(Some(warning)) => warning,
+            Prompt::Notation(None) => "Circuit (e.g. 12V: R10 + (R4k7 || R2k2))",
+            Prompt::Snap(_) => "Standard values E6, E12, E24, E48 or E96, + for pairs (e.g. E24+)",
+            Prompt::Explanation => "Markdown file to write the explanation to",
+            Prompt::Quit => "The circuit has unsaved edits, quit anyway? (y/N)",
+        }
+    }
+}
+
+// Resistor of the given value, or the part of the parts library matching
+// the search
+fn new_component(input: &str) -> Result<ElectronicComponent, String> {
+    if let Ok(value) = parse_value(input) {
+        return Ok(ElectronicComponent::new_resistor(value));
+    }
+
+    let catalog = parts_catalog()?;
+    let parts = match catalog.get(input.trim()) {
+        Some(part) => vec![part],
+        None => catalog.search(input),
+    };
+    match parts.as_slice() {
+        [part] => part.component(),
+        [] => Err(format!("No value or part matches {}", input.trim())),
+        parts => Err(format!(
+            "{} parts match: {}",
+            parts.len(),
+            parts
+                .iter()
+                .map(|part| part.part_number.as_str())
+                .collect::<Vec<&str>>()
+                .join(", ")
+        )),
+    }
+}
+
+impl App {
+    pub fn new(circuit: Circuit, history: History) -> Self {
+        App {
+            saved: circuit.clone(),
+            circuit,
+            history,
+            collapsed: HashSet::new(),
+            cursor: 0,
+            status: String::new(),
+            explanation: None,
+        }
+    }
+
+    fn push_series(&self, path: &[usize], depth: usize, lines: &mut Vec<TreeLine>) {
+        let series = match self.circuit.get_series_at(path) {
+            Some(series) => series,
+            None => return,
+        };
+
+        for (index, element) in series.iter().enumerate() {
+            let row = Row::Element {
+                path: path.to_vec(),
+                index,
+            };
+            let expanded = !row.key().is_some_and(|key| self.collapsed.contains(&key));
+            lines.push(TreeLine { depth, row });
+
+            if let (SeriesElement::Parallel(branches), true) = (element, expanded) {
+                for branch in 0..branches.len() {
+                    let branch_path = [path, &[index, branch]].concat();
+                    let row = Row::Branch {
+                        path: path.to_vec(),
+                        index,
+                        branch,
+                    };
+                    let expanded = !self.collapsed.contains(&branch_path);
+                    lines.push(TreeLine {
+                        depth: depth + 1,
+                        row,
+                    });
+                    if expanded {
+                        self.push_series(&branch_path, depth + 2, lines);
+                    }
+                }
+            }
+            if let (SeriesElement::Subcircuit(_), true) = (element, expanded) {
+                self.push_series(&[path, &[index, 0]].concat(), depth + 1, lines);
+            }
+        }
+    }
+
+    pub fn lines(&self) -> Vec<TreeLine> {
+        let mut lines = vec![TreeLine {
+            depth: 0,
+            row: Row::Supply,
+        }];
+        self.push_series(&[], 1, &mut lines);
+        lines
+    }
+
+    pub fn is_collapsed(&self, row: &Row) -> bool {
+        row.key().is_some_and(|key| self.collapsed.contains(&key))
+    }
+
+    pub fn has_unsaved_edits(&self) -> bool {
+        self.circuit != self.saved
+    }
+
+    // Collapsed nodes are keyed by their position, which edits shift: expand
+    // every node again, and keep the cursor within the tree
+    pub fn after_edit(&mut self) {
+        self.collapsed.clear();
+        self.cursor = self.cursor.min(self.lines().len() - 1);
+    }
+
+    fn selected(&self) -> Row {
+        self.lines()
+            .into_iter()
+            .nth(self.cursor)
+            .map_or(Row::Supply, |line| line.row)
+    }
+
+    fn apply(&mut self, edit: Edit, success: &str) -> bool {
+        match self.history.apply(&mut self.circuit, edit) {
+            Ok(_) => {
+                self.after_edit();
+                self.status = String::from(success);
+                true
+            }
+            Err(message) => {
+                self.status = message;
+                false
+            }
+        }
+    }
+
+    fn toggle(&mut self) {
+        if let Some(key) = self.selected().key() {
+            if !self.collapsed.remove(&key) {
+                self.collapsed.insert(key);
+            }
+        }
+    }
+
+    fn is_parallel(&self, path: &[usize], index: usize) -> bool {
+        matches!(
+            self.circuit.get_series_at(path).and_then(|s| s.get(index)),
+            Some(SeriesElement::Parallel(_))
+        )
+    }
+
+    fn move_selected(&mut self, up: bool) {
+        if let Row::Element { path, index } = self.selected() {
+            let target = if up {
+                index.checked_sub(1)
+            } else {
+                Some(index + 1)
+            };
+            match target {
+                Some(target) => {
+                    let edit = Edit::Move {
+                        path: path.clone(),
+                        index,
+                        target,
+                    };
+                    if self.apply(edit, "Element moved") {
+                        self.cursor = self
+                            .lines()
+                            .iter()
+                            .position(|line| match &line.row {
+                                Row::Element { path: p, index: i } => *p == path && *i == target,
+                                _ => false,
+                            })
+                            .unwrap_or(self.cursor);
+                    }
+                }
+                None => self.status = String::from("The element can't be moved further"),
+            }
+        }
+    }
+
+    fn delete_selected(&mut self) {
+        match self.selected() {
+            Row::Supply => self.status = String::from("The power supply can't be deleted"),
+            Row::Element { path, index } => {
+                self.apply(Edit::Remove { path, index }, "Element deleted");
+            }
+            Row::Branch {
+                path,
+                index,
+                branch,
+            } => {
+                self.apply(
+                    Edit::RemoveBranch {
+                        path,
+                        index,
+                        branch,
+                    },
+                    "Branch removed",
+                );
+            }
+        }
+    }
+
+    fn wrap_selected(&mut self) {
+        match self.selected() {
+            Row::Element { path, index } => {
+                self.apply(
+                    Edit::WrapInParallel {
+                        path,
+                        index,
+                        count: 1,
+                    },
+                    "Element wrapped into a parallel, press b to add a branch",
+                );
+            }
+            _ => self.status = String::from("Select an element to wrap"),
+        }
+    }
+
+    fn edit_selected(&mut self) -> Flow {
+        match self.selected() {
+            Row::Supply => Flow::Prompt(Prompt::Voltage),
+            Row::Element { path, index } if !self.is_parallel(&path, index) => Flow::Prompt(
+                Prompt::Resistance(Row::Element { path, index }, Placement::Replace),
+            ),
+            _ => {
+                self.toggle();
+                Flow::Continue
+            }
+        }
+    }
+
+    fn edit_details(&mut self, label: bool) -> Flow {
+        match self.selected() {
+            Row::Element { path, index } if !self.is_parallel(&path, index) => {
+                if label {
+                    Flow::Prompt(Prompt::Label(path, index))
+                } else {
+                    Flow::Prompt(Prompt::Notes(path, index))
+                }
+            }
+            _ => {
+                self.status = String::from("Select a component");
+                Flow::Continue
+            }
+        }
+    }
+
+    fn set_details(&mut self, path: Vec<usize>, index: usize, label: bool, input: &str) {
+        let mut info = match self.circuit.get_series_at(&path).and_then(|s| s.get(index)) {
+            Some(SeriesElement::Component(component)) => component.get_info().clone(),
+            Some(SeriesElement::Subcircuit(subcircuit)) => subcircuit.get_info().clone(),
+            _ => return,
+        };
+        let value = Some(String::from(input.trim())).filter(|value| !value.is_empty());
+        if label {
+            info.label = value;
+        } else {
+            info.notes = value;
+        }
+        self.apply(
+            Edit::SetInfo { path, index, info },
+            "Component details edited",
+        );
+    }
+
+    // Edits the circuit in the compact notation, or replaces it when the
+    // notation would drop some of its details
+    fn quick_entry(&self) -> Flow {
+        let losses = self.circuit.notation_losses();
+        Flow::Prompt(Prompt::Notation((!losses.is_empty()).then(|| {
+            format!(
+                "New circuit, dropping the {} of this one (Esc to keep it)",
+                losses.join(", ")
+            )
+        })))
+    }
+
+    fn snap_selected(&mut self) -> Flow {
+        match self.selected() {
+            Row::Supply => Flow::Prompt(Prompt::Snap(None)),
+            Row::Element { path, index } => {
+                match self.circuit.get_series_at(&path).and_then(|s| s.get(index)) {
+                    Some(SeriesElement::Component(
+                        component @ ElectronicComponent::Resistor(_),
+                    )) => Flow::Prompt(Prompt::Snap(component.get_id().map(String::from))),
+                    _ => {
+                        self.status =
+                            String::from("Select a resistor, or the power supply for all");
+                        Flow::Continue
+                    }
+                }
+            }
+            Row::Branch { .. } => {
+                self.status = String::from("Select a resistor, or the power supply for all");
+                Flow::Continue
+            }
+        }
+    }
+
+    fn snap(&mut self, id: Option<String>, input: &str) {
+        let input = input.trim();
+        let (series, pairs) = match input.strip_suffix('+') {
+            Some(series) => (series, true),
+            None => (input, false),
+        };
+        let report = match series
+            .parse::<ESeries>()
+            .and_then(|series| snap_resistances(&self.circuit, series, pairs, id.as_deref()))
+        {
+            Ok(report) => report,
+            Err(message) => {
+                self.status = message;
+                return;
+            }
+        };
+
+        let mut status = match report.resistors.as_slice() {
+            [resistor] => format!(
+                "{}: {} → {} ({:+.2}%)",
+                resistor.id,
+                format_value(resistor.ideal, "Ω"),
+                resistor.combination,
+                100.0 * resistor.error()
+            ),
+            resistors => format!("{} resistors snapped to {}", resistors.len(), report.series),
+        };
+        if let Some((id, delta)) = report.worst_voltage() {
+            status = format!(
+                "{}, largest voltage change {} {}{}",
+                status,
+                id,
+                if delta.change() < 0.0 { "-" } else { "+" },
+                format_value(delta.change().abs(), "V")
+            );
+        }
+        self.apply(Edit::ReplaceCircuit(report.circuit), &status);
+    }
+
+    fn toggle_explanation(&mut self) {
+        self.explanation = match self.explanation {
+            Some(_) => None,
+            None => {
+                self.status =
+                    String::from("PgUp/PgDn scroll the explanation, X writes it to Markdown");
+                Some(0)
+            }
+        };
+    }
+
+    fn scroll_explanation(&mut self, down: bool) {
+        if let Some(first) = self.explanation {
+            self.explanation = Some(if down {
+                first + EXPLANATION_PAGE
+            } else {
+                first.saturating_sub(EXPLANATION_PAGE)
+            });
+        }
+    }
+
+    fn write_explanation(&mut self, path: &str) {
+        let path = path.trim();
+        self.status = match fs::write(path, explain(&self.circuit).to_markdown()) {
+            Ok(()) => format!("Explanation written to {}", path),
+            Err(e) => format!("Couldn't write {}: {}", path, e),
+        };
+    }
+
+    fn add_branch(&mut self) -> Flow {
+        match self.selected() {
+            Row::Element { path, index } if self.is_parallel(&path, index) => {
+                Flow::Prompt(Prompt::AddBranch(path, index))
+            }
+            Row::Branch { path, index, .. } => Flow::Prompt(Prompt::AddBranch(path, index)),
+            _ => {
+                self.status = String::from("Select a parallel to add a branch to");
+                Flow::Continue
+            }
+        }
+    }
+
+    // Quits, asking first when the circuit has unsaved edits
+    fn quit(&self) -> Flow {
+        if self.has_unsaved_edits() {
+            Flow::Prompt(Prompt::Quit)
+        } else {
+            Flow::Quit
+        }
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> Flow {
+        let lines = self.lines().len();
+
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return self.quit(),
+            KeyCode::Char('z') if key.modifiers.contains(KeyModifiers::CONTROL) => self.undo(),
+            KeyCode::Char('y') if key.modifiers.contains(KeyModifiers::CONTROL) => self.redo(),
+            KeyCode::Up | KeyCode::Char('k') => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => self.cursor = (self.cursor + 1).min(lines - 1),
+            KeyCode::Left | KeyCode::Char('h') => {
+                if let Some(key) = self.selected().key() {
+                    self.collapsed.insert(key);
+                }
+            }
+            KeyCode::Right | KeyCode::Char('l') => {
+                if let Some(key) = self.selected().key() {
+                    self.collapsed.remove(&key);
+                }
+            }
+            KeyCode::Enter | KeyCode::Char('e') => return self.edit_selected(),
+            KeyCode::Char('a') => {
+                return Flow::Prompt(Prompt::Resistance(self.selected(), Placement::After))
+            }
+            KeyCode::Char('i') => {
+                return Flow::Prompt(Prompt::Resistance(self.selected(), Placement::Before))
+            }
+            KeyCode::Char('b') => return self.add_branch(),
+            KeyCode::Char('t') => return self.edit_details(true),
+            KeyCode::Char('n') => return self.edit_details(false),
+            KeyCode::Char('d') | KeyCode::Delete => self.delete_selected(),
+            KeyCode::Char('p') => self.wrap_selected(),
+            KeyCode::Char('K') => self.move_selected(true),
+            KeyCode::Char('J') => self.move_selected(false),
+            KeyCode::Char('u') => self.undo(),
+            KeyCode::Char('r') => self.redo(),
+            KeyCode::Char(':') => return self.quick_entry(),
+            KeyCode::Char('s') => return self.snap_selected(),
+            KeyCode::Char('x') => self.toggle_explanation(),
+            KeyCode::Char('X') => return Flow::Prompt(Prompt::Explanation),
+            KeyCode::PageDown => self.scroll_explanation(true),
+            KeyCode::PageUp => self.scroll_explanation(false),
+            KeyCode::Char('m') => return Flow::Menu,
+            _ => {}
+        }
+
+        Flow::Continue
+    }
+
+    // Text the prompt starts with
+    pub fn initial_input(&self, prompt: &Prompt) -> String {
+        match prompt {
+            Prompt::Notation(None) => self.circuit.to_notation(),
+            Prompt::Explanation => String::from(DEFAULT_EXPLANATION),
+            _ => String::new(),
+        }
+    }
+
+    pub fn submit(&mut self, prompt: Prompt, input: &str) -> Flow {
+        match prompt {
+            Prompt::Quit if ["y", "yes"].contains(&input.trim().to_lowercase().as_str()) => {
+                return Flow::Quit
+            }
+            Prompt::Quit => {}
+            Prompt::Label(path, index) => self.set_details(path, index, true, input),
+            Prompt::Notes(path, index) => self.set_details(path, index, false, input),
+            Prompt::Snap(id) => self.snap(id, input),
+            Prompt::Explanation => self.write_explanation(input),
+            Prompt::Notation(_)
+                if input.trim().is_empty() || input.trim() == self.circuit.to_notation() =>
+            {
+                self.status = String::from("Circuit unchanged")
+            }
+            Prompt::Notation(_) => match Circuit::from_notation(input) {
+                Ok(circuit) => {
+                    self.apply(Edit::ReplaceCircuit(circuit), "Circuit replaced");
+                }
+                Err(message) => self.status = message,
+            },
+            prompt @ (Prompt::AddBranch(..)
+            | Prompt::Resistance(_, Placement::Before | Placement::After)) => {
+                match new_component(input) {
+                    Ok(component) => self.insert_component(prompt, component),
+                    Err(message) => self.status = message,
+                }
+            }
+            prompt => match parse_value(input) {
+                Ok(value) => self.submit_value(prompt, value),
+                Err(message) => self.status = message,
+            },
+        }
+        Flow::Continue
+    }
+
+    fn submit_value(&mut self, prompt: Prompt, value: f64) {
+        match prompt {
+            Prompt::Voltage => {
+                self.apply(Edit::SetVoltage(value), "Power supply edited");
+            }
+            Prompt::Resistance(Row::Element { path, index }, Placement::Replace) => {
+                self.apply(
+                    Edit::SetResistance {
+                        path,
+                        index,
+                        resistance: value,
+                    },
+                    "Component edited",
+                );
+            }
+            // other prompts are handled by `submit`
+            _ => {}
+        }
+    }
+
+    fn insert_component(&mut self, prompt: Prompt, component: ElectronicComponent) {
+        match prompt {
+            Prompt::AddBranch(path, index) => {
+                self.apply(
+                    Edit::AddBranch {
+                        path,
+                        index,
+                        branch: vec![SeriesElement::new(component)],
+                    },
+                    "Branch added",
+                );
+            }
+            Prompt::Resistance(row, placement) => {
+                let (path, index) = match (row, placement) {
+                    (Row::Supply, _) => (vec![], 0),
+                    (Row::Element { path, index }, Placement::After) => (path, index + 1),
+                    (Row::Element { path, index }, _) => (path, index),
+                    (
+                        Row::Branch {
+                            path,
+                            index,
+                            branch,
+                        },
+                        _,
+                    ) => {
+                        let branch_path = [path.as_slice(), &[index, branch]].concat();
+                        let len = self
+                            .circuit
+                            .get_series_at(&branch_path)
+                            .map_or(0, |s| s.len());
+                        (branch_path, len)
+                    }
+                };
+                self.apply(
+                    Edit::Insert {
+                        path,
+                        index,
+                        element: SeriesElement::new(component),
+                    },
+                    "Component inserted",
+                );
+            }
+            _ => {}
+        }
+    }
+
+    fn undo(&mut self) {
+        self.status = match self.history.undo(&mut self.circuit) {
+            Some(edit) => format!("Undone: {}", edit),
+            None => String::from("Nothing to undo"),
+        };
+        self.after_edit();
+    }
+
+    fn redo(&mut self) {
+        self.status = match self.history.redo(&mut self.circuit) {
+            Some(edit) => format!("Redone: {}", edit),
+            None => String::from("Nothing to redo"),
+        };
+        self.after_edit();
+    }
+}
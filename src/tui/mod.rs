@@ -0,0 +1,120 @@
+mod app;
+mod render;
+
+use std::io::{self, stdout, Stdout};
+
+use console::Term;
+use crossterm::{
+    cursor::{Hide, Show},
+    event::{self, Event, KeyCode, KeyEventKind},
+    execute,
+    terminal::{self, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use dialoguer::theme::ColorfulTheme;
+
+use crate::views::{self, HomeReturn};
+use circuit_sim::circuit::{Circuit, History};
+
+use app::{App, Flow, Prompt};
+
+fn enter(out: &mut Stdout) -> io::Result<()> {
+    terminal::enable_raw_mode()?;
+    execute!(out, EnterAlternateScreen, Hide)
+}
+
+fn leave(out: &mut Stdout) -> io::Result<()> {
+    execute!(out, Show, LeaveAlternateScreen)?;
+    terminal::disable_raw_mode()
+}
+
+fn read_input(out: &mut Stdout, app: &App, prompt: &Prompt) -> io::Result<Option<String>> {
+    let mut input = app.initial_input(prompt);
+
+    loop {
+        render::draw(out, app, Some((prompt.label(), &input)))?;
+
+        if let Event::Key(key) = event::read()? {
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            match key.code {
+                KeyCode::Enter => return Ok(Some(input)),
+                KeyCode::Esc => return Ok(None),
+                KeyCode::Backspace => {
+                    input.pop();
+                }
+                KeyCode::Char(c) => input.push(c),
+                _ => {}
+            }
+        }
+    }
+}
+
+// Runs the classic menu pages outside of the full-screen interface
+fn open_menu(
+    out: &mut Stdout,
+    app: &mut App,
+    term: &Term,
+    theme: &ColorfulTheme,
+) -> io::Result<bool> {
+    leave(out)?;
+    let unsaved = app.has_unsaved_edits();
+    let response = views::home(&mut app.circuit, &mut app.history, unsaved, term, theme);
+    enter(out)?;
+
+    app.after_edit();
+
+    Ok(match response {
+        HomeReturn::Exit => false,
+        HomeReturn::Continue => true,
+        HomeReturn::ContinueWithMessage(message) => {
+            app.status = message;
+            true
+        }
+        HomeReturn::Synced(message) => {
+            app.saved = app.circuit.clone();
+            app.status = message;
+            true
+        }
+    })
+}
+
+pub fn run(
+    circuit: Circuit,
+    history: History,
+    term: &Term,
+    theme: &ColorfulTheme,
+) -> io::Result<()> {
+    let mut out = stdout();
+    let mut app = App::new(circuit, history);
+
+    enter(&mut out)?;
+
+    loop {
+        render::draw(&mut out, &app, None)?;
+
+        let key = match event::read()? {
+            Event::Key(key) if key.kind == KeyEventKind::Press => key,
+            _ => continue,
+        };
+
+        match app.handle_key(key) {
+            Flow::Continue => {}
+            Flow::Prompt(prompt) => {
+                if let Some(input) = read_input(&mut out, &app, &prompt)? {
+                    if let Flow::Quit = app.submit(prompt, &input) {
+                        break;
+                    }
+                }
+            }
+            Flow::Menu => {
+                if !open_menu(&mut out, &mut app, term, theme)? {
+                    break;
+                }
+            }
+            Flow::Quit => break,
+        }
+    }
+
+    leave(&mut out)
+}
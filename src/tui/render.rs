@@ -0,0 +1,298 @@
+use std::io::{self, Write};
+
+use console::style;
+use crossterm::{
+    cursor::MoveTo,
+    queue,
+    style::Print,
+    terminal::{self, Clear, ClearType},
+};
+
+use circuit_sim::{
+    analysis::{check_ratings, explain, DesignChecks, Severity, DEFAULT_MARGIN},
+    circuit::{
+        calculate_parallel_resistance, calculate_total_resistance, ElectronicComponentTrait,
+        Series, SeriesElement,
+    },
+    units::format_value,
+};
+
+use super::app::{App, Row};
+
+const HELP: &str = "↑↓ move  ←→ fold  e edit  a/i insert  b branch  t/n label/notes  p wrap  d delete  K/J move  s snap  x/X explain/export  u/r undo/redo  : quick entry  m menu  q quit";
+
+fn truncate(text: &str, width: usize) -> String {
+    text.chars().take(width).collect()
+}
+
+fn row_label(app: &App, row: &Row) -> String {
+    let fold = if app.is_collapsed(row) { "▸" } else { "▾" };
+
+    match row {
+        Row::Supply => format!("{} {}", fold, app.circuit.get_power_supply()),
+        Row::Element { path, index } => {
+            match app.circuit.get_series_at(path).and_then(|s| s.get(*index)) {
+                Some(SeriesElement::Component(component)) => format!("  {}", component),
+                Some(SeriesElement::Parallel(branches)) => {
+                    format!("{} Parallel ({} branches)", fold, branches.len())
+                }
+                Some(SeriesElement::Subcircuit(subcircuit)) => format!("{} {}", fold, subcircuit),
+                None => String::new(),
+            }
+        }
+        Row::Branch { branch, .. } => format!("{} Branch {}", fold, branch),
+    }
+}
+
+fn row_severity(app: &App, row: &Row, checks: &DesignChecks) -> Option<Severity> {
+    match row {
+        Row::Element { path, index } => {
+            match app.circuit.get_series_at(path).and_then(|s| s.get(*index)) {
+                Some(SeriesElement::Component(component)) => checks.severity(component.get_id()?),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+fn quantities(lines: &mut Vec<String>, resistance: f64, voltage: f64, current: f64) {
+    lines.push(format!("  Resistance: {}", format_value(resistance, "Ω")));
+    lines.push(format!("  Voltage:    {}", format_value(voltage, "V")));
+    lines.push(format!("  Current:    {}", format_value(current, "A")));
+    lines.push(format!(
+        "  Power:      {}",
+        format_value(voltage * current, "W")
+    ));
+}
+
+// Voltage across a series, read from the voltages of its components
+fn series_voltage(series: &Series) -> f64 {
+    series
+        .iter()
+        .map(|element| match element {
+            SeriesElement::Component(component) => component.get_tension(),
+            SeriesElement::Parallel(branches) => branches.first().map_or(0.0, series_voltage),
+            SeriesElement::Subcircuit(subcircuit) => subcircuit.get_voltage(),
+        })
+        .sum()
+}
+
+// Current through a series, the one through its first element
+fn series_current(series: &Series) -> f64 {
+    match series.first() {
+        Some(SeriesElement::Component(component)) => component.get_current(),
+        Some(SeriesElement::Parallel(branches)) => branches.iter().map(series_current).sum(),
+        Some(SeriesElement::Subcircuit(subcircuit)) => subcircuit.get_current(),
+        None => 0.0,
+    }
+}
+
+fn results(app: &App, selected: Option<&Row>) -> Vec<String> {
+    let circuit = &app.circuit;
+    let mut lines = vec![String::from("Circuit")];
+
+    quantities(
+        &mut lines,
+        calculate_total_resistance(circuit.get_series()),
+        circuit.get_power_supply().get_voltage(),
+        circuit.get_intensity(),
+    );
+    lines.push(String::new());
+
+    match selected {
+        Some(Row::Element { path, index }) => {
+            match circuit.get_series_at(path).and_then(|s| s.get(*index)) {
+                Some(SeriesElement::Component(component)) => {
+                    let info = component.get_info();
+                    lines.push(format!(
+                        "Selected {} {}",
+                        component.name().to_lowercase(),
+                        info.id.as_deref().unwrap_or_default()
+                    ));
+                    if let Some(label) = &info.label {
+                        lines.push(format!("  Label:      {}", label));
+                    }
+                    if let Some(notes) = &info.notes {
+                        lines.push(format!("  Notes:      {}", notes));
+                    }
+                    if let Some(part) = &info.part {
+                        lines.push(format!("  Part:       {}", part));
+                    }
+                    if let Some(power_rating) = info.power_rating {
+                        lines.push(format!("  Rating:     {}", format_value(power_rating, "W")));
+                    }
+                    if let Some(max_voltage) = info.max_voltage {
+                        lines.push(format!("  Max:        {}", format_value(max_voltage, "V")));
+                    }
+                    quantities(
+                        &mut lines,
+                        component.get_resistance(),
+                        component.get_tension(),
+                        component.get_current(),
+                    );
+                }
+                Some(SeriesElement::Parallel(branches)) => {
+                    let resistance = calculate_parallel_resistance(branches);
+                    let voltage = branches.first().map_or(0.0, series_voltage);
+                    let current = branches.iter().map(series_current).sum();
+                    lines.push(String::from("Selected parallel"));
+                    quantities(&mut lines, resistance, voltage, current);
+                }
+                Some(SeriesElement::Subcircuit(subcircuit)) => {
+                    lines.push(format!(
+                        "Selected subcircuit {} ({})",
+                        subcircuit.get_id().unwrap_or_default(),
+                        subcircuit.get_definition()
+                    ));
+                    quantities(
+                        &mut lines,
+                        calculate_total_resistance(subcircuit.get_series()),
+                        subcircuit.get_voltage(),
+                        subcircuit.get_current(),
+                    );
+                }
+                None => {}
+            }
+        }
+        Some(Row::Branch {
+            path,
+            index,
+            branch,
+        }) => {
+            let branch_path = [path.as_slice(), &[*index, *branch]].concat();
+            if let Some(series) = circuit.get_series_at(&branch_path) {
+                let resistance = calculate_total_resistance(series);
+                lines.push(format!("Selected branch {}", branch));
+                quantities(
+                    &mut lines,
+                    resistance,
+                    series_voltage(series),
+                    series_current(series),
+                );
+            }
+        }
+        _ => {}
+    }
+
+    lines
+}
+
+// Splits `text` into lines of at most `width` characters, breaking between
+// words
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for word in text.split_whitespace() {
+        match lines.last_mut() {
+            Some(line) if line.chars().count() + 1 + word.chars().count() <= width => {
+                line.push(' ');
+                line.push_str(word);
+            }
+            _ => lines.push(String::from(word)),
+        }
+    }
+    lines
+}
+
+// Numbered steps explaining how the circuit is solved
+fn explanation(app: &App, width: usize) -> Vec<String> {
+    let explanation = explain(&app.circuit);
+    let mut lines = Vec::new();
+    let mut title = "";
+    for (number, step) in explanation.steps.iter().enumerate() {
+        let section = if step.is_reduction() {
+            "Equivalent resistance"
+        } else {
+            "Voltages and currents"
+        };
+        if section != title {
+            if !title.is_empty() {
+                lines.push(String::new());
+            }
+            lines.push(String::from(section));
+            title = section;
+        }
+        // the lines of a step are aligned after its number
+        for (i, line) in wrap(&step.to_string(), width.saturating_sub(5))
+            .into_iter()
+            .enumerate()
+        {
+            lines.push(match i {
+                0 => format!("{:>3}. {}", number + 1, line),
+                _ => format!("     {}", line),
+            });
+        }
+    }
+    lines
+}
+
+pub fn draw(out: &mut impl Write, app: &App, prompt: Option<(&str, &str)>) -> io::Result<()> {
+    let (width, height) = terminal::size()?;
+    let (width, height) = (width as usize, height as usize);
+    let tree_width = (width / 2).max(20).min(width);
+    let body_height = height.saturating_sub(4);
+
+    queue!(out, Clear(ClearType::All), MoveTo(0, 0))?;
+    queue!(
+        out,
+        Print(style("Electronic Circuit Simulator").underlined().cyan())
+    )?;
+
+    let lines = app.lines();
+    let checks = check_ratings(&app.circuit, DEFAULT_MARGIN);
+    let scroll = app.cursor.saturating_sub(body_height.saturating_sub(1));
+
+    for (row, line) in lines.iter().enumerate().skip(scroll).take(body_height) {
+        let label = format!("{}{}", "  ".repeat(line.depth), row_label(app, &line.row));
+        let label = truncate(&label, tree_width.saturating_sub(1));
+        let y = (row - scroll + 2) as u16;
+
+        // components beyond their ratings are highlighted
+        let label = match row_severity(app, &line.row, &checks) {
+            Some(Severity::Overload) => style(label).red().bold(),
+            Some(Severity::Warning) => style(label).yellow(),
+            None => style(label),
+        };
+        if row == app.cursor {
+            queue!(out, MoveTo(0, y), Print(label.reverse()))?;
+        } else {
+            queue!(out, MoveTo(0, y), Print(label))?;
+        }
+    }
+
+    let selected = lines.get(app.cursor).map(|line| &line.row);
+    let right_width = width.saturating_sub(tree_width + 2);
+
+    for y in 2..2 + body_height {
+        queue!(out, MoveTo(tree_width as u16, y as u16), Print("│"))?;
+    }
+    let panel = match app.explanation {
+        Some(first) => {
+            let lines = explanation(app, right_width);
+            let first = first.min(lines.len().saturating_sub(body_height));
+            lines.into_iter().skip(first).collect()
+        }
+        None => results(app, selected),
+    };
+    for (i, line) in panel.iter().take(body_height).enumerate() {
+        queue!(
+            out,
+            MoveTo((tree_width + 2) as u16, (i + 2) as u16),
+            Print(truncate(line, right_width))
+        )?;
+    }
+
+    let status = match prompt {
+        Some((label, input)) => format!("{}: {}", label, input),
+        None => app.status.clone(),
+    };
+    queue!(
+        out,
+        MoveTo(0, height.saturating_sub(2) as u16),
+        Print(style(truncate(&status, width)).bold().blue()),
+        MoveTo(0, height.saturating_sub(1) as u16),
+        Print(style(truncate(HELP, width)).dim())
+    )?;
+
+    out.flush()
+}
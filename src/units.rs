@@ -0,0 +1,254 @@
+//! Engineering notation of values, such as `4k7` or `2.2MΩ`.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use schemars::{gen::SchemaGenerator, schema::Schema};
+use serde::{Deserialize, Deserializer};
+
+const DEFAULT_DIGITS: usize = 3;
+
+static SIGNIFICANT_DIGITS: AtomicUsize = AtomicUsize::new(DEFAULT_DIGITS);
+
+// SI prefixes from pico to tera, as (symbol, power of 1000)
+const PREFIXES: [(&str, i32); 9] = [
+    ("p", -4),
+    ("n", -3),
+    ("µ", -2),
+    ("m", -1),
+    ("", 0),
+    ("k", 1),
+    ("M", 2),
+    ("G", 3),
+    ("T", 4),
+];
+
+const UNITS: [&str; 6] = ["Ω", "ohms", "ohm", "V", "A", "W"];
+
+/// Number of significant digits of the displayed values, 3 by default.
+pub fn significant_digits() -> usize {
+    SIGNIFICANT_DIGITS.load(Ordering::Relaxed)
+}
+
+/// Sets the number of significant digits of the displayed values, from 1 to 15.
+pub fn set_significant_digits(digits: usize) {
+    SIGNIFICANT_DIGITS.store(digits.clamp(1, 15), Ordering::Relaxed);
+}
+
+fn multiplier(prefix: char) -> Option<f64> {
+    match prefix {
+        'p' => Some(1e-12),
+        'n' => Some(1e-9),
+        'u' | 'µ' | 'μ' => Some(1e-6),
+        'm' => Some(1e-3),
+        // "4R7" is the usual way to write 4.7Ω
+        'R' | 'r' => Some(1.0),
+        'k' | 'K' => Some(1e3),
+        'M' => Some(1e6),
+        'G' => Some(1e9),
+        'T' => Some(1e12),
+        _ => None,
+    }
+}
+
+/// Parses values such as "4k7", "2.2M", "470m", "10µ" or "4.7kΩ".
+///
+/// ```
+/// assert_eq!(circuit_sim::units::parse_value("4k7"), Ok(4700.0));
+/// ```
+pub fn parse_value(input: &str) -> Result<f64, String> {
+    let invalid = || format!("Invalid value: {}", input);
+    let mut text = input.trim();
+
+    if let Some(unit) = UNITS.iter().find(|unit| text.ends_with(*unit)) {
+        text = text[..text.len() - unit.len()].trim_end();
+    }
+
+    let value = match text.parse::<f64>() {
+        Ok(value) => value,
+        Err(_) => {
+            let (position, prefix) = text
+                .char_indices()
+                .find(|(_, c)| c.is_alphabetic())
+                .ok_or_else(invalid)?;
+            let (head, tail) = (&text[..position], &text[position + prefix.len_utf8()..]);
+
+            let number = if tail.is_empty() {
+                String::from(head)
+            } else if !head.contains('.') && tail.chars().all(|c| c.is_ascii_digit()) {
+                format!("{}.{}", head, tail)
+            } else {
+                return Err(invalid());
+            };
+
+            number.parse::<f64>().map_err(|_| invalid())?
+                * multiplier(prefix).ok_or_else(invalid)?
+        }
+    };
+
+    if value.is_finite() {
+        Ok(value)
+    } else {
+        Err(invalid())
+    }
+}
+
+/// Splits a value into a mantissa rounded to `digits` significant digits and
+/// its SI prefix.
+pub fn engineering(value: f64, digits: usize) -> (String, &'static str) {
+    if value == 0.0 || !value.is_finite() {
+        return (format!("{}", value), "");
+    }
+
+    let magnitude = value.abs().log10();
+    let power = ((magnitude / 3.0).floor() as i32).clamp(-4, 4);
+    let mut mantissa = value / 1000f64.powi(power);
+    let mut power = power;
+
+    let decimals = |mantissa: f64| {
+        let integer_digits = mantissa.abs().log10().floor() as i64 + 1;
+        (digits as i64 - integer_digits).max(0) as usize
+    };
+    let mut rounded = round_to(mantissa, decimals(mantissa));
+
+    // rounding may carry into the next prefix, as for 999.7 → 1k
+    if rounded.abs() >= 1000.0 && power < 4 {
+        power += 1;
+        mantissa /= 1000.0;
+        rounded = round_to(mantissa, decimals(mantissa));
+    }
+
+    let prefix = PREFIXES
+        .iter()
+        .find(|(_, p)| *p == power)
+        .map_or("", |(symbol, _)| symbol);
+
+    (format!("{}", rounded), prefix)
+}
+
+fn round_to(value: f64, decimals: usize) -> f64 {
+    let factor = 10f64.powi(decimals as i32);
+    (value * factor).round() / factor
+}
+
+/// Formats a value with `digits` significant digits, e.g. 4700.0 → "4.7kΩ".
+pub fn format_with_digits(value: f64, unit: &str, digits: usize) -> String {
+    let (mantissa, prefix) = engineering(value, digits);
+    format!("{}{}{}", mantissa, prefix, unit)
+}
+
+/// Formats a value with the configured number of significant digits,
+/// e.g. 4700.0 → "4.7kΩ".
+pub fn format_value(value: f64, unit: &str) -> String {
+    format_with_digits(value, unit, significant_digits())
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawValue {
+    Number(f64),
+    Text(String),
+}
+
+// Lets circuit files give values either as numbers or as strings like "4k7"
+pub(crate) fn deserialize_value<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<f64, D::Error> {
+    match RawValue::deserialize(deserializer)? {
+        RawValue::Number(value) => Ok(value),
+        RawValue::Text(text) => parse_value(&text).map_err(serde::de::Error::custom),
+    }
+}
+
+// Same as `deserialize_value`, for optional fields
+pub(crate) fn deserialize_optional_value<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<f64>, D::Error> {
+    match Option::<RawValue>::deserialize(deserializer)? {
+        Some(RawValue::Number(value)) => Ok(Some(value)),
+        Some(RawValue::Text(text)) => parse_value(&text)
+            .map(Some)
+            .map_err(serde::de::Error::custom),
+        None => Ok(None),
+    }
+}
+
+// Schema of the values accepted by `deserialize_value`
+pub(crate) fn value_schema(_: &mut SchemaGenerator) -> Schema {
+    serde_json::from_value(serde_json::json!({
+        "anyOf": [
+            { "type": "number" },
+            {
+                "type": "string",
+                "description": "Value in engineering notation, such as \"4k7\", \"2.2M\" or \"470m\""
+            }
+        ]
+    }))
+    .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parses_to(input: &str, expected: f64) {
+        let value = parse_value(input).unwrap_or_else(|error| panic!("{}", error));
+        assert!(
+            (value - expected).abs() <= 1e-12 * expected.abs(),
+            "{input} → {value}, expected {expected}"
+        );
+    }
+
+    #[test]
+    fn parses_engineering_notation() {
+        parses_to("4k7", 4700.0);
+        parses_to("2.2M", 2.2e6);
+        parses_to("470m", 0.47);
+        parses_to("10µ", 1e-5);
+        parses_to("10u", 1e-5);
+        parses_to("4R7", 4.7);
+        parses_to("100", 100.0);
+        parses_to("1e3", 1000.0);
+        parses_to("-5", -5.0);
+    }
+
+    #[test]
+    fn parses_unit_suffixes() {
+        parses_to("4.7kΩ", 4700.0);
+        parses_to("220 ohms", 220.0);
+        parses_to("1 ohm", 1.0);
+        parses_to("9V", 9.0);
+        parses_to("20mA", 0.02);
+        parses_to(" 250mW ", 0.25);
+    }
+
+    #[test]
+    fn rejects_invalid_values() {
+        for input in ["", "k", "1e3k", "inf", "NaN", "4k7k", "4.7k5", "1x", "abc"] {
+            match parse_value(input) {
+                Err(error) => assert_eq!(error, format!("Invalid value: {}", input)),
+                Ok(value) => panic!("{input:?} parsed as {value}"),
+            }
+        }
+    }
+
+    #[test]
+    fn formats_with_prefixes() {
+        assert_eq!(format_with_digits(4700.0, "Ω", 3), "4.7kΩ");
+        assert_eq!(format_with_digits(0.47, "A", 3), "470mA");
+        assert_eq!(format_with_digits(1e-5, "A", 3), "10µA");
+        assert_eq!(format_with_digits(-2.2e6, "V", 3), "-2.2MV");
+        assert_eq!(format_with_digits(4.7, "Ω", 3), "4.7Ω");
+        assert_eq!(format_with_digits(0.0, "V", 3), "0V");
+        assert_eq!(format_with_digits(1234.5, "Ω", 2), "1.2kΩ");
+    }
+
+    #[test]
+    fn carries_rounding_into_the_next_prefix() {
+        assert_eq!(engineering(999.95, 3), (String::from("1"), "k"));
+        assert_eq!(engineering(999.4, 3), (String::from("999"), ""));
+        assert_eq!(engineering(0.99996, 4), (String::from("1"), ""));
+        assert_eq!(engineering(999_950.0, 3), (String::from("1"), "M"));
+        // there is no prefix above tera to carry into
+        assert_eq!(engineering(999.95e12, 3), (String::from("1000"), "T"));
+    }
+}
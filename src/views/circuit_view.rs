@@ -1,4 +1,8 @@
-use crate::{circuit::Circuit, views::home::HomeReturn};
+use crate::views::home::HomeReturn;
+use circuit_sim::{
+    analysis::{check_ratings, DesignChecks, Severity, DEFAULT_MARGIN},
+    circuit::{Circuit, Series, SeriesElement},
+};
 use console::{style, Term};
 
 use crossterm::{
@@ -9,7 +13,47 @@ use crossterm::{
 
 use std::io::stdout;
 
-pub fn print_circuit_view(circuit: &mut Circuit, term: &Term) -> HomeReturn {
+// Prints `line`, highlighted when the element identified by `id` is beyond
+// its ratings
+fn print_checked(line: String, id: Option<&str>, checks: &DesignChecks) {
+    match id.and_then(|id| checks.severity(id)) {
+        Some(Severity::Overload) => println!("{}", style(line).red().bold()),
+        Some(Severity::Warning) => println!("{}", style(line).yellow()),
+        None => println!("{}", line),
+    }
+}
+
+// Same layout as the `Display` of circuits
+fn print_series(series: &Series, depth: usize, checks: &DesignChecks) {
+    let indent = "  ".repeat(depth);
+
+    for element in series {
+        match element {
+            SeriesElement::Component(component) => print_checked(
+                format!("{}{}", indent, component),
+                component.get_id(),
+                checks,
+            ),
+            SeriesElement::Parallel(branches) => {
+                println!("{}Parallel:", indent);
+                for (i, branch) in branches.iter().enumerate() {
+                    println!("{}  Branch {}:", indent, i);
+                    print_series(branch, depth + 2, checks);
+                }
+            }
+            SeriesElement::Subcircuit(subcircuit) => {
+                print_checked(
+                    format!("{}{}", indent, subcircuit),
+                    subcircuit.get_id(),
+                    checks,
+                );
+                print_series(subcircuit.get_series(), depth + 1, checks);
+            }
+        }
+    }
+}
+
+pub fn print_circuit_view(circuit: &Circuit, term: &Term) -> HomeReturn {
     let mut stdout = stdout();
 
     stdout
@@ -30,8 +74,15 @@ pub fn print_circuit_view(circuit: &mut Circuit, term: &Term) -> HomeReturn {
 
     println!("\n{}\n", title_styled);
 
-    circuit.update_tensions();
-    println!("{}", circuit);
+    // components beyond their ratings are highlighted, by their identifier
+    let checks = check_ratings(circuit, DEFAULT_MARGIN);
+    println!("{}", circuit.get_power_supply());
+    if let Some(temperature) = circuit.get_ambient_temperature() {
+        println!("Ambient temperature: {}°C", temperature);
+    }
+    print_series(circuit.get_series(), 0, &checks);
+
+    println!("\n{}", checks);
 
     let _ = term.read_key();
 
@@ -1,4 +1,10 @@
-use crate::circuit::{Circuit, ElectronicComponent, Series, SeriesElement};
+use circuit_sim::{
+    circuit::{
+        Circuit, ComponentInfo, Distribution, Edit, ElectronicComponent, History, Series,
+        SeriesElement,
+    },
+    units::parse_value,
+};
 
 use console::style;
 
@@ -9,9 +15,48 @@ use crossterm::{
 };
 use dialoguer::theme::ColorfulTheme;
 
-use std::io::stdout;
+use std::{fmt::Display, io::stdout};
 
 use super::HomeReturn;
+use crate::cli::parts_catalog;
+
+enum Action {
+    EditResistance,
+    EditDetails,
+    EditInstanceDetails,
+    OpenBranch,
+    AddBranch,
+    RemoveBranch,
+    InsertBefore,
+    InsertAfter,
+    MoveUp,
+    MoveDown,
+    WrapInParallel,
+    Delete,
+    Back,
+}
+
+impl Display for Action {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Action::EditResistance => write!(f, "Edit resistance"),
+            Action::EditDetails => {
+                write!(f, "Edit identifier, label, notes, tolerance and ratings")
+            }
+            Action::EditInstanceDetails => write!(f, "Edit identifier, label and notes"),
+            Action::OpenBranch => write!(f, "Open a branch"),
+            Action::AddBranch => write!(f, "Add a branch"),
+            Action::RemoveBranch => write!(f, "Remove a branch"),
+            Action::InsertBefore => write!(f, "Insert a component before"),
+            Action::InsertAfter => write!(f, "Insert a component after"),
+            Action::MoveUp => write!(f, "Move up"),
+            Action::MoveDown => write!(f, "Move down"),
+            Action::WrapInParallel => write!(f, "Wrap into a parallel"),
+            Action::Delete => write!(f, "Delete"),
+            Action::Back => write!(f, "Back"),
+        }
+    }
+}
 
 fn clear_terminal() {
     let mut stdout = stdout();
@@ -25,60 +70,459 @@ fn clear_terminal() {
         .expect("Unable to replace console cursor");
 }
 
-fn edit_component(component: &mut ElectronicComponent) -> HomeReturn {
-    let resistance = dialoguer::Input::<f64>::new()
-        .with_prompt("Enter the resistance Ω")
-        .interact()
-        .unwrap();
+fn edit_result(result: Result<(), String>, success: &str) -> HomeReturn {
+    match result {
+        Ok(_) => HomeReturn::ContinueWithMessage(String::from(success)),
+        Err(message) => HomeReturn::ContinueWithMessage(message),
+    }
+}
 
-    component.set_resistance(resistance);
+// Reads a value written in engineering notation, such as "4k7"
+fn prompt_value(prompt: &str, default: Option<f64>, theme: &ColorfulTheme) -> f64 {
+    let mut input = dialoguer::Input::<String>::with_theme(theme);
+    input = input
+        .with_prompt(prompt)
+        .validate_with(|text: &String| parse_value(text).map(|_| ()));
+    if let Some(default) = default {
+        input = input.default(default.to_string());
+    }
 
-    HomeReturn::ContinueWithMessage(String::from("Component edited successfully"))
+    parse_value(&input.interact_text().unwrap()).unwrap()
 }
 
-fn render_series(series: &mut Series, theme: &ColorfulTheme) -> HomeReturn {
-    clear_terminal();
-    let select = dialoguer::Select::with_theme(theme)
-        .with_prompt("Select a component to edit")
-        .items(series)
-        .default(0)
+// Empty answers remove the value
+fn prompt_optional_value(prompt: &str, initial: Option<f64>, theme: &ColorfulTheme) -> Option<f64> {
+    let text = dialoguer::Input::<String>::with_theme(theme)
+        .with_prompt(prompt)
+        .with_initial_text(initial.map(|value| value.to_string()).unwrap_or_default())
+        .allow_empty(true)
+        .validate_with(|text: &String| match text.trim() {
+            "" => Ok(()),
+            text => parse_value(text).map(|_| ()),
+        })
+        .interact_text()
+        .unwrap();
+
+    parse_value(text.trim()).ok()
+}
+
+// Asked only for components with a tolerance
+fn prompt_distribution(initial: Option<Distribution>, theme: &ColorfulTheme) -> Distribution {
+    let distributions = [Distribution::Uniform, Distribution::Normal];
+    let selection = dialoguer::Select::with_theme(theme)
+        .with_prompt("Distribution within the tolerance")
+        .items(&distributions)
+        .default(usize::from(initial == Some(Distribution::Normal)))
         .interact()
         .unwrap();
 
-    let selected_series = &mut series[select];
+    distributions[selection]
+}
+
+fn prompt_resistance(theme: &ColorfulTheme) -> f64 {
+    prompt_value("Enter the resistance Ω", None, theme)
+}
+
+// Searches the parts library until a part is picked; None to go back
+fn prompt_part(theme: &ColorfulTheme) -> Option<ElectronicComponent> {
+    loop {
+        let query = dialoguer::Input::<String>::with_theme(theme)
+            .with_prompt("Search the parts (part number, description, value)")
+            .allow_empty(true)
+            .interact_text()
+            .unwrap();
+        let parts = match parts_catalog() {
+            Ok(catalog) => catalog.search(&query),
+            Err(message) => {
+                println!("{}", message);
+                return None;
+            }
+        };
+        if parts.is_empty() {
+            println!("No matching part");
+            continue;
+        }
 
-    match selected_series {
-        SeriesElement::Component(component) => edit_component(component),
-        SeriesElement::Parallel(series) => render_select_parallel(series.as_mut(), theme),
+        let mut items: Vec<String> = parts.iter().map(|part| part.to_string()).collect();
+        items.push(String::from("Search again"));
+        items.push(String::from("Back"));
+        let selection = dialoguer::Select::with_theme(theme)
+            .with_prompt("Select a part")
+            .items(&items)
+            .default(0)
+            .max_length(15)
+            .interact()
+            .unwrap();
+
+        match parts.get(selection) {
+            Some(part) => match part.component() {
+                Ok(component) => return Some(component),
+                Err(message) => println!("{}", message),
+            },
+            None if selection == parts.len() => continue,
+            None => return None,
+        }
     }
 }
 
-fn render_select_parallel(series: &mut Vec<Series>, theme: &ColorfulTheme) -> HomeReturn {
-    clear_terminal();
+fn prompt_component(theme: &ColorfulTheme) -> ElectronicComponent {
+    // a library that can't be loaded leaves only resistors to add
+    if parts_catalog().is_ok_and(|catalog| !catalog.parts.is_empty()) {
+        let choice = dialoguer::Select::with_theme(theme)
+            .with_prompt("Component to add")
+            .items(&["Resistor", "Part from the parts library"])
+            .default(0)
+            .interact()
+            .unwrap();
+        if choice == 1 {
+            if let Some(component) = prompt_part(theme) {
+                return component;
+            }
+        }
+    }
+    ElectronicComponent::new_resistor(prompt_resistance(theme))
+}
 
-    let select = dialoguer::Select::with_theme(theme)
-        .with_prompt("Select a component to edit")
+// Empty answers remove the field
+fn prompt_text(prompt: &str, initial: Option<&str>, theme: &ColorfulTheme) -> Option<String> {
+    let text = dialoguer::Input::<String>::with_theme(theme)
+        .with_prompt(prompt)
+        .with_initial_text(initial.unwrap_or_default())
+        .allow_empty(true)
+        .interact_text()
+        .unwrap();
+    let text = text.trim();
+
+    (!text.is_empty()).then(|| String::from(text))
+}
+
+fn select_branch(branches: &[Series], prompt: &str, theme: &ColorfulTheme) -> usize {
+    dialoguer::Select::with_theme(theme)
+        .with_prompt(prompt)
         .items(
-            &series
+            &branches
                 .iter()
                 .enumerate()
-                .map(|(i, s)| format!("Branche {}: ({}...)", i, s.first().unwrap()))
+                .map(|(i, s)| match s.first() {
+                    Some(first) => format!("Branch {}: ({}...)", i, first),
+                    None => format!("Branch {}: ()", i),
+                })
                 .collect::<Vec<String>>(),
         )
         .default(0)
         .interact()
+        .unwrap()
+}
+
+fn apply_action(
+    circuit: &mut Circuit,
+    history: &mut History,
+    path: &[usize],
+    index: usize,
+    action: &Action,
+    theme: &ColorfulTheme,
+) -> HomeReturn {
+    match action {
+        Action::EditResistance => {
+            let resistance = prompt_resistance(theme);
+            edit_result(
+                history.apply(
+                    circuit,
+                    Edit::SetResistance {
+                        path: path.to_vec(),
+                        index,
+                        resistance,
+                    },
+                ),
+                "Component edited successfully",
+            )
+        }
+        Action::EditInstanceDetails => {
+            let info = match circuit.get_series_at(path).and_then(|s| s.get(index)) {
+                Some(SeriesElement::Subcircuit(subcircuit)) => subcircuit.get_info(),
+                _ => return HomeReturn::Continue,
+            };
+            // tolerances and ratings belong to the elements of the definition
+            let info = ComponentInfo {
+                id: prompt_text("Identifier", info.id.as_deref(), theme),
+                label: prompt_text("Label", info.label.as_deref(), theme),
+                notes: prompt_text("Notes", info.notes.as_deref(), theme),
+                ..info.clone()
+            };
+            edit_result(
+                history.apply(
+                    circuit,
+                    Edit::SetInfo {
+                        path: path.to_vec(),
+                        index,
+                        info,
+                    },
+                ),
+                "Subcircuit details edited successfully",
+            )
+        }
+        Action::EditDetails => {
+            let info = match circuit.get_series_at(path).and_then(|s| s.get(index)) {
+                Some(SeriesElement::Component(component)) => component.get_info(),
+                _ => return HomeReturn::Continue,
+            };
+            let info = ComponentInfo {
+                id: prompt_text("Identifier", info.id.as_deref(), theme),
+                label: prompt_text("Label", info.label.as_deref(), theme),
+                notes: prompt_text("Notes", info.notes.as_deref(), theme),
+                tolerance: prompt_optional_value("Tolerance %", info.tolerance, theme),
+                temperature_coefficient: prompt_optional_value(
+                    "Temperature coefficient ppm/°C",
+                    info.temperature_coefficient,
+                    theme,
+                ),
+                power_rating: prompt_optional_value("Power rating W", info.power_rating, theme),
+                max_voltage: prompt_optional_value("Maximum voltage V", info.max_voltage, theme),
+                ..info.clone()
+            };
+            let info = ComponentInfo {
+                distribution: info
+                    .tolerance
+                    .map(|_| prompt_distribution(info.distribution, theme)),
+                ..info
+            };
+            edit_result(
+                history.apply(
+                    circuit,
+                    Edit::SetInfo {
+                        path: path.to_vec(),
+                        index,
+                        info,
+                    },
+                ),
+                "Component details edited successfully",
+            )
+        }
+        Action::OpenBranch => {
+            let branch = match circuit.get_series_at(path).and_then(|s| s.get(index)) {
+                Some(SeriesElement::Parallel(branches)) => {
+                    select_branch(branches, "Select a branch to open", theme)
+                }
+                _ => return HomeReturn::Continue,
+            };
+            let mut branch_path = path.to_vec();
+            branch_path.extend([index, branch]);
+            render_series(circuit, history, &branch_path, theme)
+        }
+        Action::AddBranch => {
+            let component = prompt_component(theme);
+            edit_result(
+                history.apply(
+                    circuit,
+                    Edit::AddBranch {
+                        path: path.to_vec(),
+                        index,
+                        branch: vec![SeriesElement::new(component)],
+                    },
+                ),
+                "Branch added successfully",
+            )
+        }
+        Action::RemoveBranch => {
+            let branch = match circuit.get_series_at(path).and_then(|s| s.get(index)) {
+                Some(SeriesElement::Parallel(branches)) => {
+                    select_branch(branches, "Select a branch to remove", theme)
+                }
+                _ => return HomeReturn::Continue,
+            };
+            edit_result(
+                history.apply(
+                    circuit,
+                    Edit::RemoveBranch {
+                        path: path.to_vec(),
+                        index,
+                        branch,
+                    },
+                ),
+                "Branch removed successfully",
+            )
+        }
+        Action::InsertBefore | Action::InsertAfter => {
+            let component = prompt_component(theme);
+            let position = match action {
+                Action::InsertAfter => index + 1,
+                _ => index,
+            };
+            edit_result(
+                history.apply(
+                    circuit,
+                    Edit::Insert {
+                        path: path.to_vec(),
+                        index: position,
+                        element: SeriesElement::new(component),
+                    },
+                ),
+                "Component inserted successfully",
+            )
+        }
+        Action::MoveUp => match index.checked_sub(1) {
+            Some(target) => edit_result(
+                history.apply(
+                    circuit,
+                    Edit::Move {
+                        path: path.to_vec(),
+                        index,
+                        target,
+                    },
+                ),
+                "Element moved successfully",
+            ),
+            None => {
+                HomeReturn::ContinueWithMessage(String::from("The element can't be moved further"))
+            }
+        },
+        Action::MoveDown => edit_result(
+            history.apply(
+                circuit,
+                Edit::Move {
+                    path: path.to_vec(),
+                    index,
+                    target: index + 1,
+                },
+            ),
+            "Element moved successfully",
+        ),
+        Action::WrapInParallel => {
+            let count = dialoguer::Input::<usize>::with_theme(theme)
+                .with_prompt("Number of elements to wrap, starting from this one")
+                .default(1)
+                .interact()
+                .unwrap();
+            edit_result(
+                history.apply(
+                    circuit,
+                    Edit::WrapInParallel {
+                        path: path.to_vec(),
+                        index,
+                        count,
+                    },
+                ),
+                "Elements wrapped into a parallel, add a branch to it",
+            )
+        }
+        Action::Delete => edit_result(
+            history.apply(
+                circuit,
+                Edit::Remove {
+                    path: path.to_vec(),
+                    index,
+                },
+            ),
+            "Element deleted successfully",
+        ),
+        Action::Back => HomeReturn::Continue,
+    }
+}
+
+fn render_element(
+    circuit: &mut Circuit,
+    history: &mut History,
+    path: &[usize],
+    index: usize,
+    theme: &ColorfulTheme,
+) -> HomeReturn {
+    clear_terminal();
+
+    let actions = match circuit.get_series_at(path).and_then(|s| s.get(index)) {
+        Some(SeriesElement::Component(_)) => vec![
+            Action::EditResistance,
+            Action::EditDetails,
+            Action::InsertBefore,
+            Action::InsertAfter,
+            Action::MoveUp,
+            Action::MoveDown,
+            Action::WrapInParallel,
+            Action::Delete,
+            Action::Back,
+        ],
+        Some(SeriesElement::Parallel(_)) => vec![
+            Action::OpenBranch,
+            Action::AddBranch,
+            Action::RemoveBranch,
+            Action::InsertBefore,
+            Action::InsertAfter,
+            Action::MoveUp,
+            Action::MoveDown,
+            Action::WrapInParallel,
+            Action::Delete,
+            Action::Back,
+        ],
+        Some(SeriesElement::Subcircuit(_)) => vec![
+            Action::EditInstanceDetails,
+            Action::InsertBefore,
+            Action::InsertAfter,
+            Action::MoveUp,
+            Action::MoveDown,
+            Action::WrapInParallel,
+            Action::Delete,
+            Action::Back,
+        ],
+        None => return HomeReturn::Continue,
+    };
+
+    let select = dialoguer::Select::with_theme(theme)
+        .with_prompt("Select an action")
+        .items(&actions)
+        .default(0)
+        .interact()
         .unwrap();
-    let selected_series = &mut series[select];
 
-    render_series(selected_series, theme)
+    apply_action(circuit, history, path, index, &actions[select], theme)
 }
 
-pub fn edit_component_view(circuit: &mut Circuit, theme: &ColorfulTheme) -> HomeReturn {
-    if circuit.get_series().is_empty() {
-        println!("No components in the circuit");
-        return HomeReturn::ContinueWithMessage(String::from("No components in the circuit"));
+fn render_series(
+    circuit: &mut Circuit,
+    history: &mut History,
+    path: &[usize],
+    theme: &ColorfulTheme,
+) -> HomeReturn {
+    clear_terminal();
+
+    let mut items: Vec<String> = match circuit.get_series_at(path) {
+        Some(series) => series.iter().map(|element| element.to_string()).collect(),
+        None => return HomeReturn::Continue,
+    };
+    let len = items.len();
+    items.push(String::from("+ Add a component at the end"));
+    items.push(Action::Back.to_string());
+
+    let select = dialoguer::Select::with_theme(theme)
+        .with_prompt("Select an element to edit")
+        .items(&items)
+        .default(0)
+        .interact()
+        .unwrap();
+
+    if select == len {
+        let component = prompt_component(theme);
+        edit_result(
+            history.apply(
+                circuit,
+                Edit::Insert {
+                    path: path.to_vec(),
+                    index: len,
+                    element: SeriesElement::new(component),
+                },
+            ),
+            "Component added successfully",
+        )
+    } else if select > len {
+        HomeReturn::Continue
+    } else {
+        render_element(circuit, history, path, select, theme)
     }
+}
 
+pub fn edit_component_view(
+    circuit: &mut Circuit,
+    history: &mut History,
+    theme: &ColorfulTheme,
+) -> HomeReturn {
     let mut stdout = stdout();
 
     stdout
@@ -101,7 +545,30 @@ pub fn edit_component_view(circuit: &mut Circuit, theme: &ColorfulTheme) -> Home
 
     println!("\n{}\n", title_styled);
 
-    let series = circuit.get_mut_series();
+    render_series(circuit, history, &[], theme)
+}
+
+pub fn edit_power_supply_view(
+    circuit: &mut Circuit,
+    history: &mut History,
+    theme: &ColorfulTheme,
+) -> HomeReturn {
+    clear_terminal();
+
+    let title = String::from("Edit Power Supply");
+    let title_styled: console::StyledObject<&str> =
+        style(title.as_str()).bold().underlined().green();
+
+    println!("\n{}\n", title_styled);
+
+    let voltage = prompt_value(
+        "Enter the voltage V",
+        Some(circuit.get_power_supply().get_voltage()),
+        theme,
+    );
 
-    render_series(series, theme)
+    edit_result(
+        history.apply(circuit, Edit::SetVoltage(voltage)),
+        "Power supply edited successfully",
+    )
 }
@@ -8,12 +8,16 @@ use crossterm::{
 use dialoguer::{theme::ColorfulTheme, Input};
 use std::io::stdout;
 
-use crate::{circuit::Circuit, views::HomeReturn};
-use std::fs::File;
-use std::io::prelude::*;
+use crate::views::HomeReturn;
+use circuit_sim::circuit::{Circuit, FileFormat, History};
 use std::path::Path;
 
-pub fn get_from_json_view(circuit: &mut Circuit, term: &Term, theme: &ColorfulTheme) -> HomeReturn {
+pub fn get_from_json_view(
+    circuit: &mut Circuit,
+    history: &mut History,
+    term: &Term,
+    theme: &ColorfulTheme,
+) -> HomeReturn {
     let mut stdout = stdout();
 
     stdout
@@ -24,49 +28,30 @@ pub fn get_from_json_view(circuit: &mut Circuit, term: &Term, theme: &ColorfulTh
         .execute(MoveTo(0, 0))
         .expect("Unable to replace console cursor");
 
-    let title = String::from("Get From JSON");
+    let title = String::from("Open Circuit File");
     let title_styled: console::StyledObject<&str> =
         style(title.as_str()).bold().underlined().green();
 
     println!("\n{}\n", title_styled);
 
-    let json_file_path = Input::<String>::with_theme(theme)
-        .with_prompt("Enter the path to the JSON file")
-        .interact_on(&term)
-        .expect("Couldn't get the JSON file path");
+    let file_path = Input::<String>::with_theme(theme)
+        .with_prompt("Enter the path to the JSON, YAML or TOML file")
+        .interact_on(term)
+        .expect("Couldn't get the file path");
 
-    let path = Path::new(&json_file_path);
+    let format = FileFormat::from_path(&file_path);
 
-    let file = File::open(&path);
-
-    let mut json_string = String::new();
-
-    if file.is_err() {
+    if !Path::new(&file_path).is_file() {
         return HomeReturn::ContinueWithMessage(String::from("Couldn't open the file"));
-    } else {
-        let mut file = file.unwrap();
+    }
 
-        match file.read_to_string(&mut json_string) {
-            Err(_) => {
-                return HomeReturn::ContinueWithMessage(String::from("Couldn't read the file"));
-            }
-            Ok(_) => {
-                let circuit_from_json: Result<Circuit, _> = serde_json::from_str(&json_string);
-                match circuit_from_json {
-                    Err(_) => {
-                        return HomeReturn::ContinueWithMessage(String::from(
-                            "Couldn't parse the JSON",
-                        ));
-                    }
-                    Ok(circuit_from_json) => {
-                        *circuit = circuit_from_json;
-                        circuit.update();
-                        return HomeReturn::ContinueWithMessage(String::from(
-                            "Circuit loaded from JSON",
-                        ));
-                    }
-                }
-            }
+    // read through the path, so that subcircuit libraries are found next to it
+    match Circuit::from_file(&file_path) {
+        Err(e) => HomeReturn::ContinueWithMessage(format!("Couldn't parse the {}: {}", format, e)),
+        Ok(circuit_from_file) => {
+            *circuit = circuit_from_file;
+            history.clear();
+            HomeReturn::Synced(format!("Circuit loaded from {}", format))
         }
     }
 }
@@ -0,0 +1,76 @@
+use crate::views::HomeReturn;
+use circuit_sim::circuit::{Circuit, History};
+use console::{style, Term};
+
+use crossterm::{
+    cursor::MoveTo,
+    terminal::{Clear, ClearType},
+    ExecutableCommand,
+};
+use dialoguer::{theme::ColorfulTheme, Select};
+
+use std::io::stdout;
+
+pub fn undo_view(circuit: &mut Circuit, history: &mut History) -> HomeReturn {
+    match history.undo(circuit) {
+        Some(edit) => HomeReturn::ContinueWithMessage(format!("Undone: {}", edit)),
+        None => HomeReturn::ContinueWithMessage(String::from("Nothing to undo")),
+    }
+}
+
+pub fn redo_view(circuit: &mut Circuit, history: &mut History) -> HomeReturn {
+    match history.redo(circuit) {
+        Some(edit) => HomeReturn::ContinueWithMessage(format!("Redone: {}", edit)),
+        None => HomeReturn::ContinueWithMessage(String::from("Nothing to redo")),
+    }
+}
+
+pub fn history_view(
+    circuit: &mut Circuit,
+    history: &mut History,
+    term: &Term,
+    theme: &ColorfulTheme,
+) -> HomeReturn {
+    let mut stdout = stdout();
+
+    stdout
+        .execute(Clear(ClearType::All))
+        .expect("Unable to clear console");
+
+    stdout
+        .execute(MoveTo(0, 0))
+        .expect("Unable to replace console cursor");
+
+    let title = String::from("Edit History");
+    let title_styled: console::StyledObject<&str> =
+        style(title.as_str()).bold().underlined().green();
+
+    println!("\n{}\n", title_styled);
+
+    let mut count = 0;
+    for (i, edit) in history.done().enumerate() {
+        println!("{}. {}", i + 1, edit);
+        count += 1;
+    }
+    for edit in history.undone() {
+        println!("{}", style(format!("   {} (undone)", edit)).dim());
+        count += 1;
+    }
+    if count == 0 {
+        println!("No edits yet");
+    }
+    println!();
+
+    let selection = Select::with_theme(theme)
+        .with_prompt("Select an option")
+        .default(0)
+        .items(&["Undo", "Redo", "Back"])
+        .interact_on_opt(term)
+        .unwrap();
+
+    match selection {
+        Some(0) => undo_view(circuit, history),
+        Some(1) => redo_view(circuit, history),
+        _ => HomeReturn::Continue,
+    }
+}
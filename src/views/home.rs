@@ -1,11 +1,20 @@
 use crate::{
-    circuit::Circuit,
     utils::print_header,
     views::{
-        circuit_view, edit_component::edit_component_view, get_from_json::get_from_json_view,
-        pages_enum::Pages, save_as_json::save_as_json_view,
+        circuit_view,
+        edit_component::{edit_component_view, edit_power_supply_view},
+        get_from_json::get_from_json_view,
+        history_view::{history_view, redo_view, undo_view},
+        pages_enum::Pages,
+        quick_entry::quick_entry_view,
+        save_as_circuitikz::save_as_circuitikz_view,
+        save_as_json::save_as_json_view,
     },
 };
+use circuit_sim::{
+    circuit::{Circuit, History},
+    units::format_value,
+};
 
 use crossterm::{
     cursor::MoveTo,
@@ -14,20 +23,23 @@ use crossterm::{
 };
 use std::io::stdout;
 
-use console::{style, Term};
-use dialoguer::{theme::ColorfulTheme, Select};
+use console::Term;
+use dialoguer::{theme::ColorfulTheme, Confirm, Select};
 
 pub enum HomeReturn {
     Exit,
     Continue,
     ContinueWithMessage(String),
+    // the circuit now matches a file, after loading or saving it
+    Synced(String),
 }
 
 pub fn home(
     circuit: &mut Circuit,
+    history: &mut History,
+    unsaved: bool,
     term: &Term,
     theme: &ColorfulTheme,
-    message: Option<String>,
 ) -> HomeReturn {
     let mut stdout = stdout();
 
@@ -42,19 +54,24 @@ pub fn home(
     let mut title = String::from("Home");
 
     if !circuit.get_series().is_empty() {
-        title.push_str(format!(" - CircuitInfo (Intensity").as_str());
-        title.push_str(
-            format!(": {} A)", (circuit.get_intensity() / 100.0).round() * 100.0).as_str(),
-        );
+        title.push_str(" - CircuitInfo (Intensity: ");
+        title.push_str(&format_value(circuit.get_intensity(), "A"));
+        title.push(')');
     }
 
-    print_header(&title, &message.unwrap_or_else(|| String::from("")));
+    print_header(&title);
 
     let menu: Vec<Pages> = vec![
         Pages::PrintCircuit,
         Pages::GetFromJson,
         Pages::EditComponent,
+        Pages::QuickEntry,
+        Pages::EditPowerSupply,
+        Pages::Undo,
+        Pages::Redo,
+        Pages::History,
         Pages::SaveAsJson,
+        Pages::SaveAsCircuitikz,
         Pages::Exit,
     ];
 
@@ -62,34 +79,46 @@ pub fn home(
         .with_prompt("Select an option")
         .default(0)
         .items(&menu)
-        .interact_on_opt(&term)
+        .interact_on_opt(term)
         .unwrap();
 
-    match selection {
-        Some(0) => {
+    match selection.map(|index| &menu[index]) {
+        Some(Pages::PrintCircuit) => {
             println!("Print Circuit");
 
-            return circuit_view::print_circuit_view(circuit, term);
-        }
-        Some(1) => {
-            return get_from_json_view(circuit, term, theme);
+            circuit_view::print_circuit_view(circuit, term)
         }
-        Some(2) => {
+        Some(Pages::GetFromJson) => get_from_json_view(circuit, history, term, theme),
+        Some(Pages::EditComponent) => {
             println!("Edit Component");
 
-            return edit_component_view(circuit, theme);
+            edit_component_view(circuit, history, theme)
         }
-        Some(3) => {
+        Some(Pages::QuickEntry) => quick_entry_view(circuit, history, term, theme),
+        Some(Pages::EditPowerSupply) => edit_power_supply_view(circuit, history, theme),
+        Some(Pages::Undo) => undo_view(circuit, history),
+        Some(Pages::Redo) => redo_view(circuit, history),
+        Some(Pages::History) => history_view(circuit, history, term, theme),
+        Some(Pages::SaveAsJson) => {
             println!("Save As JSON");
             save_as_json_view(circuit, term, theme);
-            return circuit_view::print_circuit_view(circuit, term);
+            circuit_view::print_circuit_view(circuit, term);
+            HomeReturn::Synced(String::from("Circuit saved"))
         }
-        Some(4) => {
+        Some(Pages::SaveAsCircuitikz) => save_as_circuitikz_view(circuit, term, theme),
+        Some(Pages::Exit) => {
             println!("Exit");
-            return HomeReturn::Exit;
-        }
-        _ => {
-            return HomeReturn::Exit;
+            let quit = !unsaved
+                || Confirm::with_theme(theme)
+                    .with_prompt("The circuit has unsaved edits, quit anyway?")
+                    .default(false)
+                    .interact_on(term)
+                    .unwrap();
+            match quit {
+                true => HomeReturn::Exit,
+                false => HomeReturn::Continue,
+            }
         }
+        None => HomeReturn::Continue,
     }
 }
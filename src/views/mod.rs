@@ -1,8 +1,11 @@
 mod circuit_view;
 mod edit_component;
 mod get_from_json;
+mod history_view;
 mod home;
 mod pages_enum;
+mod quick_entry;
+mod save_as_circuitikz;
 mod save_as_json;
 pub use home::home;
 pub use home::HomeReturn;
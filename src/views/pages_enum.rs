@@ -4,7 +4,13 @@ pub enum Pages {
     PrintCircuit,
     GetFromJson,
     EditComponent,
+    QuickEntry,
+    EditPowerSupply,
+    Undo,
+    Redo,
+    History,
     SaveAsJson,
+    SaveAsCircuitikz,
     Exit,
 }
 
@@ -12,9 +18,15 @@ impl Display for Pages {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Pages::PrintCircuit => write!(f, "Print Circuit"),
-            Pages::GetFromJson => write!(f, "Get From JSON"),
-            Pages::EditComponent => write!(f, "Edit And Show Component"),
-            Pages::SaveAsJson => write!(f, "Save As JSON"),
+            Pages::GetFromJson => write!(f, "Open (JSON, YAML, TOML)"),
+            Pages::EditComponent => write!(f, "Edit Circuit"),
+            Pages::QuickEntry => write!(f, "Quick Entry"),
+            Pages::EditPowerSupply => write!(f, "Edit Power Supply"),
+            Pages::Undo => write!(f, "Undo"),
+            Pages::Redo => write!(f, "Redo"),
+            Pages::History => write!(f, "Edit History"),
+            Pages::SaveAsJson => write!(f, "Save As (JSON, YAML, TOML)"),
+            Pages::SaveAsCircuitikz => write!(f, "Save As LaTeX (circuitikz)"),
             Pages::Exit => write!(f, "Exit"),
         }
     }
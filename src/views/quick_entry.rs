@@ -0,0 +1,73 @@
+use console::{style, Term};
+use crossterm::{
+    cursor::MoveTo,
+    terminal::{Clear, ClearType},
+    ExecutableCommand,
+};
+use dialoguer::{theme::ColorfulTheme, Input};
+
+use std::io::stdout;
+
+use crate::views::HomeReturn;
+use circuit_sim::circuit::{Circuit, Edit, History};
+
+pub fn quick_entry_view(
+    circuit: &mut Circuit,
+    history: &mut History,
+    term: &Term,
+    theme: &ColorfulTheme,
+) -> HomeReturn {
+    let mut stdout = stdout();
+
+    stdout
+        .execute(Clear(ClearType::All))
+        .expect("Unable to clear console");
+
+    stdout
+        .execute(MoveTo(0, 0))
+        .expect("Unable to replace console cursor");
+
+    let title_styled = style("Quick Entry").bold().underlined().green();
+
+    println!("\n{}\n", title_styled);
+    println!("Write the circuit as \"12V: R10 + R30 + (R20 + R20 || R320 + R20)\"\n");
+
+    // the circuit is only offered for editing when the notation holds all of it
+    let losses = circuit.notation_losses();
+    let mut input = Input::<String>::with_theme(theme);
+    if losses.is_empty() {
+        input = input.with_initial_text(circuit.to_notation());
+    } else {
+        println!(
+            "{}\n",
+            style(format!(
+                "The new circuit replaces this one, dropping its {}",
+                losses.join(", ")
+            ))
+            .yellow()
+        );
+    }
+    let notation = input
+        .with_prompt("Circuit (empty to keep this one)")
+        .allow_empty(true)
+        .validate_with(|text: &String| match text.trim() {
+            "" => Ok(()),
+            text => Circuit::from_notation(text).map(|_| ()),
+        })
+        .interact_text_on(term)
+        .expect("Couldn't get the circuit");
+
+    if notation.trim().is_empty() || notation.trim() == circuit.to_notation() {
+        return HomeReturn::ContinueWithMessage(String::from("Circuit unchanged"));
+    }
+
+    let replacement = match Circuit::from_notation(&notation) {
+        Ok(replacement) => replacement,
+        Err(message) => return HomeReturn::ContinueWithMessage(message),
+    };
+
+    match history.apply(circuit, Edit::ReplaceCircuit(replacement)) {
+        Ok(_) => HomeReturn::ContinueWithMessage(String::from("Circuit replaced")),
+        Err(message) => HomeReturn::ContinueWithMessage(message),
+    }
+}
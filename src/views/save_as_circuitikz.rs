@@ -0,0 +1,58 @@
+use crate::views::HomeReturn;
+use circuit_sim::{circuit::Circuit, export::circuitikz::to_circuitikz};
+
+use crossterm::{
+    cursor::MoveTo,
+    terminal::{Clear, ClearType},
+    ExecutableCommand,
+};
+
+use console::{style, Term};
+
+use dialoguer::{theme::ColorfulTheme, Confirm, Input};
+
+pub fn save_as_circuitikz_view(
+    circuit: &Circuit,
+    term: &Term,
+    theme: &ColorfulTheme,
+) -> HomeReturn {
+    let mut stdout = std::io::stdout();
+
+    stdout
+        .execute(Clear(ClearType::All))
+        .expect("Unable to clear console");
+
+    stdout
+        .execute(MoveTo(0, 0))
+        .expect("Unable to replace console cursor");
+
+    let app_title: console::StyledObject<&str> = style("Electronic Circuit Simulator")
+        .bold()
+        .underlined()
+        .green();
+    println!("\n{}", app_title);
+
+    let title = String::from("Save Circuit as LaTeX (circuitikz)");
+    let title_styled: console::StyledObject<&str> =
+        style(title.as_str()).bold().underlined().green();
+
+    println!("\n{}\n", title_styled);
+
+    let file_name = Input::<String>::with_theme(theme)
+        .with_prompt("Enter the path to the .tex file")
+        .interact_on(term)
+        .unwrap();
+
+    let annotate = Confirm::with_theme(theme)
+        .with_prompt("Annotate computed voltages and currents?")
+        .default(true)
+        .interact_on(term)
+        .unwrap();
+
+    let tex = to_circuitikz(circuit, annotate);
+
+    match std::fs::write(file_name, tex) {
+        Ok(_) => HomeReturn::ContinueWithMessage(String::from("Circuit saved as LaTeX")),
+        Err(_) => HomeReturn::ContinueWithMessage(String::from("Couldn't write the file")),
+    }
+}
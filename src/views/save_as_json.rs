@@ -1,4 +1,4 @@
-use crate::circuit::Circuit;
+use circuit_sim::circuit::{Circuit, FileFormat};
 
 use crossterm::{
     cursor::MoveTo,
@@ -27,18 +27,21 @@ pub fn save_as_json_view(circuit: &Circuit, term: &Term, theme: &ColorfulTheme)
         .green();
     println!("\n{}", app_title);
 
-    let title = String::from("Save Circuit as JSON");
+    let title = String::from("Save Circuit");
     let title_styled: console::StyledObject<&str> =
         style(title.as_str()).bold().underlined().green();
 
     println!("\n{}\n", title_styled);
 
     let file_name = Input::<String>::with_theme(theme)
-        .with_prompt("Enter the path to the JSON file")
-        .interact_on(&term)
+        .with_prompt("Enter the path to the JSON, YAML or TOML file")
+        .interact_on(term)
         .unwrap();
 
-    let json = circuit.to_json();
+    let format = FileFormat::from_path(&file_name);
+    let content = circuit
+        .to_format(format)
+        .expect("Unable to serialize the circuit");
 
-    std::fs::write(file_name, json).expect("Unable to write file");
+    std::fs::write(file_name, content).expect("Unable to write file");
 }
@@ -0,0 +1,53 @@
+// Guards the public API: every item below must stay reachable at its path,
+// and the library must stay usable through it alone. Internals kept out of
+// the API are checked by the `compile_fail` examples of `PrivateApi` in
+// src/lib.rs.
+
+#[allow(unused_imports)]
+use circuit_sim::{
+    analysis::{
+        check_ratings, diff, explain, monte_carlo, sensitivity, snap_resistances, solve,
+        synthesize, temperature_sweep, Change, CircuitDiff, Combination, ComponentDiff,
+        ComponentEntry, ComponentSolution, Corner, Delta, DesignChecks, ESeries, Explanation,
+        ExplanationStep, Measure, MonteCarloReport, Probe, ProbeSamples, ProbeSweep, Rating,
+        RatingIssue, Relation, ResistorSensitivity, SensitivityReport, Severity, SnapReport,
+        SnappedResistor, Solution, StepKind, SubcircuitSolution, SynthesisReport,
+        SynthesizedResistor, Target, TargetResult, TemperatureSweep, TextChange, WorstCase,
+        DEFAULT_MARGIN, EXHAUSTIVE_CORNERS_LIMIT, HISTOGRAM_BINS,
+    },
+    circuit::{
+        calculate_current, calculate_parallel_resistance, calculate_total_resistance, is_valid_id,
+        json_schema, register_component, registered_components, value_parameter, Battery, Catalog,
+        Circuit, CircuitBuilder, CloneModel, ComponentFactory, ComponentInfo, ComponentModel,
+        Distribution, Edit, ElectronicComponent, ElectronicComponentTrait, FileFormat, History,
+        Led, ParallelBuilder, Part, PluginComponent, PowerSupply, Resistor, Series, SeriesBuilder,
+        SeriesElement, Subcircuit, SubcircuitDefinition, FORMAT_VERSION, REFERENCE_TEMPERATURE,
+    },
+    export::{circuitikz::to_circuitikz, dot::to_dot, svg::to_svg},
+    units::{
+        engineering, format_value, format_with_digits, parse_value, set_significant_digits,
+        significant_digits,
+    },
+};
+
+#[test]
+fn builds_edits_and_saves_circuits_through_the_public_api() {
+    let mut circuit = CircuitBuilder::new()
+        .supply(parse_value("9V").unwrap())
+        .resistor(1000.0)
+        .parallel(|p| {
+            p.branch(|b| b.resistor(4000.0))
+                .branch(|b| b.resistor(4000.0))
+        })
+        .build();
+    assert_eq!(solve(&circuit).component("R1").unwrap().voltage, 3.0);
+
+    let mut history = History::new();
+    history.apply(&mut circuit, Edit::SetVoltage(12.0)).unwrap();
+    assert_eq!(calculate_current(&circuit), 4e-3);
+
+    let text = circuit.to_format(FileFormat::Toml).unwrap();
+    let loaded = Circuit::from_format(&text, FileFormat::Toml).unwrap();
+    assert!(diff(&circuit, &loaded).is_empty());
+    assert!(to_svg(&loaded).starts_with("<svg"));
+}